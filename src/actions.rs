@@ -1,13 +1,51 @@
 use crate::args::{ArgumentResolver, ALIAS_ARGS_NAME};
 use crate::config::RawCommandConfigVariant::Shorthand;
-use crate::config::{ActionConfig, AliasActionConfig, ExecutionConfigVariant};
-use crate::exec::{CommandExecutor, ExecutionError, ExitStatus};
-use crate::variables::{substitute_variables, VariableMap};
+use crate::config::{
+    ActionConfig, AliasActionConfig, AssertActionConfig, BashCommandConfig, ExecutionConfigVariant,
+    MultiActionConfig, RawCommandConfig, RawCommandConfigVariant, Shell, ShellCommandConfigVariant,
+    SingleActionConfig, StdinConfig, WriteFileActionConfig,
+};
+use crate::exec::{is_shebang_script, CommandExecutor, ExecutionError, ExitStatus};
+use crate::opener::Opener;
+use crate::script::command_text;
+use crate::variables::{
+    render_template, shell_quote, substitute_variables, TemplateError, VariableMap,
+};
+use colored::Colorize;
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use std::{fs, io};
 use thiserror::Error;
 
+/// The variable containing the path of the command that ran, for `on_success` and `on_failure`.
+pub const COMMAND_VARIABLE_NAME: &str = "DINGUS_COMMAND";
+
+/// The variable containing the exit code of the command that ran, for `on_success` and
+/// `on_failure`.
+pub const EXIT_CODE_VARIABLE_NAME: &str = "DINGUS_EXIT_CODE";
+
 pub struct ActionExecutor {
     pub command_executor: Box<dyn CommandExecutor>,
     pub arg_resolver: Box<dyn ArgumentResolver>,
+
+    /// When set to `true`, step banners aren't printed.
+    pub quiet: bool,
+
+    pub opener: Box<dyn Opener>,
+
+    /// When set, the time taken by each step is recorded here.
+    /// This is shared so that every executor used for a single run records to the same list.
+    pub timings: Option<Rc<RefCell<Vec<StepTiming>>>>,
+}
+
+/// How long a single step took to execute.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepTiming {
+    /// The step's banner, or its command if it doesn't have one.
+    pub name: String,
+    pub duration: Duration,
 }
 
 impl ActionExecutor {
@@ -23,10 +61,93 @@ impl ActionExecutor {
             }
 
             ActionConfig::MultiStep(multi_command_action) => {
+                if multi_command_action.shared_shell {
+                    let shared_action = shared_shell_action(&multi_command_action.actions)?;
+                    return self.execute_actions(vec![shared_action], variables);
+                }
+
                 self.execute_actions(multi_command_action.actions.clone(), variables)
             }
 
             ActionConfig::Alias(alias_action) => self.execute_alias(alias_action, variables),
+
+            // These are run by parsing the arguments again for the command they point to
+            ActionConfig::CommandAlias(command_alias_action) => {
+                Err(ActionError::UnresolvedCommandAlias {
+                    command: command_alias_action.alias.command.clone(),
+                })
+            }
+
+            ActionConfig::WriteFile(write_file_action) => {
+                self.execute_write_file(write_file_action, variables)
+            }
+
+            ActionConfig::Open(open_action) => {
+                let target = substitute_variables(&open_action.target, variables);
+                self.opener.open(&target).map_err(|err| ActionError::Open {
+                    target,
+                    source: err,
+                })
+            }
+
+            ActionConfig::Assert(assert_action) => self.execute_assert(assert_action, variables),
+        }
+    }
+
+    /// Executes the `pre_run` actions, then `run`, then the `post_run` actions.
+    /// The `post_run` actions are executed even if `run` fails, but nothing else is executed if
+    /// any of the `pre_run` actions fail.
+    /// The first error is returned.
+    pub fn execute_with_hooks<F, E>(
+        &self,
+        pre_run: &[ExecutionConfigVariant],
+        post_run: &[ExecutionConfigVariant],
+        variables: &VariableMap,
+        run: F,
+    ) -> Result<(), E>
+    where
+        F: FnOnce() -> Result<(), E>,
+        E: From<ActionError>,
+    {
+        self.execute_actions(pre_run.to_vec(), variables)?;
+
+        let result = run();
+        let post_run_result = self.execute_actions(post_run.to_vec(), variables);
+
+        result?;
+        post_run_result?;
+        Ok(())
+    }
+
+    /// Executes `on_success` or `on_failure`, depending on the `exit_code` of the command at
+    /// `command_path`.
+    /// The command path and exit code are available as the `DINGUS_COMMAND` and `DINGUS_EXIT_CODE`
+    /// variables.
+    /// These only notify about the result, so any failures are printed rather than returned.
+    pub fn execute_result_hook(
+        &self,
+        on_success: &Option<ExecutionConfigVariant>,
+        on_failure: &Option<ExecutionConfigVariant>,
+        command_path: &[String],
+        exit_code: i32,
+        variables: &VariableMap,
+    ) {
+        let (hook_name, hook) = if exit_code == 0 {
+            ("on_success", on_success)
+        } else {
+            ("on_failure", on_failure)
+        };
+        let Some(hook) = hook else {
+            return;
+        };
+
+        let mut variables = variables.clone();
+        variables.insert(COMMAND_VARIABLE_NAME.to_string(), command_path.join(" "));
+        variables.insert(EXIT_CODE_VARIABLE_NAME.to_string(), exit_code.to_string());
+
+        if let Err(err) = self.execute_actions(vec![hook.clone()], &variables) {
+            let message = format!("warning: {hook_name} failed: {err}");
+            eprintln!("{}", message.yellow());
         }
     }
 
@@ -36,7 +157,21 @@ impl ActionExecutor {
         variables: &VariableMap,
     ) -> Result<(), ActionError> {
         for (idx, execution_config) in exec_configs.iter().enumerate() {
+            if !self.quiet {
+                if let Some(banner) = step_banner(execution_config, variables) {
+                    eprintln!("{}", banner.cyan().bold());
+                }
+            }
+
+            let started_at = Instant::now();
             let result = self.command_executor.execute(&execution_config, &variables);
+            if let Some(timings) = &self.timings {
+                timings.borrow_mut().push(StepTiming {
+                    name: step_banner(execution_config, variables)
+                        .unwrap_or_else(|| command_text(execution_config)),
+                    duration: started_at.elapsed(),
+                });
+            }
 
             match result {
                 Ok(status) => {
@@ -87,6 +222,282 @@ impl ActionExecutor {
 
         return Ok(());
     }
+
+    fn execute_write_file(
+        &self,
+        write_file_action_config: &WriteFileActionConfig,
+        variables: &VariableMap,
+    ) -> Result<(), ActionError> {
+        let path = substitute_variables(write_file_action_config.path.as_str(), variables);
+        let content = render_template(
+            write_file_action_config.content.as_str(),
+            &write_file_action_config.template,
+            variables,
+        )
+        .map_err(|err| ActionError::Template {
+            path: path.clone(),
+            source: err,
+        })?;
+
+        let write = || -> io::Result<()> {
+            if let Some(parent) = Path::new(&path).parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            fs::write(&path, content)
+        };
+
+        write().map_err(|err| ActionError::WriteFile { path, source: err })
+    }
+
+    fn execute_assert(
+        &self,
+        assert_action_config: &AssertActionConfig,
+        variables: &VariableMap,
+    ) -> Result<(), ActionError> {
+        let check = ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+            BashCommandConfig {
+                working_directory: None,
+                command: assert_action_config.assert.command.clone(),
+                run_as: None,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                allow_extra_args: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
+            },
+        ));
+
+        let status = self
+            .command_executor
+            .execute(&check, variables)
+            .map_err(|err| ActionError::Execution {
+                index: 0,
+                source: err,
+            })?;
+
+        match status {
+            ExitStatus::Success => Ok(()),
+            _ => Err(ActionError::AssertionFailed {
+                message: substitute_variables(&assert_action_config.assert.message, variables),
+            }),
+        }
+    }
+}
+
+/// Appends `args` to the command of the last step in the provided [`ActionConfig`].
+/// Aliases already receive their own arguments, and file writes and assertions have no command to
+/// append to, so they're left unchanged.
+/// The `default_shell` is needed to tell whether shorthand commands are run by a shell.
+pub fn with_passthrough_args(
+    action_config: &ActionConfig,
+    args: &[String],
+    default_shell: &Option<Shell>,
+) -> ActionConfig {
+    match action_config {
+        ActionConfig::SingleStep(single_action) => ActionConfig::SingleStep(SingleActionConfig {
+            action: append_args(&single_action.action, args, default_shell),
+        }),
+
+        ActionConfig::MultiStep(multi_action) => {
+            let mut actions = multi_action.actions.clone();
+            if let Some(last_action) = actions.last_mut() {
+                *last_action = append_args(last_action, args, default_shell);
+            }
+
+            ActionConfig::MultiStep(MultiActionConfig {
+                actions,
+                ..multi_action.clone()
+            })
+        }
+
+        ActionConfig::Alias(_)
+        | ActionConfig::CommandAlias(_)
+        | ActionConfig::WriteFile(_)
+        | ActionConfig::Open(_)
+        | ActionConfig::Assert(_) => action_config.clone(),
+    }
+}
+
+/// Whether any step in the provided [`ActionConfig`] accepts extra arguments.
+pub fn accepts_extra_args(action_config: &ActionConfig) -> bool {
+    match action_config {
+        ActionConfig::SingleStep(single_action) => allows_extra_args(&single_action.action),
+        ActionConfig::MultiStep(multi_action) => multi_action.actions.iter().any(allows_extra_args),
+        ActionConfig::Alias(_)
+        | ActionConfig::CommandAlias(_)
+        | ActionConfig::WriteFile(_)
+        | ActionConfig::Open(_)
+        | ActionConfig::Assert(_) => false,
+    }
+}
+
+/// Appends `args` to the command of every step in the provided [`ActionConfig`] that allows extra
+/// arguments.
+pub fn with_extra_args(action_config: &ActionConfig, args: &[String]) -> ActionConfig {
+    // Only bash commands allow extra arguments, so the default shell doesn't matter
+    let append_if_allowed = |exec_config: &ExecutionConfigVariant| {
+        if allows_extra_args(exec_config) {
+            append_args(exec_config, args, &None)
+        } else {
+            exec_config.clone()
+        }
+    };
+
+    match action_config {
+        ActionConfig::SingleStep(single_action) => ActionConfig::SingleStep(SingleActionConfig {
+            action: append_if_allowed(&single_action.action),
+        }),
+
+        ActionConfig::MultiStep(multi_action) => ActionConfig::MultiStep(MultiActionConfig {
+            actions: multi_action.actions.iter().map(append_if_allowed).collect(),
+            ..multi_action.clone()
+        }),
+
+        ActionConfig::Alias(_)
+        | ActionConfig::CommandAlias(_)
+        | ActionConfig::WriteFile(_)
+        | ActionConfig::Open(_)
+        | ActionConfig::Assert(_) => action_config.clone(),
+    }
+}
+
+fn allows_extra_args(exec_config: &ExecutionConfigVariant) -> bool {
+    matches!(
+        exec_config,
+        ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(bash_config))
+            if bash_config.allow_extra_args
+    )
+}
+
+fn append_args(
+    exec_config: &ExecutionConfigVariant,
+    args: &[String],
+    default_shell: &Option<Shell>,
+) -> ExecutionConfigVariant {
+    // Quoted so that the shell passes them through as-is
+    let quoted_args: Vec<String> = args.iter().map(|arg| shell_quote(arg)).collect();
+
+    match exec_config {
+        // Scripts with a shebang aren't run by bash, so the args are passed to their interpreter
+        // as-is instead of being appended to the script
+        ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(bash_config))
+            if is_shebang_script(&bash_config.command) =>
+        {
+            ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+                BashCommandConfig {
+                    extra_args: [bash_config.extra_args.as_slice(), args].concat(),
+                    ..bash_config.clone()
+                },
+            ))
+        }
+
+        ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(bash_config)) => {
+            ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+                BashCommandConfig {
+                    command: format!("{} {}", bash_config.command, quoted_args.join(" ")),
+                    ..bash_config.clone()
+                },
+            ))
+        }
+
+        ExecutionConfigVariant::RawCommand(Shorthand(command)) if default_shell.is_some() => {
+            ExecutionConfigVariant::RawCommand(Shorthand(format!(
+                "{} {}",
+                command,
+                quoted_args.join(" ")
+            )))
+        }
+
+        // Raw commands aren't run by a shell, so the args are kept apart from the command to stop
+        // them from being split or having variables substituted into them
+        ExecutionConfigVariant::RawCommand(Shorthand(command)) => {
+            ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::RawCommandConfig(
+                RawCommandConfig {
+                    command: command.clone(),
+                    extra_args: args.to_vec(),
+                    ..Default::default()
+                },
+            ))
+        }
+
+        ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::RawCommandConfig(
+            raw_config,
+        )) => ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::RawCommandConfig(
+            RawCommandConfig {
+                extra_args: [raw_config.extra_args.as_slice(), args].concat(),
+                ..raw_config.clone()
+            },
+        )),
+    }
+}
+
+/// The banner to print before executing the provided step, with variables substituted, if any.
+fn step_banner(exec_config: &ExecutionConfigVariant, variables: &VariableMap) -> Option<String> {
+    let banner = match exec_config {
+        ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(bash_config)) => {
+            bash_config.banner.as_ref()
+        }
+        ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::RawCommandConfig(
+            raw_config,
+        )) => raw_config.banner.as_ref(),
+        ExecutionConfigVariant::RawCommand(Shorthand(_)) => None,
+    };
+
+    banner.map(|banner| substitute_variables(banner, variables))
+}
+
+/// Formats the provided [`StepTiming`]s as a summary with a line for each step, in the order they
+/// were executed.
+pub fn format_timings(timings: &[StepTiming]) -> Vec<String> {
+    timings
+        .iter()
+        .enumerate()
+        .map(|(index, timing)| {
+            format!(
+                "{:>3}. {:>10}  {}",
+                index + 1,
+                format!("{:.2?}", timing.duration),
+                timing.name
+            )
+        })
+        .collect()
+}
+
+/// Combines the provided actions into a single bash action which stops at the first failure.
+/// Fails if any of the actions set a field that can't be combined into a bash script.
+fn shared_shell_action(
+    exec_configs: &[ExecutionConfigVariant],
+) -> Result<ExecutionConfigVariant, ActionError> {
+    for (index, exec_config) in exec_configs.iter().enumerate() {
+        if let Some(field) = exec_config.shared_shell_unsupported_field() {
+            return Err(ActionError::UnsupportedInSharedShell { index, field });
+        }
+    }
+
+    let mut lines = vec!["set -e".to_string()];
+    lines.extend(exec_configs.iter().map(command_text));
+
+    Ok(ExecutionConfigVariant::ShellCommand(
+        ShellCommandConfigVariant::Bash(BashCommandConfig {
+            working_directory: None,
+            command: lines.join("\n"),
+            run_as: None,
+            stdout_file: None,
+            stderr_file: None,
+            append: false,
+            allow_extra_args: false,
+            banner: None,
+            stdin: StdinConfig::Inherit,
+            label: None,
+            max_output_bytes: None,
+            extra_args: vec![],
+        }),
+    ))
 }
 
 #[derive(Error, Debug)]
@@ -100,17 +511,42 @@ pub enum ActionError {
     // TODO: Reconsider whether a non-zero exit codes should be treated as errors
     #[error("failed to execute action {index}: {status}")]
     StatusCode { index: usize, status: ExitStatus },
+
+    #[error("failed to write file {path}")]
+    WriteFile { path: String, source: io::Error },
+
+    #[error("failed to render content for file {path}")]
+    Template { path: String, source: TemplateError },
+
+    #[error("failed to open {target}")]
+    Open { target: String, source: io::Error },
+
+    #[error("assertion failed: {message}")]
+    AssertionFailed { message: String },
+
+    #[error("action {index} can't set {field} when using shared_shell")]
+    UnsupportedInSharedShell { index: usize, field: &'static str },
+
+    #[error("alias for command \"{command}\" can only be run directly")]
+    UnresolvedCommandAlias { command: String },
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{AssertConfig, OpenActionConfig};
+    use crate::opener::MockOpener;
     use crate::{
         args::MockArgumentResolver,
-        config::{MultiActionConfig, RawCommandConfigVariant, SingleActionConfig},
-        exec::MockCommandExecutor,
+        config::{DingusOptions, TemplateEngine},
+        exec::{
+            create_command_executor, create_command_executor_with_deadline, ExecutionError,
+            MockCommandExecutor, SearchPath,
+        },
     };
     use mockall::{predicate::eq, Sequence};
+    use std::time::{Duration, Instant};
+    use tempfile::TempDir;
 
     #[test]
     fn execute_single_step() {
@@ -145,6 +581,9 @@ mod tests {
         let action_executor = ActionExecutor {
             command_executor: Box::new(command_executor),
             arg_resolver: Box::new(arg_resolver),
+            quiet: false,
+            opener: Box::new(MockOpener::new()),
+            timings: None,
         };
 
         let result = action_executor.execute(&action, &variables.clone());
@@ -198,11 +637,15 @@ mod tests {
                     command_text_3.to_string(),
                 )),
             ],
+            shared_shell: false,
         });
 
         let action_executor = ActionExecutor {
             command_executor: Box::new(command_executor),
             arg_resolver: Box::new(arg_resolver),
+            quiet: false,
+            opener: Box::new(MockOpener::new()),
+            timings: None,
         };
 
         let result = action_executor.execute(&action, &variables.clone());
@@ -211,6 +654,128 @@ mod tests {
         assert!(result.is_ok())
     }
 
+    #[test]
+    fn execute_multi_step_records_timings() {
+        // Arrange
+        let commands = ["cargo build", "cargo test", "cargo doc"];
+
+        let mut command_executor = MockCommandExecutor::new();
+        command_executor
+            .expect_execute()
+            .times(commands.len())
+            .returning(|_, _| Ok(ExitStatus::Success));
+
+        let action = ActionConfig::MultiStep(MultiActionConfig {
+            actions: commands.iter().map(|command| shorthand(command)).collect(),
+            shared_shell: false,
+        });
+
+        let timings = Rc::new(RefCell::new(Vec::new()));
+        let action_executor = ActionExecutor {
+            command_executor: Box::new(command_executor),
+            arg_resolver: Box::new(MockArgumentResolver::new()),
+            quiet: false,
+            opener: Box::new(MockOpener::new()),
+            timings: Some(timings.clone()),
+        };
+
+        // Act
+        let result = action_executor.execute(&action, &VariableMap::new());
+
+        // Assert
+        assert!(result.is_ok());
+        let timings = timings.borrow();
+        assert_eq!(timings.len(), commands.len());
+        for (timing, command) in timings.iter().zip(commands) {
+            assert_eq!(timing.name, command);
+            assert!(timing.duration >= Duration::ZERO);
+        }
+        assert_eq!(format_timings(&timings).len(), commands.len());
+    }
+
+    #[test]
+    fn execute_multi_step_with_shared_shell_preserves_working_directory() {
+        // Arrange
+        let temp_dir = TempDir::new().unwrap();
+        let nested_dir = temp_dir.path().join("nested");
+        fs::create_dir(&nested_dir).unwrap();
+
+        let action = ActionConfig::MultiStep(MultiActionConfig {
+            actions: vec![
+                ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(format!(
+                    "cd {}",
+                    nested_dir.display()
+                ))),
+                ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+                    BashCommandConfig {
+                        working_directory: None,
+                        command: "pwd > pwd.txt".to_string(),
+                        run_as: None,
+                        stdout_file: None,
+                        stderr_file: None,
+                        append: false,
+                        allow_extra_args: false,
+                        banner: None,
+                        stdin: StdinConfig::Inherit,
+                        label: None,
+                        max_output_bytes: None,
+                        extra_args: vec![],
+                    },
+                )),
+            ],
+            shared_shell: true,
+        });
+
+        let action_executor = ActionExecutor {
+            command_executor: create_command_executor(&DingusOptions::default(), &None, &[]),
+            arg_resolver: Box::new(MockArgumentResolver::new()),
+            quiet: false,
+            opener: Box::new(MockOpener::new()),
+            timings: None,
+        };
+
+        // Act
+        let result = action_executor.execute(&action, &VariableMap::new());
+
+        // Assert
+        assert!(result.is_ok());
+        let pwd = fs::read_to_string(nested_dir.join("pwd.txt")).unwrap();
+        assert_eq!(pwd.trim_end(), nested_dir.to_str().unwrap());
+    }
+
+    #[test]
+    fn execute_multi_step_with_shared_shell_rejects_banners() {
+        // Arrange
+        let action: ActionConfig = serde_yaml::from_str(
+            "shared_shell: true
+actions:
+  - echo Building
+  - command: echo Publishing
+    banner: Publishing...",
+        )
+        .unwrap();
+
+        let action_executor = ActionExecutor {
+            command_executor: Box::new(MockCommandExecutor::new()),
+            arg_resolver: Box::new(MockArgumentResolver::new()),
+            quiet: false,
+            opener: Box::new(MockOpener::new()),
+            timings: None,
+        };
+
+        // Act
+        let result = action_executor.execute(&action, &VariableMap::new());
+
+        // Assert
+        assert!(matches!(
+            result,
+            Err(ActionError::UnsupportedInSharedShell {
+                index: 1,
+                field: "banner"
+            })
+        ));
+    }
+
     #[test]
     fn execute_alias() {
         // Arrange
@@ -247,6 +812,9 @@ mod tests {
         let action_executor = ActionExecutor {
             command_executor: Box::new(command_executor),
             arg_resolver: Box::new(arg_resolver),
+            quiet: false,
+            opener: Box::new(MockOpener::new()),
+            timings: None,
         };
 
         let result = action_executor.execute(&action, &variables.clone());
@@ -254,4 +822,620 @@ mod tests {
         // Assert
         assert!(result.is_ok())
     }
+
+    #[test]
+    fn execute_passes_through_args_to_last_step() {
+        // Arrange
+        let variables = VariableMap::new();
+
+        let mut command_executor = MockCommandExecutor::new();
+        let mut seq = Sequence::new();
+        command_executor
+            .expect_execute()
+            .once()
+            .in_sequence(&mut seq)
+            .with(
+                eq(ExecutionConfigVariant::RawCommand(
+                    RawCommandConfigVariant::Shorthand("cargo build".to_string()),
+                )),
+                eq(variables.clone()),
+            )
+            .returning(|_, _| Ok(ExitStatus::Success));
+        command_executor
+            .expect_execute()
+            .once()
+            .in_sequence(&mut seq)
+            .with(
+                eq(ExecutionConfigVariant::ShellCommand(
+                    ShellCommandConfigVariant::Bash(BashCommandConfig {
+                        working_directory: None,
+                        command: "cargo test '--' '--nocapture' 'it'\\''s'".to_string(),
+                        run_as: None,
+                        stdout_file: None,
+                        stderr_file: None,
+                        append: false,
+                        allow_extra_args: false,
+                        banner: None,
+                        stdin: StdinConfig::Inherit,
+                        label: None,
+                        max_output_bytes: None,
+                        extra_args: vec![],
+                    }),
+                )),
+                eq(variables.clone()),
+            )
+            .returning(|_, _| Ok(ExitStatus::Success));
+
+        let action = ActionConfig::MultiStep(MultiActionConfig {
+            actions: vec![
+                ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(
+                    "cargo build".to_string(),
+                )),
+                ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+                    BashCommandConfig {
+                        working_directory: None,
+                        command: "cargo test".to_string(),
+                        run_as: None,
+                        stdout_file: None,
+                        stderr_file: None,
+                        append: false,
+                        allow_extra_args: false,
+                        banner: None,
+                        stdin: StdinConfig::Inherit,
+                        label: None,
+                        max_output_bytes: None,
+                        extra_args: vec![],
+                    },
+                )),
+            ],
+            shared_shell: false,
+        });
+
+        let action_executor = ActionExecutor {
+            command_executor: Box::new(command_executor),
+            arg_resolver: Box::new(MockArgumentResolver::new()),
+            quiet: false,
+            opener: Box::new(MockOpener::new()),
+            timings: None,
+        };
+
+        // Act
+        let action = with_passthrough_args(
+            &action,
+            &[
+                "--".to_string(),
+                "--nocapture".to_string(),
+                "it's".to_string(),
+            ],
+            &None,
+        );
+        let result = action_executor.execute(&action, &variables);
+
+        // Assert
+        assert!(result.is_ok())
+    }
+
+    #[test]
+    fn with_passthrough_args_keeps_raw_command_args_separate() {
+        // Arrange
+        let action = ActionConfig::SingleStep(SingleActionConfig {
+            action: ExecutionConfigVariant::RawCommand(Shorthand("echo".to_string())),
+        });
+        let args = ["a b".to_string(), "$name".to_string()];
+
+        // Act
+        let raw_action = with_passthrough_args(&action, &args, &None);
+        let shell_action = with_passthrough_args(&action, &args, &Some(Shell::Bash));
+
+        // Assert
+        assert_eq!(
+            raw_action,
+            ActionConfig::SingleStep(SingleActionConfig {
+                action: ExecutionConfigVariant::RawCommand(
+                    RawCommandConfigVariant::RawCommandConfig(RawCommandConfig {
+                        command: "echo".to_string(),
+                        extra_args: args.to_vec(),
+                        ..Default::default()
+                    })
+                ),
+            })
+        );
+        assert_eq!(
+            shell_action,
+            ActionConfig::SingleStep(SingleActionConfig {
+                action: ExecutionConfigVariant::RawCommand(Shorthand(
+                    "echo 'a b' '$name'".to_string()
+                )),
+            })
+        );
+    }
+
+    #[test]
+    fn with_extra_args_appends_quoted_args_to_allowed_steps() {
+        // Arrange
+        let bash_step = |command: &str, allow_extra_args: bool| {
+            ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+                BashCommandConfig {
+                    working_directory: None,
+                    command: command.to_string(),
+                    run_as: None,
+                    stdout_file: None,
+                    stderr_file: None,
+                    append: false,
+                    allow_extra_args,
+                    banner: None,
+                    stdin: StdinConfig::Inherit,
+                    label: None,
+                    max_output_bytes: None,
+                    extra_args: vec![],
+                },
+            ))
+        };
+        let action = ActionConfig::MultiStep(MultiActionConfig {
+            actions: vec![
+                bash_step("cargo build", false),
+                bash_step("cargo run", true),
+            ],
+            shared_shell: false,
+        });
+
+        // Act
+        let accepts_args = accepts_extra_args(&action);
+        let action = with_extra_args(&action, &["--release".to_string(), "a b".to_string()]);
+
+        // Assert
+        assert!(accepts_args);
+        assert_eq!(
+            action,
+            ActionConfig::MultiStep(MultiActionConfig {
+                actions: vec![
+                    bash_step("cargo build", false),
+                    bash_step("cargo run '--release' 'a b'", true)
+                ],
+                shared_shell: false,
+            })
+        );
+    }
+
+    #[test]
+    fn with_extra_args_keeps_args_out_of_shebang_scripts() {
+        // Arrange
+        let script = "#!/usr/bin/env python3\nimport sys\nprint(sys.argv)";
+        let bash_step = |extra_args: Vec<String>| {
+            ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+                BashCommandConfig {
+                    working_directory: None,
+                    command: script.to_string(),
+                    run_as: None,
+                    stdout_file: None,
+                    stderr_file: None,
+                    append: false,
+                    allow_extra_args: true,
+                    banner: None,
+                    stdin: StdinConfig::Inherit,
+                    label: None,
+                    max_output_bytes: None,
+                    extra_args,
+                },
+            ))
+        };
+        let action = ActionConfig::SingleStep(SingleActionConfig {
+            action: bash_step(vec![]),
+        });
+
+        // Act
+        let action = with_extra_args(&action, &["--release".to_string()]);
+
+        // Assert
+        assert_eq!(
+            action,
+            ActionConfig::SingleStep(SingleActionConfig {
+                action: bash_step(vec!["--release".to_string()]),
+            })
+        );
+    }
+
+    #[test]
+    fn execute_write_file() {
+        // Arrange
+        let mut variables = VariableMap::new();
+        variables.insert("name".to_string(), "Dingus".to_string());
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("nested").join("config.txt");
+
+        let command_executor = MockCommandExecutor::new();
+        let arg_resolver = MockArgumentResolver::new();
+
+        // Act
+        let action = ActionConfig::WriteFile(WriteFileActionConfig {
+            path: path.to_str().unwrap().to_string(),
+            content: "Hello, $name!".to_string(),
+            template: TemplateEngine::Simple,
+        });
+
+        let action_executor = ActionExecutor {
+            command_executor: Box::new(command_executor),
+            arg_resolver: Box::new(arg_resolver),
+            quiet: false,
+            opener: Box::new(MockOpener::new()),
+            timings: None,
+        };
+
+        let result = action_executor.execute(&action, &variables);
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(fs::read_to_string(path).unwrap(), "Hello, Dingus!");
+    }
+
+    #[test]
+    #[cfg(feature = "tera")]
+    fn execute_write_file_renders_tera_template() {
+        // Arrange
+        let mut variables = VariableMap::new();
+        variables.insert("environment".to_string(), "prod".to_string());
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.txt");
+
+        let command_executor = MockCommandExecutor::new();
+        let arg_resolver = MockArgumentResolver::new();
+
+        // Act
+        let action = ActionConfig::WriteFile(WriteFileActionConfig {
+            path: path.to_str().unwrap().to_string(),
+            content: "{% if environment == \"prod\" %}replicas: 3{% else %}replicas: 1{% endif %}"
+                .to_string(),
+            template: TemplateEngine::Tera,
+        });
+
+        let action_executor = ActionExecutor {
+            command_executor: Box::new(command_executor),
+            arg_resolver: Box::new(arg_resolver),
+            quiet: false,
+            opener: Box::new(MockOpener::new()),
+            timings: None,
+        };
+
+        let result = action_executor.execute(&action, &variables);
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(fs::read_to_string(path).unwrap(), "replicas: 3");
+    }
+
+    #[test]
+    fn execute_write_file_returns_error_on_failure() {
+        // Arrange
+        let temp_dir = TempDir::new().unwrap();
+
+        let command_executor = MockCommandExecutor::new();
+        let arg_resolver = MockArgumentResolver::new();
+
+        // Act
+        // Writing to a directory will always fail.
+        let action = ActionConfig::WriteFile(WriteFileActionConfig {
+            path: temp_dir.path().to_str().unwrap().to_string(),
+            content: "Hello!".to_string(),
+            template: TemplateEngine::Simple,
+        });
+
+        let action_executor = ActionExecutor {
+            command_executor: Box::new(command_executor),
+            arg_resolver: Box::new(arg_resolver),
+            quiet: false,
+            opener: Box::new(MockOpener::new()),
+            timings: None,
+        };
+
+        let result = action_executor.execute(&action, &VariableMap::new());
+
+        // Assert
+        assert!(matches!(result, Err(ActionError::WriteFile { .. })));
+    }
+
+    #[test]
+    fn step_banner_substitutes_variables() {
+        // Arrange
+        let exec_config = ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+            BashCommandConfig {
+                working_directory: None,
+                command: "docker build .".to_string(),
+                run_as: None,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                allow_extra_args: false,
+                banner: Some("Building $image...".to_string()),
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
+            },
+        ));
+        let mut variables = VariableMap::new();
+        variables.insert("image".to_string(), "dingus".to_string());
+
+        // Act
+        let banner = step_banner(&exec_config, &variables);
+        let shorthand_banner = step_banner(
+            &ExecutionConfigVariant::RawCommand(Shorthand("docker build .".to_string())),
+            &variables,
+        );
+
+        // Assert
+        assert_eq!(banner, Some("Building dingus...".to_string()));
+        assert_eq!(shorthand_banner, None);
+    }
+
+    fn shorthand(command_text: &str) -> ExecutionConfigVariant {
+        ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(
+            command_text.to_string(),
+        ))
+    }
+
+    #[test]
+    fn execute_with_hooks_runs_post_run_when_command_fails() {
+        // Arrange
+        let mut seq = Sequence::new();
+        let mut command_executor = MockCommandExecutor::new();
+        for command_text in ["./connect-vpn.sh", "./disconnect-vpn.sh"] {
+            command_executor
+                .expect_execute()
+                .once()
+                .in_sequence(&mut seq)
+                .with(eq(shorthand(command_text)), eq(VariableMap::new()))
+                .returning(|_, _| Ok(ExitStatus::Success));
+        }
+
+        let action_executor = ActionExecutor {
+            command_executor: Box::new(command_executor),
+            arg_resolver: Box::new(MockArgumentResolver::new()),
+            quiet: false,
+            opener: Box::new(MockOpener::new()),
+            timings: None,
+        };
+
+        // Act
+        let result = action_executor.execute_with_hooks(
+            &[shorthand("./connect-vpn.sh")],
+            &[shorthand("./disconnect-vpn.sh")],
+            &VariableMap::new(),
+            || {
+                Err(ActionError::StatusCode {
+                    index: 0,
+                    status: ExitStatus::Fail(1),
+                })
+            },
+        );
+
+        // Assert
+        assert!(matches!(result, Err(ActionError::StatusCode { .. })));
+    }
+
+    #[test]
+    fn execute_result_hook_runs_on_failure_with_exit_code() {
+        // Arrange
+        let expected_variables = VariableMap::from([
+            ("environment".to_string(), "production".to_string()),
+            (COMMAND_VARIABLE_NAME.to_string(), "db migrate".to_string()),
+            (EXIT_CODE_VARIABLE_NAME.to_string(), "3".to_string()),
+        ]);
+        let mut command_executor = MockCommandExecutor::new();
+        command_executor
+            .expect_execute()
+            .once()
+            .with(eq(shorthand("./notify.sh")), eq(expected_variables))
+            .returning(|_, _| Ok(ExitStatus::Fail(1)));
+
+        let action_executor = ActionExecutor {
+            command_executor: Box::new(command_executor),
+            arg_resolver: Box::new(MockArgumentResolver::new()),
+            quiet: false,
+            opener: Box::new(MockOpener::new()),
+            timings: None,
+        };
+
+        // Act
+        action_executor.execute_result_hook(
+            &Some(shorthand("./celebrate.sh")),
+            &Some(shorthand("./notify.sh")),
+            &["db".to_string(), "migrate".to_string()],
+            3,
+            &VariableMap::from([("environment".to_string(), "production".to_string())]),
+        );
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn execute_stops_multi_step_action_at_deadline_and_runs_post_run() {
+        // Arrange
+        let temp_dir = TempDir::new().unwrap();
+        let second_step_path = temp_dir.path().join("second.txt");
+        let cleanup_path = temp_dir.path().join("cleanup.txt");
+
+        let action = ActionConfig::MultiStep(MultiActionConfig {
+            actions: vec![
+                shorthand("sleep 5"),
+                shorthand(&format!("touch {}", second_step_path.display())),
+            ],
+            shared_shell: false,
+        });
+
+        let create_action_executor = |deadline: Option<Instant>| ActionExecutor {
+            command_executor: create_command_executor_with_deadline(
+                &DingusOptions::default(),
+                &None,
+                &[],
+                &SearchPath::default(),
+                deadline,
+            ),
+            arg_resolver: Box::new(MockArgumentResolver::new()),
+            quiet: true,
+            opener: Box::new(MockOpener::new()),
+            timings: None,
+        };
+        let started = Instant::now();
+
+        // Act
+        let result = create_action_executor(None).execute_with_hooks(
+            &[],
+            &[shorthand(&format!("touch {}", cleanup_path.display()))],
+            &VariableMap::new(),
+            || {
+                create_action_executor(Some(Instant::now() + Duration::from_millis(200)))
+                    .execute(&action, &VariableMap::new())
+            },
+        );
+
+        // Assert
+        assert!(matches!(
+            result,
+            Err(ActionError::Execution {
+                index: 0,
+                source: ExecutionError::TimedOut
+            })
+        ));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(!second_step_path.exists());
+        assert!(cleanup_path.exists());
+    }
+
+    #[test]
+    fn execute_with_hooks_stops_when_pre_run_fails() {
+        // Arrange
+        let mut command_executor = MockCommandExecutor::new();
+        command_executor
+            .expect_execute()
+            .once()
+            .with(eq(shorthand("./connect-vpn.sh")), eq(VariableMap::new()))
+            .returning(|_, _| Ok(ExitStatus::Fail(1)));
+
+        let action_executor = ActionExecutor {
+            command_executor: Box::new(command_executor),
+            arg_resolver: Box::new(MockArgumentResolver::new()),
+            quiet: false,
+            opener: Box::new(MockOpener::new()),
+            timings: None,
+        };
+
+        let mut ran = false;
+
+        // Act
+        let result = action_executor.execute_with_hooks(
+            &[shorthand("./connect-vpn.sh")],
+            &[shorthand("./disconnect-vpn.sh")],
+            &VariableMap::new(),
+            || -> Result<(), ActionError> {
+                ran = true;
+                Ok(())
+            },
+        );
+
+        // Assert
+        assert!(matches!(result, Err(ActionError::StatusCode { .. })));
+        assert!(!ran);
+    }
+
+    #[test]
+    fn execute_open_opens_substituted_target() {
+        // Arrange
+        let mut variables = VariableMap::new();
+        variables.insert("environment".to_string(), "production".to_string());
+
+        let mut opener = MockOpener::new();
+        opener
+            .expect_open()
+            .once()
+            .with(eq("https://example.com/dashboards/production"))
+            .returning(|_| Ok(()));
+
+        let action_executor = ActionExecutor {
+            command_executor: Box::new(MockCommandExecutor::new()),
+            arg_resolver: Box::new(MockArgumentResolver::new()),
+            quiet: false,
+            opener: Box::new(opener),
+            timings: None,
+        };
+
+        let action = ActionConfig::Open(OpenActionConfig {
+            target: "https://example.com/dashboards/$environment".to_string(),
+        });
+
+        // Act
+        let result = action_executor.execute(&action, &variables);
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn execute_open_returns_error_on_failure() {
+        // Arrange
+        let mut opener = MockOpener::new();
+        opener
+            .expect_open()
+            .returning(|_| Err(io::Error::new(io::ErrorKind::NotFound, "no opener")));
+
+        let action_executor = ActionExecutor {
+            command_executor: Box::new(MockCommandExecutor::new()),
+            arg_resolver: Box::new(MockArgumentResolver::new()),
+            quiet: false,
+            opener: Box::new(opener),
+            timings: None,
+        };
+
+        let action = ActionConfig::Open(OpenActionConfig {
+            target: "./docs/index.html".to_string(),
+        });
+
+        // Act
+        let result = action_executor.execute(&action, &VariableMap::new());
+
+        // Assert
+        assert!(matches!(
+            result,
+            Err(ActionError::Open { target, .. }) if target == "./docs/index.html"
+        ));
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn execute_assert_fails_with_message() {
+        // Arrange
+        let action_executor = ActionExecutor {
+            command_executor: create_command_executor(&DingusOptions::default(), &None, &[]),
+            arg_resolver: Box::new(MockArgumentResolver::new()),
+            quiet: false,
+            opener: Box::new(MockOpener::new()),
+            timings: None,
+        };
+
+        let action = ActionConfig::Assert(AssertActionConfig {
+            assert: AssertConfig {
+                command: "test -n \"$TOKEN\"".to_string(),
+                message: "TOKEN must be set for $environment".to_string(),
+            },
+        });
+        let unset_variables = VariableMap::from([
+            ("TOKEN".to_string(), String::new()),
+            ("environment".to_string(), "prod".to_string()),
+        ]);
+        let set_variables = VariableMap::from([
+            ("TOKEN".to_string(), "secret".to_string()),
+            ("environment".to_string(), "prod".to_string()),
+        ]);
+
+        // Act
+        let unset_result = action_executor.execute(&action, &unset_variables);
+        let set_result = action_executor.execute(&action, &set_variables);
+
+        // Assert
+        assert!(matches!(
+            unset_result,
+            Err(ActionError::AssertionFailed { message }) if message == "TOKEN must be set for prod"
+        ));
+        assert!(set_result.is_ok());
+    }
 }
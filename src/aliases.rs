@@ -0,0 +1,239 @@
+use crate::args::ALIAS_ARGS_NAME;
+use crate::cli::{find_command_by_name, matched_command_path, subcommand_index};
+use crate::config::{ActionConfig, CommandConfig, CommandConfigMap};
+use crate::dependencies::PATH_SEPARATOR;
+use crate::platform::{platform_action, PlatformProvider};
+use clap::ArgMatches;
+use thiserror::Error;
+
+/// The command line which runs the command at the `.`-separated `path`, such as
+/// `dingus db migrate`.
+pub fn command_alias_text(path: &str) -> String {
+    let names: Vec<&str> = path.split(PATH_SEPARATOR).collect();
+    format!("dingus {}", names.join(" "))
+}
+
+/// If the command matched by `arg_matches` is an alias for another command, returns `args`
+/// rewritten to run that command instead, with any arguments passed to the alias appended.
+/// Aliases for other aliases are followed until a command that isn't an alias is found.
+pub fn command_alias_args(
+    arg_matches: &ArgMatches,
+    args: &[String],
+    commands: &CommandConfigMap,
+    platform_provider: &dyn PlatformProvider,
+) -> Result<Option<Vec<String>>, AliasError> {
+    let command_path = matched_command_path(arg_matches);
+    let Some((command_config, _)) = find_command_by_names(&command_path, commands) else {
+        return Ok(None);
+    };
+
+    let Some(ActionConfig::CommandAlias(command_alias)) =
+        platform_action(&command_config, platform_provider)
+    else {
+        return Ok(None);
+    };
+
+    let mut chain = vec![command_path.join(&PATH_SEPARATOR.to_string())];
+    let mut target_path = command_alias.alias.command;
+    let target_names = loop {
+        // If we've already been through this command, then we've gone in a circle.
+        if chain.contains(&target_path) {
+            chain.push(target_path);
+            return Err(AliasError::Cycle {
+                chain: chain.join(" -> "),
+            });
+        }
+
+        let segments: Vec<String> = target_path
+            .split(PATH_SEPARATOR)
+            .map(|segment| segment.to_string())
+            .collect();
+        let (target_config, target_names) =
+            find_command_by_names(&segments, commands).ok_or_else(|| AliasError::NotFound {
+                path: target_path.clone(),
+            })?;
+
+        chain.push(target_path);
+        match platform_action(&target_config, platform_provider) {
+            Some(ActionConfig::CommandAlias(next_alias)) => target_path = next_alias.alias.command,
+            _ => break target_names,
+        }
+    };
+
+    // Any options for dingus itself come before the alias
+    let alias_index = subcommand_index(args).min(args.len());
+    let mut alias_args = args[..alias_index].to_vec();
+    alias_args.extend(target_names);
+
+    let mut leaf_matches = arg_matches;
+    while let Some((_, subcommand_matches)) = leaf_matches.subcommand() {
+        leaf_matches = subcommand_matches;
+    }
+    if let Ok(Some(passthrough_args)) = leaf_matches.try_get_many::<String>(ALIAS_ARGS_NAME) {
+        alias_args.extend(passthrough_args.cloned());
+    }
+
+    Ok(Some(alias_args))
+}
+
+/// Finds the [`CommandConfig`] with the provided path of command `names`, along with the names
+/// used to run each command in the path on the command line.
+fn find_command_by_names(
+    names: &[String],
+    commands: &CommandConfigMap,
+) -> Option<(CommandConfig, Vec<String>)> {
+    let (first_name, other_names) = names.split_first()?;
+    let mut command_config = find_command_by_name(first_name, commands)?;
+    let mut command_names = vec![command_config.name.clone().unwrap_or(first_name.clone())];
+
+    for name in other_names {
+        command_config = find_command_by_name(name, &command_config.commands)?;
+        command_names.push(command_config.name.clone().unwrap_or(name.clone()));
+    }
+
+    Some((command_config, command_names))
+}
+
+#[derive(Error, Debug)]
+pub enum AliasError {
+    #[error("could not find aliased command \"{path}\"")]
+    NotFound { path: String },
+
+    #[error("circular alias detected: {chain}")]
+    Cycle { chain: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::create_root_command;
+    use crate::config::{parse_config, Platform};
+    use crate::platform::MockPlatformProvider;
+
+    fn mock_platform_provider() -> Box<dyn PlatformProvider> {
+        let mut platform_provider = MockPlatformProvider::new();
+        platform_provider
+            .expect_get_platform()
+            .return_const(Platform::Linux);
+        Box::new(platform_provider)
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn command_alias_args_runs_target_command_with_passthrough_args() {
+        // Arrange
+        let config = parse_config(
+            &"commands:
+  db:
+    commands:
+      migrate:
+        variables:
+          steps:
+            arg: steps
+        action: ./migrate.sh $steps
+  up:
+    alias:
+      command: migrate
+  migrate:
+    alias:
+      command: db.migrate
+"
+            .to_string(),
+            Platform::Linux,
+        )
+        .unwrap();
+        let platform_provider = mock_platform_provider();
+        let root_command = create_root_command(&config, &platform_provider);
+        let args = args(&["dingus", "--quiet", "--since", "up", "up", "--steps", "2"]);
+        let arg_matches = root_command.clone().get_matches_from(&args);
+
+        // Act
+        let alias_args = command_alias_args(
+            &arg_matches,
+            &args,
+            &config.commands,
+            platform_provider.as_ref(),
+        )
+        .unwrap()
+        .unwrap();
+
+        // Assert
+        assert_eq!(
+            alias_args,
+            vec!["dingus", "--quiet", "--since", "up", "db", "migrate", "--steps", "2"]
+        );
+        let target_matches = root_command.get_matches_from(&alias_args);
+        let (_, db_matches) = target_matches.subcommand().unwrap();
+        let (_, migrate_matches) = db_matches.subcommand().unwrap();
+        assert_eq!(
+            migrate_matches.get_one::<String>("steps"),
+            Some(&"2".to_string())
+        );
+    }
+
+    #[test]
+    fn command_alias_args_detects_cycles() {
+        // Arrange
+        let config = parse_config(
+            &"commands:
+  a:
+    alias:
+      command: b
+  b:
+    alias:
+      command: a
+"
+            .to_string(),
+            Platform::Linux,
+        )
+        .unwrap();
+        let platform_provider = mock_platform_provider();
+        let args = args(&["dingus", "a"]);
+        let arg_matches = create_root_command(&config, &platform_provider).get_matches_from(&args);
+
+        // Act
+        let result = command_alias_args(
+            &arg_matches,
+            &args,
+            &config.commands,
+            platform_provider.as_ref(),
+        );
+
+        // Assert
+        assert!(matches!(
+            result,
+            Err(AliasError::Cycle { chain }) if chain == "a -> b -> a"
+        ));
+    }
+
+    #[test]
+    fn command_alias_args_ignores_other_commands() {
+        // Arrange
+        let config = parse_config(
+            &"commands:
+  build:
+    action: cargo build
+"
+            .to_string(),
+            Platform::Linux,
+        )
+        .unwrap();
+        let platform_provider = mock_platform_provider();
+        let args = args(&["dingus", "build"]);
+        let arg_matches = create_root_command(&config, &platform_provider).get_matches_from(&args);
+
+        // Act
+        let result = command_alias_args(
+            &arg_matches,
+            &args,
+            &config.commands,
+            platform_provider.as_ref(),
+        );
+
+        // Assert
+        assert!(matches!(result, Ok(None)));
+    }
+}
@@ -0,0 +1,241 @@
+use crate::config::{CommandConfig, CommandConfigMap};
+use clap::ArgMatches;
+use inquire::{InquireError, Select};
+use mockall::automock;
+use thiserror::Error;
+
+#[automock]
+pub trait CandidateSelector {
+    /// Picks which of the `candidates` sharing the name `command_name` should be used, returning
+    /// its index.
+    fn select(
+        &self,
+        command_name: &str,
+        candidates: &[CommandConfig],
+    ) -> Result<usize, AmbiguityError>;
+}
+
+/// Asks the user to pick a candidate from the terminal.
+pub struct TerminalCandidateSelector;
+
+impl CandidateSelector for TerminalCandidateSelector {
+    fn select(
+        &self,
+        command_name: &str,
+        candidates: &[CommandConfig],
+    ) -> Result<usize, AmbiguityError> {
+        let options: Vec<String> = candidates
+            .iter()
+            .map(|candidate| describe_candidate(command_name, candidate))
+            .collect();
+
+        let message =
+            format!("There are multiple \"{command_name}\" commands, which one should run?");
+        let selection = Select::new(&message, options).raw_prompt()?;
+        Ok(selection.index)
+    }
+}
+
+/// Fails for any ambiguous command since there's no way to ask the user.
+pub struct NonInteractiveCandidateSelector;
+
+impl CandidateSelector for NonInteractiveCandidateSelector {
+    fn select(
+        &self,
+        command_name: &str,
+        candidates: &[CommandConfig],
+    ) -> Result<usize, AmbiguityError> {
+        Err(AmbiguityError::Ambiguous {
+            command_name: command_name.to_string(),
+            sources: candidates
+                .iter()
+                .map(describe_source)
+                .collect::<Vec<String>>()
+                .join(", "),
+        })
+    }
+}
+
+/// Creates a [`CandidateSelector`] which prompts the user if there's a terminal to prompt with.
+pub fn create_candidate_selector(stdin_is_terminal: bool) -> Box<dyn CandidateSelector> {
+    if stdin_is_terminal {
+        Box::new(TerminalCandidateSelector)
+    } else {
+        Box::new(NonInteractiveCandidateSelector)
+    }
+}
+
+/// Replaces the first ambiguous command in the matched subcommand path with the candidate picked
+/// by the `selector`.
+/// Returns `true` if a command was replaced, in which case the arguments need to be parsed again
+/// since the picked command may accept different arguments.
+pub fn disambiguate(
+    arg_matches: &ArgMatches,
+    commands: &mut CommandConfigMap,
+    selector: &dyn CandidateSelector,
+) -> Result<bool, AmbiguityError> {
+    let Some((subcommand_name, subcommand_matches)) = arg_matches.subcommand() else {
+        return Ok(false);
+    };
+
+    // Built-in commands won't be found here
+    let Some(command_key) = find_command_key(subcommand_name, commands) else {
+        return Ok(false);
+    };
+
+    // Safe to unwrap: we've just found the key
+    let command_config = commands.get_mut(&command_key).unwrap();
+    if command_config.candidates.is_empty() {
+        return disambiguate(subcommand_matches, &mut command_config.commands, selector);
+    }
+
+    let mut candidates = vec![CommandConfig {
+        candidates: vec![],
+        ..command_config.clone()
+    }];
+    candidates.append(&mut command_config.candidates);
+
+    let index = selector.select(subcommand_name, &candidates)?;
+    *command_config = candidates.swap_remove(index);
+
+    Ok(true)
+}
+
+/// Finds the key of the command that would be found by [`find_command_by_name`].
+///
+/// [`find_command_by_name`]: crate::cli::find_command_by_name
+fn find_command_key(command_name: &str, commands: &CommandConfigMap) -> Option<String> {
+    commands
+        .iter()
+        .find(|(key, command_config)| {
+            command_config.name.as_deref() == Some(command_name) || key.as_str() == command_name
+        })
+        .map(|(key, _)| key.clone())
+}
+
+fn describe_candidate(command_name: &str, candidate: &CommandConfig) -> String {
+    let description = candidate
+        .description
+        .clone()
+        .unwrap_or(command_name.to_string());
+    format!("{description} ({})", describe_source(candidate))
+}
+
+fn describe_source(candidate: &CommandConfig) -> String {
+    match &candidate.source {
+        Some(source) => source.display().to_string(),
+        None => "stdin".to_string(),
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum AmbiguityError {
+    #[error("\"{command_name}\" is ambiguous, it's defined in: {sources}")]
+    Ambiguous {
+        command_name: String,
+        sources: String,
+    },
+
+    #[error("prompt failed")]
+    Prompt(#[from] InquireError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Command;
+    use std::path::PathBuf;
+
+    fn command(description: &str, source: &str) -> CommandConfig {
+        CommandConfig {
+            description: Some(description.to_string()),
+            source: Some(PathBuf::from(source)),
+            ..Default::default()
+        }
+    }
+
+    fn ambiguous_commands() -> CommandConfigMap {
+        let mut commands = CommandConfigMap::new();
+        commands.insert(
+            "deploy".to_string(),
+            CommandConfig {
+                candidates: vec![command("Imported deploy", "./ops/dingus.yaml")],
+                ..command("Root deploy", "./dingus.yaml")
+            },
+        );
+        commands
+    }
+
+    #[test]
+    fn disambiguate_replaces_command_with_selected_candidate() {
+        // Arrange
+        let mut commands = ambiguous_commands();
+        let arg_matches = Command::new("dingus")
+            .subcommand(Command::new("deploy"))
+            .get_matches_from(vec!["dingus", "deploy"]);
+
+        let mut selector = MockCandidateSelector::new();
+        selector
+            .expect_select()
+            .withf(|command_name, candidates| {
+                command_name == "deploy"
+                    && candidates
+                        == [
+                            command("Root deploy", "./dingus.yaml"),
+                            command("Imported deploy", "./ops/dingus.yaml"),
+                        ]
+            })
+            .returning(|_, _| Ok(1));
+
+        // Act
+        let replaced = disambiguate(&arg_matches, &mut commands, &selector).unwrap();
+
+        // Assert
+        assert!(replaced);
+        assert_eq!(
+            commands.get("deploy").unwrap(),
+            &command("Imported deploy", "./ops/dingus.yaml")
+        );
+    }
+
+    #[test]
+    fn disambiguate_ignores_unambiguous_commands() {
+        // Arrange
+        let mut commands = CommandConfigMap::new();
+        commands.insert(
+            "deploy".to_string(),
+            command("Root deploy", "./dingus.yaml"),
+        );
+        let arg_matches = Command::new("dingus")
+            .subcommand(Command::new("deploy"))
+            .get_matches_from(vec!["dingus", "deploy"]);
+
+        let mut selector = MockCandidateSelector::new();
+        selector.expect_select().never();
+
+        // Act
+        let replaced = disambiguate(&arg_matches, &mut commands, &selector).unwrap();
+
+        // Assert
+        assert!(!replaced);
+    }
+
+    #[test]
+    fn non_interactive_selector_fails() {
+        // Arrange
+        let candidates = vec![
+            command("Root deploy", "./dingus.yaml"),
+            command("Imported deploy", "./ops/dingus.yaml"),
+        ];
+
+        // Act
+        let result = NonInteractiveCandidateSelector.select("deploy", &candidates);
+
+        // Assert
+        assert!(matches!(
+            result,
+            Err(AmbiguityError::Ambiguous { sources, .. })
+                if sources == "./dingus.yaml, ./ops/dingus.yaml"
+        ));
+    }
+}
@@ -1,7 +1,19 @@
+use clap::parser::ValueSource;
 use clap::ArgMatches;
 use mockall::automock;
 
 pub const ALIAS_ARGS_NAME: &str = "ARGS";
+pub const PASSTHROUGH_ARGS_NAME: &str = "PASSTHROUGH_ARGS";
+pub const ASSIGNMENTS_ARG_NAME: &str = "ASSIGNMENTS";
+pub const EXTRA_ARGS_NAME: &str = "EXTRA_ARGS";
+
+/// Splits a `KEY=VALUE` assignment into its key and value.
+pub fn parse_assignment(assignment: &str) -> Result<(String, String), String> {
+    match assignment.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, found \"{assignment}\"")),
+    }
+}
 
 /// Capable of resolving command-line argument values.
 #[automock]
@@ -29,15 +41,33 @@ impl ClapArgumentResolver {
 
 impl ArgumentResolver for ClapArgumentResolver {
     fn get(&self, key: &String) -> Option<String> {
-        if let Some(found_value) = self.arg_matches.get_one::<String>(key) {
-            return Some(found_value.clone());
+        // Not every variable has an argument, so unknown ids are expected here.
+        // Default values are ignored so that an assignment or the variable config can decide the
+        // value instead. The default value of an argument is the variable's value anyway, unless it
+        // has since been overridden (e.g. by a profile).
+        if let Ok(Some(found_value)) = self.arg_matches.try_get_one::<String>(key) {
+            if self.arg_matches.value_source(key) != Some(ValueSource::DefaultValue) {
+                return Some(found_value.clone());
+            }
+        }
+
+        // Assignments like `name=Dingus` can set any variable, the last one wins
+        if let Ok(Some(assignments)) = self
+            .arg_matches
+            .try_get_many::<String>(ASSIGNMENTS_ARG_NAME)
+        {
+            return assignments
+                .filter_map(|assignment| parse_assignment(assignment).ok())
+                .rev()
+                .find(|(assignment_key, _)| assignment_key == key)
+                .map(|(_, value)| value);
         }
 
         return None;
     }
 
     fn get_many(&self, key: &String) -> Option<Vec<String>> {
-        if let Some(found_values) = self.arg_matches.get_many::<String>(key) {
+        if let Ok(Some(found_values)) = self.arg_matches.try_get_many::<String>(key) {
             let mut values: Vec<String> = Vec::new();
 
             for found_value in found_values {
@@ -125,6 +155,62 @@ mod tests {
         );
     }
 
+    #[test]
+    fn argresolver_returns_none_for_unknown_arg() {
+        // Arrange
+        let matches = Command::new("dingus").get_matches_from(vec!["dingus"]);
+        let arg_resolver = ClapArgumentResolver::from_arg_matches(&matches);
+
+        // Act
+        let found_value = arg_resolver.get(&"name".to_string());
+        let found_values = arg_resolver.get_many(&ALIAS_ARGS_NAME.to_string());
+
+        // Assert
+        assert_eq!(found_value, None);
+        assert_eq!(found_values, None);
+    }
+
+    #[test]
+    fn argresolver_resolves_assignment() {
+        // Arrange
+        let assignments_arg = Arg::new(ASSIGNMENTS_ARG_NAME).num_args(0..);
+        let matches = Command::new("dingus")
+            .arg(assignments_arg)
+            .get_matches_from(vec![
+                "dingus",
+                "name=Foo",
+                "greeting=Hi=there",
+                "name=Dingus",
+            ]);
+
+        // Act
+        let arg_resolver = ClapArgumentResolver::from_arg_matches(&matches);
+
+        // Assert
+        assert_eq!(
+            arg_resolver.get(&"name".to_string()),
+            Some("Dingus".to_string())
+        );
+        assert_eq!(
+            arg_resolver.get(&"greeting".to_string()),
+            Some("Hi=there".to_string())
+        );
+        assert_eq!(arg_resolver.get(&"missing".to_string()), None);
+    }
+
+    #[test]
+    fn parse_assignment_requires_key() {
+        // Act
+        let valid = parse_assignment("name=");
+        let missing_key = parse_assignment("=Dingus");
+        let missing_equals = parse_assignment("Dingus");
+
+        // Assert
+        assert_eq!(valid, Ok(("name".to_string(), "".to_string())));
+        assert!(missing_key.is_err());
+        assert!(missing_equals.is_err());
+    }
+
     fn single_arg(name: &String) -> Arg {
         return Arg::new(name.clone())
             .long(name.clone())
@@ -138,4 +224,42 @@ mod tests {
             .action(ArgAction::Append)
             .num_args(0..);
     }
+
+    #[test]
+    fn argresolver_ignores_default_values() {
+        // Arrange
+        let arg = single_arg(&"name".to_string()).default_value("Dingus");
+        let matches = Command::new("dingus")
+            .arg(arg)
+            .get_matches_from(vec!["dingus"]);
+
+        // Act
+        let arg_resolver = ClapArgumentResolver::from_arg_matches(&matches);
+
+        // Assert
+        let found_value = arg_resolver.get(&"name".to_string());
+        assert_eq!(found_value, None);
+    }
+
+    #[test]
+    fn argresolver_resolves_assignment_for_arg_with_default_value() {
+        // Arrange
+        let arg = single_arg(&"name".to_string()).default_value("Dingus");
+        let assignments_arg = Arg::new(ASSIGNMENTS_ARG_NAME).num_args(0..);
+        let command = Command::new("dingus").arg(arg).arg(assignments_arg);
+
+        // Act
+        let assigned_matches = command.clone().get_matches_from(vec!["dingus", "name=Foo"]);
+        let explicit_matches =
+            command.get_matches_from(vec!["dingus", "--name", "Bar", "name=Foo"]);
+
+        // Assert
+        let assigned_value =
+            ClapArgumentResolver::from_arg_matches(&assigned_matches).get(&"name".to_string());
+        assert_eq!(assigned_value, Some("Foo".to_string()));
+
+        let explicit_value =
+            ClapArgumentResolver::from_arg_matches(&explicit_matches).get(&"name".to_string());
+        assert_eq!(explicit_value, Some("Bar".to_string()));
+    }
 }
@@ -0,0 +1,237 @@
+use crate::cli::{leading_options, subcommand_index};
+use std::path::PathBuf;
+use std::{fs, io};
+use thiserror::Error;
+
+const ARGS_FILE_ARG: &str = "--args-file";
+
+/// Replaces each `--args-file <FILE>` in the raw command-line `args` with the arguments in that
+/// file, so that they're parsed as if they'd been written in its place.
+/// Like dingus's other options, it's only used before the subcommand. Anything after that is left
+/// alone, since it could be meant for another program.
+pub fn expand_args_file(args: Vec<String>) -> Result<Vec<String>, ArgsFileError> {
+    let mut expanded_args: Vec<String> = args.iter().take(1).cloned().collect();
+    for (arg, value) in leading_options(&args) {
+        let path = if arg == ARGS_FILE_ARG {
+            value.ok_or(ArgsFileError::MissingPath)?.clone()
+        } else if let Some(path) = arg
+            .strip_prefix(ARGS_FILE_ARG)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            path.to_string()
+        } else {
+            expanded_args.push(arg.clone());
+            expanded_args.extend(value.cloned());
+            continue;
+        };
+
+        let path = PathBuf::from(path);
+        let text = fs::read_to_string(&path).map_err(|err| ArgsFileError::ReadFailed {
+            path: path.clone(),
+            source: err,
+        })?;
+        let file_args = split_args(&text).ok_or(ArgsFileError::UnterminatedQuote { path })?;
+        expanded_args.extend(file_args);
+    }
+
+    expanded_args.extend(args.iter().skip(subcommand_index(&args)).cloned());
+    Ok(expanded_args)
+}
+
+/// Splits the provided `text` into arguments on whitespace, similar to a shell.
+/// Single quotes keep everything between them, double quotes allow `\"` and `\\` escapes, and a
+/// word starting with `#` comments out the rest of the line.
+/// Returns `None` if a quote isn't closed.
+fn split_args(text: &str) -> Option<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current: Option<String> = None;
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if let Some(arg) = current.take() {
+                    args.push(arg);
+                }
+            }
+            '#' if current.is_none() => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '\'' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next()? {
+                        '\'' => break,
+                        c => arg.push(c),
+                    }
+                }
+            }
+            '"' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => match chars.next()? {
+                            c @ ('"' | '\\') => arg.push(c),
+                            c => {
+                                arg.push('\\');
+                                arg.push(c);
+                            }
+                        },
+                        c => arg.push(c),
+                    }
+                }
+            }
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    current.get_or_insert_with(String::new).push(c);
+                }
+            }
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    args.extend(current);
+    Some(args)
+}
+
+#[derive(Error, Debug)]
+pub enum ArgsFileError {
+    #[error("{ARGS_FILE_ARG} requires a file")]
+    MissingPath,
+
+    #[error("failed to read args file {}", path.display())]
+    ReadFailed { path: PathBuf, source: io::Error },
+
+    #[error("unterminated quote in args file {}", path.display())]
+    UnterminatedQuote { path: PathBuf },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::create_root_command;
+    use crate::config::{parse_config, Platform};
+    use crate::platform::{MockPlatformProvider, PlatformProvider};
+    use tempfile::TempDir;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn split_args_respects_quotes_and_comments() {
+        // Act
+        let args = split_args(
+            "# Greets Bob
+--name \"Bob \\\"The Builder\\\"\" --greeting 'Hi there'
+--punctuation=\\!  # Excited
+",
+        );
+
+        // Assert
+        assert_eq!(
+            args,
+            Some(vec![
+                "--name".to_string(),
+                "Bob \"The Builder\"".to_string(),
+                "--greeting".to_string(),
+                "Hi there".to_string(),
+                "--punctuation=!".to_string(),
+            ])
+        );
+        assert_eq!(split_args("--name 'Bob"), None);
+    }
+
+    #[test]
+    fn expand_args_file_applies_args_to_subcommand() {
+        // Arrange
+        let temp_dir = TempDir::new().unwrap();
+        let args_file_path = temp_dir.path().join("run.txt");
+        fs::write(&args_file_path, "greet --name \"Bob Smith\"\n").unwrap();
+
+        let config = parse_config(
+            &"commands:
+  greet:
+    variables:
+      name:
+        arg: name
+    action: echo Hello, $name!
+"
+            .to_string(),
+            Platform::Linux,
+        )
+        .unwrap();
+        let mut platform_provider = MockPlatformProvider::new();
+        platform_provider
+            .expect_get_platform()
+            .return_const(Platform::Linux);
+        let platform_provider: Box<dyn PlatformProvider> = Box::new(platform_provider);
+
+        // Act
+        let expanded_args = expand_args_file(args(&[
+            "dingus",
+            "--quiet",
+            "--args-file",
+            &args_file_path.display().to_string(),
+        ]))
+        .unwrap();
+
+        // Assert
+        assert_eq!(
+            expanded_args,
+            args(&["dingus", "--quiet", "greet", "--name", "Bob Smith"])
+        );
+        let arg_matches =
+            create_root_command(&config, &platform_provider).get_matches_from(&expanded_args);
+        let (_, greet_matches) = arg_matches.subcommand().unwrap();
+        assert_eq!(
+            greet_matches.get_one::<String>("name"),
+            Some(&"Bob Smith".to_string())
+        );
+    }
+
+    #[test]
+    fn expand_args_file_ignores_args_after_subcommand() {
+        // Act
+        let expanded_args = expand_args_file(args(&[
+            "dingus",
+            "--from",
+            "--args-file",
+            "compose",
+            "--args-file",
+            "run.txt",
+        ]))
+        .unwrap();
+
+        // Assert
+        assert_eq!(
+            expanded_args,
+            args(&[
+                "dingus",
+                "--from",
+                "--args-file",
+                "compose",
+                "--args-file",
+                "run.txt"
+            ])
+        );
+    }
+
+    #[test]
+    fn expand_args_file_ignores_args_after_separator() {
+        // Act
+        let expanded_args =
+            expand_args_file(args(&["dingus", "run", "--", "--args-file", "run.txt"])).unwrap();
+
+        // Assert
+        assert_eq!(
+            expanded_args,
+            args(&["dingus", "run", "--", "--args-file", "run.txt"])
+        );
+    }
+}
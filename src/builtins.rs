@@ -0,0 +1,369 @@
+use crate::cli::find_command_by_name;
+use crate::config::{
+    validate, CommandConfig, CommandConfigMap, CommandMetadata, Config, ConfigError, Source,
+};
+use crate::dependencies::{resolve_dependencies, DependencyError};
+use crate::doctor::{doctor_report, DOCTOR_COMMAND_NAME};
+use crate::platform::current_platform_provider;
+use crate::script::generate_script;
+use crate::update::{self_update, UpdateError};
+use crate::variables::apply_overrides;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use std::env;
+use thiserror::Error;
+
+pub const WHICH_COMMAND_NAME: &str = "which";
+pub const EXPORT_COMMAND_NAME: &str = "export";
+pub const CHECK_COMMAND_NAME: &str = "check";
+pub const SELF_UPDATE_COMMAND_NAME: &str = "self-update";
+pub const WHERE_COMMAND_NAME: &str = "where";
+const COMMAND_ARG_NAME: &str = "COMMAND";
+const CHECK_ONLY_ARG_NAME: &str = "CHECK_ONLY";
+
+/// Creates the [`Command`]s for any built-in commands.
+/// Built-in commands yield to configured commands with the same name.
+pub fn create_builtin_commands(commands: &CommandConfigMap) -> Vec<Command> {
+    vec![
+        create_which_command(),
+        create_export_command(),
+        create_check_command(),
+        create_self_update_command(),
+        create_where_command(),
+        create_doctor_command(),
+    ]
+    .into_iter()
+    .filter(|builtin| !is_shadowed(builtin.get_name(), commands))
+    .collect()
+}
+
+fn create_which_command() -> Command {
+    Command::new(WHICH_COMMAND_NAME)
+        .about("Shows which config file defines a command")
+        .arg(
+            Arg::new(COMMAND_ARG_NAME)
+                .required(true)
+                .num_args(1..)
+                .help("The command to look up, including any parent commands."),
+        )
+}
+
+fn create_export_command() -> Command {
+    Command::new(EXPORT_COMMAND_NAME)
+        .about("Prints a standalone bash script equivalent to a command")
+        .arg(
+            Arg::new(COMMAND_ARG_NAME)
+                .required(true)
+                .num_args(1..)
+                .help("The command to export, including any parent commands."),
+        )
+}
+
+fn create_check_command() -> Command {
+    Command::new(CHECK_COMMAND_NAME)
+        .about("Checks the config for problems without running anything")
+}
+
+fn create_self_update_command() -> Command {
+    Command::new(SELF_UPDATE_COMMAND_NAME)
+        .about("Updates dingus to the latest release")
+        .arg(
+            Arg::new(CHECK_ONLY_ARG_NAME)
+                .long("check")
+                .action(ArgAction::SetTrue)
+                .help("Only checks whether an update is available."),
+        )
+}
+
+fn create_where_command() -> Command {
+    Command::new(WHERE_COMMAND_NAME).about("Shows which config file is being used")
+}
+
+fn create_doctor_command() -> Command {
+    Command::new(DOCTOR_COMMAND_NAME)
+        .about("Shows details about the terminal and environment for troubleshooting")
+}
+
+fn is_shadowed(name: &str, commands: &CommandConfigMap) -> bool {
+    find_command_by_name(&name.to_string(), commands).is_some()
+}
+
+/// Formats the fields set on the provided [`CommandMetadata`], one per line.
+fn format_metadata(metadata: &CommandMetadata) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let Some(owner) = &metadata.owner {
+        lines.push(format!("owner: {owner}"));
+    }
+
+    if let Some(ticket) = &metadata.ticket {
+        lines.push(format!("ticket: {ticket}"));
+    }
+
+    if !metadata.tags.is_empty() {
+        lines.push(format!("tags: {}", metadata.tags.join(", ")));
+    }
+
+    lines
+}
+
+/// Executes the built-in command matching the provided [`ArgMatches`], if any.
+/// Returns `false` if no built-in command was matched.
+pub fn execute_builtin(
+    arg_matches: &ArgMatches,
+    config: &Config,
+    source: &Source,
+) -> Result<bool, BuiltinError> {
+    let Some((subcommand_name, subcommand_matches)) = arg_matches.subcommand() else {
+        return Ok(false);
+    };
+
+    if is_shadowed(subcommand_name, &config.commands) {
+        return Ok(false);
+    }
+
+    // Not every built-in command accepts a command path.
+    let command_path: Vec<String> = subcommand_matches
+        .try_get_many::<String>(COMMAND_ARG_NAME)
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+        .cloned()
+        .collect();
+
+    match subcommand_name {
+        WHICH_COMMAND_NAME => {
+            for line in which(&command_path, &config.commands)? {
+                println!("{line}");
+            }
+
+            Ok(true)
+        }
+        EXPORT_COMMAND_NAME => {
+            print!("{}", export(&command_path, config)?);
+            Ok(true)
+        }
+        CHECK_COMMAND_NAME => {
+            validate(config)?;
+            println!("No problems found");
+            Ok(true)
+        }
+        SELF_UPDATE_COMMAND_NAME => {
+            let platform = current_platform_provider().get_platform();
+            let check_only = subcommand_matches.get_flag(CHECK_ONLY_ARG_NAME);
+            println!("{}", self_update(&platform, check_only)?);
+            Ok(true)
+        }
+        WHERE_COMMAND_NAME => {
+            println!("{}", source.display());
+            Ok(true)
+        }
+        DOCTOR_COMMAND_NAME => {
+            for line in doctor_report(Ok(source), &config.options.bash_path, env::var_os("PATH")) {
+                println!("{line}");
+            }
+
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Generates a bash script for the command at `command_path`.
+fn export(command_path: &[String], config: &Config) -> Result<String, BuiltinError> {
+    let mut variable_configs = config.variables.clone();
+    let mut available_commands = config.commands.clone();
+    let mut command_config: Option<CommandConfig> = None;
+
+    for (depth, command_name) in command_path.iter().enumerate() {
+        let found_command =
+            find_command_by_name(command_name, &available_commands).ok_or_else(|| {
+                BuiltinError::CommandNotFound {
+                    path: command_path[..=depth].join(" "),
+                }
+            })?;
+
+        variable_configs.extend(found_command.variables.clone());
+        apply_overrides(&mut variable_configs, &found_command.overrides);
+
+        available_commands = found_command.commands.clone();
+        command_config = Some(found_command);
+    }
+
+    // Safe to unwrap: clap requires at least one command name
+    let command_config = command_config.unwrap();
+    let dependencies = resolve_dependencies(&command_config, &config.commands)?;
+
+    Ok(generate_script(
+        &command_config,
+        &variable_configs,
+        &dependencies,
+    ))
+}
+
+/// Describes where each command in `command_path` was defined, one line per level.
+fn which(
+    command_path: &[String],
+    commands: &CommandConfigMap,
+) -> Result<Vec<String>, BuiltinError> {
+    let mut lines = Vec::new();
+    let mut available_commands = commands.clone();
+    let mut metadata = CommandMetadata::default();
+
+    for (depth, command_name) in command_path.iter().enumerate() {
+        let command_config =
+            find_command_by_name(command_name, &available_commands).ok_or_else(|| {
+                BuiltinError::CommandNotFound {
+                    path: command_path[..=depth].join(" "),
+                }
+            })?;
+
+        let source = match &command_config.source {
+            Some(source) => source.display().to_string(),
+            None => "stdin".to_string(),
+        };
+
+        lines.push(format!("{}{command_name} ({source})", "  ".repeat(depth)));
+        available_commands = command_config.commands;
+        metadata = command_config.meta;
+    }
+
+    // Only the metadata for the command itself is shown, not its parents
+    let indent = "  ".repeat(command_path.len());
+    lines.extend(
+        format_metadata(&metadata)
+            .into_iter()
+            .map(|line| format!("{indent}{line}")),
+    );
+
+    Ok(lines)
+}
+
+#[derive(Error, Debug)]
+pub enum BuiltinError {
+    #[error("could not find command \"{path}\"")]
+    CommandNotFound { path: String },
+
+    #[error(transparent)]
+    Dependency(#[from] DependencyError),
+
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    #[error(transparent)]
+    Update(#[from] UpdateError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CommandConfig;
+    use std::path::PathBuf;
+
+    #[test]
+    fn which_shows_source_of_each_level() {
+        // Arrange
+        let mut imported_commands = CommandConfigMap::new();
+        imported_commands.insert(
+            "build".to_string(),
+            CommandConfig {
+                meta: CommandMetadata {
+                    owner: Some("team-docs".to_string()),
+                    ticket: None,
+                    tags: vec!["docs".to_string(), "ci".to_string()],
+                },
+                source: Some(PathBuf::from("./docs/dingus.yaml")),
+                ..Default::default()
+            },
+        );
+
+        let mut commands = CommandConfigMap::new();
+        commands.insert(
+            "docs".to_string(),
+            CommandConfig {
+                commands: imported_commands,
+                source: Some(PathBuf::from("/project/dingus.yaml")),
+                ..Default::default()
+            },
+        );
+
+        // Act
+        let lines = which(&["docs".to_string(), "build".to_string()], &commands).unwrap();
+
+        // Assert
+        assert_eq!(
+            lines,
+            vec![
+                "docs (/project/dingus.yaml)".to_string(),
+                "  build (./docs/dingus.yaml)".to_string(),
+                "    owner: team-docs".to_string(),
+                "    tags: docs, ci".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn which_fails_for_unknown_command() {
+        // Arrange
+        let mut commands = CommandConfigMap::new();
+        commands.insert("docs".to_string(), CommandConfig::default());
+
+        // Act
+        let result = which(&["docs".to_string(), "missing".to_string()], &commands);
+
+        // Assert
+        assert!(matches!(
+            result,
+            Err(BuiltinError::CommandNotFound { path }) if path == "docs missing"
+        ));
+    }
+
+    #[test]
+    fn check_fails_for_invalid_config() {
+        // Arrange
+        let mut commands = CommandConfigMap::new();
+        commands.insert("empty".to_string(), CommandConfig::default());
+
+        let config = Config {
+            commands,
+            ..Default::default()
+        };
+
+        let arg_matches = Command::new("dingus")
+            .subcommands(create_builtin_commands(&config.commands))
+            .get_matches_from(vec!["dingus", CHECK_COMMAND_NAME]);
+
+        // Act
+        let result = execute_builtin(&arg_matches, &config, &Source::Stdin);
+
+        // Assert
+        assert!(matches!(
+            result,
+            Err(BuiltinError::Config(ConfigError::EmptyCommand { command })) if command == "empty"
+        ));
+    }
+
+    #[test]
+    fn builtin_commands_yield_to_configured_commands() {
+        // Arrange
+        let mut commands = CommandConfigMap::new();
+        commands.insert(WHICH_COMMAND_NAME.to_string(), CommandConfig::default());
+
+        // Act
+        let builtin_commands = create_builtin_commands(&commands);
+
+        // Assert
+        let builtin_command_names: Vec<&str> = builtin_commands
+            .iter()
+            .map(|builtin_command| builtin_command.get_name())
+            .collect();
+        assert_eq!(
+            builtin_command_names,
+            vec![
+                EXPORT_COMMAND_NAME,
+                CHECK_COMMAND_NAME,
+                SELF_UPDATE_COMMAND_NAME,
+                WHERE_COMMAND_NAME,
+                DOCTOR_COMMAND_NAME
+            ]
+        );
+    }
+}
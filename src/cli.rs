@@ -1,11 +1,42 @@
-use crate::args::ALIAS_ARGS_NAME;
+use crate::actions::accepts_extra_args;
+use crate::args::{
+    parse_assignment, ALIAS_ARGS_NAME, ASSIGNMENTS_ARG_NAME, EXTRA_ARGS_NAME, PASSTHROUGH_ARGS_NAME,
+};
+use crate::builtins::create_builtin_commands;
 use crate::config::{
     ActionConfig, ArgumentConfigVariant, CommandConfig, CommandConfigMap, Config, DingusOptions,
-    ExecutionConfigVariant, NamedArgumentConfig, RawCommandConfigVariant, VariableConfig,
-    VariableConfigMap,
+    ExecutionConfigVariant, NamedArgumentConfig, RawCommandConfigVariant,
+    ShellCommandConfigVariant, VariableConfig, VariableConfigMap,
 };
-use crate::platform::{is_current_platform, PlatformProvider};
-use clap::{Arg, ArgMatches, Command, ValueHint};
+use crate::platform::{is_current_platform, platform_action, PlatformProvider};
+use crate::profiles::create_profile_arg;
+use crate::variables::apply_overrides;
+use clap::parser::ValueSource;
+use clap::{value_parser, Arg, ArgAction, ArgMatches, ColorChoice, Command, ValueEnum, ValueHint};
+use linked_hash_map::LinkedHashMap;
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+
+pub const EXPORT_ENV_ARG_NAME: &str = "EXPORT_ENV";
+pub const DUMP_ENV_ARG_NAME: &str = "DUMP_ENV";
+pub const CHECK_VARS_ARG_NAME: &str = "CHECK_VARS";
+pub const DUMP_PLAN_ARG_NAME: &str = "DUMP_PLAN";
+pub const INTERACTIVE_VARS_ARG_NAME: &str = "INTERACTIVE_VARS";
+pub const WATCH_ARG_NAME: &str = "WATCH";
+pub const SINCE_ARG_NAME: &str = "SINCE";
+pub const DEBUG_ARGS_ARG_NAME: &str = "DEBUG_ARGS";
+pub const WAIT_ARG_NAME: &str = "WAIT";
+pub const FROM_ARG_NAME: &str = "FROM";
+pub const ARGS_FILE_ARG_NAME: &str = "ARGS_FILE";
+pub const DUMP_SCHEMA_YAML_ARG_NAME: &str = "DUMP_SCHEMA_YAML";
+pub const QUIET_ARG_NAME: &str = "QUIET";
+pub const TIMINGS_ARG_NAME: &str = "TIMINGS";
+pub const TRACE_CONFIG_ARG_NAME: &str = "TRACE_CONFIG";
+pub const INIT_WITH_ARG_NAME: &str = "INIT_WITH";
+pub const COLOR_ARG_NAME: &str = "COLOR";
+pub const FORCE_TTY_ARG_NAME: &str = "FORCE_TTY";
+pub const NO_TTY_ARG_NAME: &str = "NO_TTY";
+const AMBIGUOUS_ARGS_NAME: &str = "AMBIGUOUS_ARGS";
 
 /// Creates a root-level [`Command`] for the provided [`Config`].
 pub fn create_root_command(
@@ -20,18 +51,170 @@ pub fn create_root_command(
         &platform_provider,
     );
 
-    let mut root_command = Command::new("dingus")
+    let program_name = config
+        .options
+        .program_name
+        .clone()
+        .unwrap_or("dingus".to_string());
+    let mut root_command = Command::new(&program_name)
         .version(env!("CARGO_PKG_VERSION"))
         .subcommands(subcommands)
+        .subcommands(create_builtin_commands(&config.commands))
         .subcommand_required(true)
         .arg_required_else_help(true)
-        .args(root_args);
+        .args(root_args)
+        .args(create_dingus_args());
+
+    // Subcommand usage lines are built from the root command's bin name
+    if config.options.program_name.is_some() {
+        root_command = root_command.bin_name(&program_name)
+    }
+
+    if !config.profiles.is_empty() {
+        root_command = root_command.arg(create_profile_arg(&config.profiles))
+    }
 
     if let Some(description) = &config.description {
         root_command = root_command.about(description)
     }
 
-    return root_command;
+    return with_command_groups(root_command, &config.commands);
+}
+
+/// Creates the [`Arg`]s for dingus's own options, which don't depend on the config.
+fn create_dingus_args() -> Vec<Arg> {
+    vec![
+        Arg::new(INTERACTIVE_VARS_ARG_NAME)
+            .long("interactive-vars")
+            .action(ArgAction::SetTrue)
+            .global(true)
+            .help(
+                "Prompts for the value of every variable, using the resolved value as the default.",
+            ),
+
+        Arg::new(EXPORT_ENV_ARG_NAME)
+            .long("export-env")
+            .action(ArgAction::SetTrue)
+            .global(true)
+            .help(
+                "Prints the resolved variables as shell exports instead of executing the command.",
+            ),
+
+        Arg::new(DUMP_ENV_ARG_NAME)
+            .long("dump-env")
+            .action(ArgAction::SetTrue)
+            .global(true)
+            .help("Prints the environment variables that would be passed to the command, with sensitive values redacted, instead of executing it."),
+
+        Arg::new(DUMP_PLAN_ARG_NAME)
+            .long("dump-plan")
+            .value_name("FORMAT")
+            .value_parser(["json"])
+            .global(true)
+            .help("Prints everything that the command would execute, with sensitive values redacted, instead of executing it."),
+
+        Arg::new(CHECK_VARS_ARG_NAME)
+            .long("check-vars")
+            .action(ArgAction::SetTrue)
+            .global(true)
+            .help("Resolves every variable without prompting and reports all failures instead of executing the command."),
+
+        Arg::new(WATCH_ARG_NAME)
+            .long("watch")
+            .action(ArgAction::SetTrue)
+            .global(true)
+            .help("Re-runs the command whenever a file in the config file's directory changes."),
+
+        Arg::new(SINCE_ARG_NAME)
+            .long("since")
+            .value_name("REF")
+            .global(true)
+            .help(
+                "Skips the command if none of its sources have changed since the provided git ref.",
+            ),
+
+        Arg::new(WAIT_ARG_NAME)
+            .long("wait")
+            .action(ArgAction::SetTrue)
+            .global(true)
+            .help(
+                "Waits for any other run holding the command's lock to finish instead of failing.",
+            ),
+
+        Arg::new(QUIET_ARG_NAME)
+            .long("quiet")
+            .action(ArgAction::SetTrue)
+            .global(true)
+            .help("Hides the banners printed before each step."),
+
+        Arg::new(TIMINGS_ARG_NAME)
+            .long("timings")
+            .action(ArgAction::SetTrue)
+            .global(true)
+            .help("Prints how long each step took once the command finishes."),
+
+        // Handled by from_directory before the config is loaded, this just makes it known to clap.
+        // Like the other options handled before the config is loaded, it's only accepted before
+        // the subcommand since that's the only place it's looked for.
+        Arg::new(FROM_ARG_NAME)
+            .long("from")
+            .value_name("DIR")
+            .value_hint(ValueHint::DirPath)
+            .help("Runs as if dingus was started in the provided directory."),
+
+        // Replaced by expand_args_file before anything else, this just makes it known to clap
+        Arg::new(ARGS_FILE_ARG_NAME)
+            .long("args-file")
+            .value_name("FILE")
+            .value_hint(ValueHint::FilePath)
+            .help("Reads more arguments from the provided file, as if they were written in its place."),
+
+        // Handled by trace_config before the config is loaded, this just makes it known to clap
+        Arg::new(TRACE_CONFIG_ARG_NAME)
+            .long("trace-config")
+            .action(ArgAction::SetTrue)
+            .help("Prints each import to stderr as the config is loaded."),
+
+        // Handled by color_choice before the config is loaded, this just makes it known to clap
+        Arg::new(COLOR_ARG_NAME)
+            .long("color")
+            .value_name("WHEN")
+            .value_parser(value_parser!(ColorChoice))
+            .help("Whether to use colors in the output. Defaults to auto, which only uses colors when stdout is a terminal."),
+
+        // Handled by tty_override before the config is loaded, these just make them known to clap
+        Arg::new(FORCE_TTY_ARG_NAME)
+            .long("force-tty")
+            .action(ArgAction::SetTrue)
+            .conflicts_with(NO_TTY_ARG_NAME)
+            .help("Treats stdin as a terminal, loading the config file and prompting as usual even when stdin isn't detected as one."),
+
+        Arg::new(NO_TTY_ARG_NAME)
+            .long("no-tty")
+            .action(ArgAction::SetTrue)
+            .help("Treats stdin as not being a terminal, reading the config from stdin and never prompting to pick between commands."),
+
+        // Handled by init_template before the config is loaded, this just makes it known to clap
+        Arg::new(INIT_WITH_ARG_NAME)
+            .long("init-with")
+            .value_name("TEMPLATE")
+            .exclusive(true)
+            .help("Creates a config file in the current directory from a built-in template."),
+
+        // Handled by dump_schema_yaml before the config is loaded, this just makes it known to clap
+        Arg::new(DUMP_SCHEMA_YAML_ARG_NAME)
+            .long("dump-schema-yaml")
+            .action(ArgAction::SetTrue)
+            .exclusive(true)
+            .help("Prints an annotated example config demonstrating every kind of variable and action."),
+
+        Arg::new(DEBUG_ARGS_ARG_NAME)
+            .long("debug-args")
+            .action(ArgAction::SetTrue)
+            .global(true)
+            .hide(true)
+            .help("Prints the parsed arguments for the command instead of executing it."),
+    ]
 }
 
 fn create_commands(
@@ -58,11 +241,16 @@ fn create_commands(
                 name = alternate_name;
             }
 
+            if !command_config.candidates.is_empty() {
+                return create_ambiguous_command(name, command_config);
+            }
+
             // Combine the variable configs provided by the caller (parent) with the variable
             // configs from the current command.
             // This lets us inherit variables from the root config/parent commands.
             let mut variables = parent_variables.clone();
             variables.extend(command_config.variables.clone());
+            apply_overrides(&mut variables, &command_config.overrides);
 
             let args = create_args(dingus_options, &variables);
 
@@ -76,16 +264,49 @@ fn create_commands(
             // If this command doesn't have any action, then it needs a subcommand
             // Doesn't make sense to have a command that does nothing and has no subcommands to
             // execute either.
-            let has_action = command_config.action.is_some();
+            let action = platform_action(command_config, platform_provider.as_ref());
+            let has_action = action.is_some();
 
             let mut command = Command::new(name)
                 .subcommands(subcommands)
                 .subcommand_required(!has_action)
                 .args(args)
-                .hide(command_config.hidden);
+                .hide(command_config.is_hidden());
+
+            // Any variable can be set with a `KEY=VALUE` assignment, except for aliases and shell
+            // commands accepting extra arguments, where they're passed through to the command
+            // instead.
+            if action.as_ref().is_some_and(accepts_extra_args) {
+                let extra_args = Arg::new(EXTRA_ARGS_NAME)
+                    .index(next_positional_index(&command))
+                    .num_args(1..)
+                    .allow_hyphen_values(true)
+                    .trailing_var_arg(true)
+                    .value_hint(ValueHint::CommandWithArguments)
+                    .help("Extra arguments to append to the command.");
+
+                command = command.arg(extra_args)
+            } else if has_action
+                && !matches!(
+                    action,
+                    Some(ActionConfig::Alias(_) | ActionConfig::CommandAlias(_))
+                )
+            {
+                // Assignments come after any positional arguments for variables
+                let assignments = Arg::new(ASSIGNMENTS_ARG_NAME)
+                    .index(next_positional_index(&command))
+                    .num_args(1..)
+                    .value_name("KEY=VALUE")
+                    .value_parser(|assignment: &str| {
+                        parse_assignment(assignment).map(|_| assignment.to_string())
+                    })
+                    .help("Values for any of the command's variables.");
+
+                command = command.arg(assignments)
+            }
 
             // If the action is an alias, then we use a special argument for the arguments to pass through to the alias
-            if let Some(ActionConfig::Alias(_)) = command_config.action.clone() {
+            if let Some(ActionConfig::Alias(_) | ActionConfig::CommandAlias(_)) = action {
                 let raw_args = Arg::new(ALIAS_ARGS_NAME)
                     .num_args(1..)
                     .allow_hyphen_values(true)
@@ -94,17 +315,171 @@ fn create_commands(
                     .help("Arguments and options for the aliased command.");
 
                 command = command.arg(raw_args)
+            } else if command_config.is_passthrough() && has_action {
+                let passthrough_args = Arg::new(PASSTHROUGH_ARGS_NAME)
+                    .index(next_positional_index(&command))
+                    .num_args(1..)
+                    .last(true)
+                    .allow_hyphen_values(true)
+                    .value_hint(ValueHint::CommandWithArguments)
+                    .help("Arguments to pass through to the command.");
+
+                command = command.arg(passthrough_args)
             }
 
-            if let Some(description) = command_config.description.clone() {
+            if let Some(description) = command_description(command_config, action.as_ref()) {
                 command = command.about(description)
             }
 
-            return command;
+            return with_command_groups(command, &command_config.commands);
         })
         .collect()
 }
 
+/// The description of the provided [`CommandConfig`].
+/// Commands without a description fall back to a leading `# comment` in the first step of their
+/// action, if it's a bash command.
+fn command_description(
+    command_config: &CommandConfig,
+    action: Option<&ActionConfig>,
+) -> Option<String> {
+    if command_config.description.is_some() {
+        return command_config.description.clone();
+    }
+
+    let first_step = match action? {
+        ActionConfig::SingleStep(single_action_config) => &single_action_config.action,
+        ActionConfig::MultiStep(multi_action_config) => multi_action_config.actions.first()?,
+        _ => return None,
+    };
+
+    let ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(bash_config)) =
+        first_step
+    else {
+        return None;
+    };
+
+    // Shebangs aren't descriptions
+    let first_line = bash_config
+        .command
+        .lines()
+        .find(|line| !line.trim().is_empty())?;
+    let comment = first_line.trim().strip_prefix('#')?;
+    if comment.starts_with('!') || comment.trim().is_empty() {
+        return None;
+    }
+
+    Some(comment.trim().to_string())
+}
+
+/// Creates a placeholder [`Command`] for a command which shares its name with other commands.
+/// The arguments depend on which command the user picks, so any arguments are accepted until
+/// they're parsed again for the picked command.
+fn create_ambiguous_command(name: &str, command_config: &CommandConfig) -> Command {
+    let raw_args = Arg::new(AMBIGUOUS_ARGS_NAME)
+        .num_args(0..)
+        .allow_hyphen_values(true)
+        .trailing_var_arg(true)
+        .help("Arguments, options and subcommands for the picked command.");
+
+    let mut command = Command::new(name.to_string())
+        .arg(raw_args)
+        .hide(command_config.is_hidden());
+
+    if let Some(description) = command_config.description.clone() {
+        command = command.about(description)
+    }
+
+    command
+}
+
+/// Lists any subcommands of `command` that have a group under a heading for their group in the
+/// --help output.
+/// Clap doesn't support headings for individual subcommands, so the subcommands are listed in the
+/// help template instead, using clap's own listing for each group. Ungrouped subcommands are left
+/// under the default heading.
+fn with_command_groups(command: Command, commands: &CommandConfigMap) -> Command {
+    let mut groups: LinkedHashMap<String, Vec<String>> = LinkedHashMap::new();
+
+    // Commands for other platforms won't have been added, so only look at the actual subcommands
+    for subcommand in command.get_subcommands() {
+        let name = subcommand.get_name().to_string();
+        let Some(command_config) = find_command_by_name(&name, commands) else {
+            continue;
+        };
+
+        let Some(group) = command_config.group else {
+            continue;
+        };
+
+        groups.entry(group).or_default().push(name);
+    }
+
+    if groups.is_empty() {
+        return command;
+    }
+
+    let header = command.get_styles().get_header();
+
+    let mut command_help = String::new();
+    for (group, names) in groups.iter() {
+        let listing = subcommand_listing(&command, |name| names.iter().any(|n| n == name), false);
+        if !listing.is_empty() {
+            command_help.push_str(&format!("{header}{group}:{header:#}\n{listing}\n\n"));
+        }
+    }
+
+    let grouped_names: Vec<&str> = groups.values().flatten().map(String::as_str).collect();
+    let listing = subcommand_listing(&command, |name| !grouped_names.contains(&name), true);
+    if !listing.is_empty() {
+        command_help.push_str(&format!("{header}Commands:{header:#}\n{listing}\n\n"));
+    }
+
+    // The subcommands can't be left out of {all-args}, so the arguments are listed separately
+    let mut arg_help = String::new();
+    if command.get_positionals().any(|arg| !arg.is_hide_set()) {
+        arg_help.push_str(&format!(
+            "{header}Arguments:{header:#}\n{{positionals}}\n\n"
+        ));
+    }
+    arg_help.push_str(&format!("{header}Options:{header:#}\n{{options}}"));
+
+    command.help_template(format!(
+        "{{before-help}}{{about-with-newline}}\n{{usage-heading}} {{usage}}\n\n{command_help}{arg_help}{{after-help}}"
+    ))
+}
+
+/// Renders clap's listing of the visible subcommands of `command` with a name matching `filter`.
+fn subcommand_listing(
+    command: &Command,
+    filter: impl Fn(&str) -> bool,
+    include_help_subcommand: bool,
+) -> String {
+    command
+        .clone()
+        .mut_subcommands(|subcommand| {
+            let hidden = subcommand.is_hide_set() || !filter(subcommand.get_name());
+            subcommand.hide(hidden)
+        })
+        .disable_help_subcommand(!include_help_subcommand)
+        .help_template("{subcommands}")
+        .render_help()
+        .ansi()
+        .to_string()
+        .trim_end()
+        .to_string()
+}
+
+/// The index to use for a positional argument added after all of the command's existing ones.
+fn next_positional_index(command: &Command) -> usize {
+    command
+        .get_positionals()
+        .filter_map(|arg| arg.get_index())
+        .max()
+        .unwrap_or(0)
+        + 1
+}
+
 fn create_args(
     dingus_options: &DingusOptions,
     variable_config_map: &VariableConfigMap,
@@ -112,20 +487,7 @@ fn create_args(
     variable_config_map
         .iter()
         .map(|(key, var_config)| -> Option<Arg> {
-            let mut arg_config = match var_config {
-                VariableConfig::ShorthandLiteral(_) => None,
-                VariableConfig::Literal(literal) => literal.clone().argument,
-                VariableConfig::Execution(exec) => exec.clone().argument,
-                VariableConfig::Prompt(prompt) => prompt.clone().argument,
-                VariableConfig::Argument(argument) => Some(argument.clone().argument),
-            };
-
-            // Automatically create an argument if the auto_args option is enabled
-            if dingus_options.auto_args && arg_config == None {
-                arg_config = Some(ArgumentConfigVariant::Shorthand(key.clone()));
-            }
-
-            if let Some(arg_config) = arg_config {
+            if let Some(arg_config) = argument_config(dingus_options, key, var_config) {
                 // Use the variable key as the ID so we can link this arg to the variable
                 let mut arg = Arg::new(key.clone());
 
@@ -140,11 +502,21 @@ fn create_args(
                             arg = arg.short(short_arg_name)
                         }
 
+                        if !named_arg_config.aliases.is_empty() {
+                            arg = arg.visible_aliases(named_arg_config.aliases)
+                        }
+
                         if let Some(description) = named_arg_config.description {
                             arg = arg.help(description)
                         }
 
-                        arg
+                        with_relations(
+                            arg,
+                            &named_arg_config.conflicts_with,
+                            &named_arg_config.requires,
+                            dingus_options,
+                            variable_config_map,
+                        )
                     }
 
                     // Positional arguments only set the position and description
@@ -155,7 +527,13 @@ fn create_args(
                             arg = arg.help(description)
                         }
 
-                        arg
+                        with_relations(
+                            arg,
+                            &positional_arg_config.conflicts_with,
+                            &positional_arg_config.requires,
+                            dingus_options,
+                            variable_config_map,
+                        )
                     }
                 };
 
@@ -176,6 +554,81 @@ fn create_args(
         .collect()
 }
 
+/// The long names of dingus's own options for the root command of the provided [`Config`], or
+/// for its subcommands if `is_root` is `false`.
+/// Only the global options are available to subcommands.
+pub fn reserved_arg_names(config: &Config, is_root: bool) -> Vec<String> {
+    let mut dingus_args = create_dingus_args();
+    if !config.profiles.is_empty() {
+        dingus_args.push(create_profile_arg(&config.profiles));
+    }
+
+    let mut names: Vec<String> = dingus_args
+        .into_iter()
+        .filter(|arg| is_root || arg.is_global_set())
+        .filter_map(|arg| arg.get_long().map(|long| long.to_string()))
+        .collect();
+
+    // Added by clap itself
+    names.push("help".to_string());
+    if is_root {
+        names.push("version".to_string());
+    }
+
+    names
+}
+
+/// The [`ArgumentConfigVariant`] for the variable with the provided `key`, if it has an argument.
+pub fn argument_config(
+    dingus_options: &DingusOptions,
+    key: &str,
+    var_config: &VariableConfig,
+) -> Option<ArgumentConfigVariant> {
+    let mut arg_config = match var_config {
+        VariableConfig::ShorthandLiteral(_) => None,
+        VariableConfig::Literal(literal) => literal.clone().argument,
+        VariableConfig::Execution(exec) => exec.clone().argument,
+        VariableConfig::Prompt(prompt) => prompt.clone().argument,
+        VariableConfig::Random(random) => random.clone().argument,
+        VariableConfig::File(file) => file.clone().argument,
+        VariableConfig::Argument(argument) => Some(argument.clone().argument),
+    };
+
+    // Automatically create an argument if the auto_args option is enabled
+    if dingus_options.auto_args && arg_config == None {
+        arg_config = Some(ArgumentConfigVariant::Shorthand(key.to_string()));
+    }
+
+    arg_config
+}
+
+/// Stops `arg` from being used alongside the arguments for the variables in `conflicts_with`, and
+/// from being used without the arguments for the variables in `requires`.
+/// Variables without an argument are skipped, since they can't be provided on the command line
+/// anyway.
+fn with_relations(
+    arg: Arg,
+    conflicts_with: &[String],
+    requires: &[String],
+    dingus_options: &DingusOptions,
+    variable_config_map: &VariableConfigMap,
+) -> Arg {
+    let has_argument = |key: &&String| {
+        variable_config_map
+            .get(*key)
+            .and_then(|var_config| argument_config(dingus_options, key, var_config))
+            .is_some()
+    };
+
+    let conflicts: Vec<&String> = conflicts_with.iter().filter(has_argument).collect();
+    requires
+        .iter()
+        .filter(has_argument)
+        .fold(arg.conflicts_with_all(conflicts), |arg, requirement| {
+            arg.requires(requirement)
+        })
+}
+
 /// Finds the [`CommandConfig`], [`VariableConfigMap`], and [`ArgMatches`], matching the provided `arg_matches`.
 /// This essentially returns the command to invoke (and it's relevent [`ArgMatches`]), all the variables available to the command.
 pub fn find_subcommand(
@@ -194,6 +647,7 @@ pub fn find_subcommand(
         // Add the subcommands variables to the variables provided by the parent
         let mut available_variables = parent_variables.clone();
         available_variables.extend(command_config.variables.clone());
+        apply_overrides(&mut available_variables, &command_config.overrides);
 
         // If we've matched another subcommand, return that one instead
         let matched_subcommand = find_subcommand(
@@ -218,7 +672,9 @@ pub fn find_subcommand(
     return None;
 }
 
-fn find_command_by_name(
+/// Finds the [`CommandConfig`] in `available_commands` whose key or overridden name matches
+/// `command_name`.
+pub fn find_command_by_name(
     command_name: &String,
     available_commands: &CommandConfigMap,
 ) -> Option<CommandConfig> {
@@ -243,20 +699,191 @@ fn find_command_by_name(
     return None;
 }
 
+/// Returns the names of each subcommand matched by the provided [`ArgMatches`], from the top-level
+/// command down.
+pub fn matched_command_path(arg_matches: &ArgMatches) -> Vec<String> {
+    let mut command_path = vec![];
+
+    let mut current_matches = arg_matches;
+    while let Some((subcommand_name, subcommand_matches)) = current_matches.subcommand() {
+        command_path.push(subcommand_name.to_string());
+        current_matches = subcommand_matches;
+    }
+
+    command_path
+}
+
+/// Finds the directory provided with `--from` in the raw command-line `args`.
+/// This can't wait for clap since the directory affects which config file gets loaded, and the
+/// config is needed to create the [`Command`] that clap parses with.
+pub fn from_directory(args: &[String]) -> Option<PathBuf> {
+    raw_arg_value(args, "--from").map(PathBuf::from)
+}
+
+/// Finds the template name provided with `--init-with` in the raw command-line `args`.
+/// Like [`from_directory`], this can't wait for clap since the config file doesn't exist yet.
+pub fn init_template(args: &[String]) -> Option<String> {
+    raw_arg_value(args, "--init-with")
+}
+
+/// The options at the start of the raw command-line `args`, before the first subcommand, along
+/// with their separate value if they have one.
+/// These are the only options that belong to dingus itself, anything after the subcommand could
+/// be an argument for a variable, or for another program.
+pub fn leading_options(args: &[String]) -> Vec<(&String, Option<&String>)> {
+    // Any other options are for the profile or root variables, which always take a value
+    let flag_args: Vec<String> = create_dingus_args()
+        .into_iter()
+        .filter(|arg| !arg.get_action().takes_values())
+        .filter_map(|arg| arg.get_long().map(|long| format!("--{long}")))
+        .chain(["--help", "-h", "--version", "-V"].map(String::from))
+        .collect();
+
+    let mut options = vec![];
+    let mut args = args.iter().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--" || !arg.starts_with('-') {
+            break;
+        }
+
+        let has_inline_value = arg.contains('=') || (!arg.starts_with("--") && arg.len() > 2);
+        if flag_args.contains(arg) || has_inline_value {
+            options.push((arg, None));
+        } else {
+            options.push((arg, args.next()));
+        }
+    }
+
+    options
+}
+
+/// The index of the first subcommand in the raw command-line `args`, after any
+/// [`leading_options`].
+pub fn subcommand_index(args: &[String]) -> usize {
+    let leading_count: usize = leading_options(args)
+        .iter()
+        .map(|(_, value)| 1 + usize::from(value.is_some()))
+        .sum();
+
+    1 + leading_count
+}
+
+/// Whether `--dump-schema-yaml` is in the raw command-line `args`.
+/// Like [`init_template`], this can't wait for clap since there may not be a config file yet.
+pub fn dump_schema_yaml(args: &[String]) -> bool {
+    leading_options(args)
+        .into_iter()
+        .any(|(arg, _)| arg == "--dump-schema-yaml")
+}
+
+/// Finds the last value provided for the `long` argument in the raw command-line `args`, ignoring
+/// anything after the subcommand.
+fn raw_arg_value(args: &[String], long: &str) -> Option<String> {
+    leading_options(args)
+        .into_iter()
+        .filter_map(|(arg, value)| {
+            if arg == long {
+                return value.cloned();
+            }
+
+            arg.strip_prefix(long)
+                .and_then(|rest| rest.strip_prefix('='))
+                .map(|inline_value| inline_value.to_string())
+        })
+        .next_back()
+}
+
+/// Whether `--trace-config` is in the raw command-line `args`.
+/// Like [`from_directory`], this can't wait for clap since it affects how the config is loaded.
+pub fn trace_config(args: &[String]) -> bool {
+    leading_options(args)
+        .into_iter()
+        .any(|(arg, _)| arg == "--trace-config")
+}
+
+/// Finds the choice provided with `--color` in the raw command-line `args`, defaulting to
+/// [`ColorChoice::Auto`].
+/// Like [`trace_config`], this can't wait for clap since errors loading the config are colored
+/// too.
+pub fn color_choice(args: &[String]) -> ColorChoice {
+    raw_arg_value(args, "--color")
+        .and_then(|value| ColorChoice::from_str(&value, true).ok())
+        .unwrap_or(ColorChoice::Auto)
+}
+
+/// Whether `--force-tty` or `--no-tty` is in the raw command-line `args`, overriding whether stdin
+/// is a terminal. The last one provided wins.
+/// Like [`trace_config`], this can't wait for clap since it affects where the config is loaded
+/// from.
+pub fn tty_override(args: &[String]) -> Option<bool> {
+    leading_options(args)
+        .into_iter()
+        .filter_map(|(arg, _)| match arg.as_str() {
+            "--force-tty" => Some(true),
+            "--no-tty" => Some(false),
+            _ => None,
+        })
+        .next_back()
+}
+
+/// Whether stdin is a terminal, unless that's overridden with `tty_override`.
+pub fn stdin_is_terminal(tty_override: Option<bool>) -> bool {
+    tty_override.unwrap_or_else(|| io::stdin().is_terminal())
+}
+
+/// Forces colors on or off according to `color_choice`.
+/// [`ColorChoice::Auto`] leaves it to the `colored` crate, which checks whether stdout is a
+/// terminal along with the `NO_COLOR`, `CLICOLOR` and `CLICOLOR_FORCE` environment variables.
+pub fn apply_color_choice(color_choice: ColorChoice) {
+    match color_choice {
+        ColorChoice::Auto => colored::control::unset_override(),
+        ColorChoice::Always => colored::control::set_override(true),
+        ColorChoice::Never => colored::control::set_override(false),
+    }
+}
+
 type SubcommandSearchResult = (CommandConfig, VariableConfigMap, ArgMatches);
 
+/// Describes each argument present in the provided [`ArgMatches`], one line per argument.
+/// Each line contains the argument's ID, its values, and where the values came from.
+pub fn describe_arg_matches(arg_matches: &ArgMatches) -> Vec<String> {
+    arg_matches
+        .ids()
+        .map(|id| {
+            let values: Vec<String> = arg_matches
+                .get_raw(id.as_str())
+                .unwrap_or_default()
+                .map(|value| format!("{:?}", value.to_string_lossy()))
+                .collect();
+
+            let source = match arg_matches.value_source(id.as_str()) {
+                Some(ValueSource::CommandLine) => "command line",
+                Some(ValueSource::EnvVariable) => "environment variable",
+                Some(ValueSource::DefaultValue) => "default value",
+                _ => "unknown",
+            };
+
+            format!("{id} = [{}] ({source})", values.join(", "))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::args::{ArgumentResolver, ClapArgumentResolver};
     use crate::config::ArgumentConfigVariant::Named;
     use crate::config::OneOrManyPlatforms::{Many, One};
     use crate::config::RawCommandConfigVariant::Shorthand;
     use crate::config::{
-        ActionConfig, AliasActionConfig, CommandConfig, DingusOptions, ExecutionVariableConfig,
-        LiteralVariableConfig, ManyPlatforms, OnePlatform, Platform, PositionalArgumentConfig,
-        PromptConfig, PromptVariableConfig, SingleActionConfig, VariableConfig,
+        ActionConfig, AliasActionConfig, ArgumentVariableConfig, BashCommandConfig, CommandConfig,
+        DingusOptions, Encoding, ExecutionVariableConfig, LiteralVariableConfig, ManyPlatforms,
+        OnePlatform, Platform, PositionalArgumentConfig, PromptConfig, PromptVariableConfig,
+        ShellCommandConfigVariant, SingleActionConfig, StdinConfig, Trim, VariableConfig,
+        VariableOverrideMap,
     };
     use crate::platform::MockPlatformProvider;
+    use colored::Colorize;
 
     fn mock_platform_provider() -> Box<dyn PlatformProvider> {
         let mut platform_provider = MockPlatformProvider::new();
@@ -277,7 +904,7 @@ mod tests {
                 name: None,
                 platform: None,
                 description: Some("Sub 1 description".to_string()),
-                hidden: false,
+                hidden: None,
                 variables: Default::default(),
                 commands: Default::default(),
                 action: Some(ActionConfig::SingleStep(SingleActionConfig {
@@ -285,6 +912,7 @@ mod tests {
                         "echo \"Hello, World!\"".to_string(),
                     )),
                 })),
+                ..Default::default()
             },
         );
 
@@ -300,7 +928,7 @@ mod tests {
                 name: None,
                 platform: None,
                 description: Some("Sub 2 description".to_string()),
-                hidden: false,
+                hidden: None,
                 variables: subcommand_variables,
                 commands: Default::default(),
                 action: Some(ActionConfig::SingleStep(SingleActionConfig {
@@ -308,6 +936,7 @@ mod tests {
                         "echo \"Hello, World!\"".to_string(),
                     )),
                 })),
+                ..Default::default()
             },
         );
 
@@ -359,6 +988,15 @@ mod tests {
                 )),
                 argument: None,
                 environment_variable_name: None,
+                encoding: Encoding::Strict,
+                trim: Trim::End,
+                prompt_if_missing: None,
+                transform: vec![],
+                export: true,
+                r#match: None,
+                group: None,
+                lazy: false,
+                sensitive: false,
             }),
         );
         subcommand_variables.insert(
@@ -368,12 +1006,19 @@ mod tests {
                     description: Some("Sub arg 2".to_string()),
                     long: "sub-arg-2".to_string(),
                     short: None,
+                    aliases: vec![],
+                    conflicts_with: vec![],
+                    requires: vec![],
                 })),
                 environment_variable_name: None,
                 prompt: PromptConfig {
                     message: "What's your name?".to_string(),
                     options: Default::default(),
+                    help: None,
                 },
+                transform: vec![],
+                export: true,
+                sensitive: false,
             }),
         );
 
@@ -384,7 +1029,7 @@ mod tests {
                 name: None,
                 platform: None,
                 description: None,
-                hidden: false,
+                hidden: None,
                 variables: subcommand_variables,
                 commands: Default::default(),
                 action: Some(ActionConfig::SingleStep(SingleActionConfig {
@@ -392,6 +1037,7 @@ mod tests {
                         "echo \"Hello, World!\"".to_string(),
                     )),
                 })),
+                ..Default::default()
             },
         );
 
@@ -406,6 +1052,10 @@ mod tests {
                 value: "bar".to_string(),
                 argument: Some(ArgumentConfigVariant::Shorthand("parent-arg-2".to_string())),
                 environment_variable_name: None,
+                prompt_if_missing: None,
+                transform: vec![],
+                export: true,
+                sensitive: false,
             }),
         );
 
@@ -422,7 +1072,7 @@ mod tests {
         // Assert
         let command = created_subcommands.get(0).unwrap();
         let command_args: Vec<&Arg> = command.get_arguments().collect();
-        assert_eq!(command_args.len(), 2);
+        assert_eq!(command_args.len(), 3);
 
         let parent_arg_1 = command_args
             .iter()
@@ -458,12 +1108,19 @@ mod tests {
                     description: Some("Sub arg 2".to_string()),
                     long: "sub-arg-2".to_string(),
                     short: None,
+                    aliases: vec![],
+                    conflicts_with: vec![],
+                    requires: vec![],
                 })),
                 environment_variable_name: None,
                 prompt: PromptConfig {
                     message: "What's your name?".to_string(),
                     options: Default::default(),
+                    help: None,
                 },
+                transform: vec![],
+                export: true,
+                sensitive: false,
             }),
         );
 
@@ -474,7 +1131,7 @@ mod tests {
                 name: None,
                 platform: None,
                 description: None,
-                hidden: false,
+                hidden: None,
                 variables: subsubcommand_variables,
                 commands: Default::default(),
                 action: Some(ActionConfig::SingleStep(SingleActionConfig {
@@ -482,6 +1139,7 @@ mod tests {
                         "echo \"Hello, World!\"".to_string(),
                     )),
                 })),
+                ..Default::default()
             },
         );
 
@@ -494,6 +1152,15 @@ mod tests {
                 )),
                 argument: Some(ArgumentConfigVariant::Shorthand("sub-arg-1".to_string())),
                 environment_variable_name: None,
+                encoding: Encoding::Strict,
+                trim: Trim::End,
+                prompt_if_missing: None,
+                transform: vec![],
+                export: true,
+                r#match: None,
+                group: None,
+                lazy: false,
+                sensitive: false,
             }),
         );
 
@@ -504,7 +1171,7 @@ mod tests {
                 name: None,
                 platform: None,
                 description: None,
-                hidden: false,
+                hidden: None,
                 variables: subcommand_variables,
                 commands: subsubcommands,
                 action: Some(ActionConfig::SingleStep(SingleActionConfig {
@@ -512,6 +1179,7 @@ mod tests {
                         "echo \"Hello, World!\"".to_string(),
                     )),
                 })),
+                ..Default::default()
             },
         );
 
@@ -530,7 +1198,7 @@ mod tests {
         let subcommands: Vec<&Command> = command.get_subcommands().collect();
         let subcommand = subcommands.get(0).unwrap();
         let subcommand_args: Vec<&Arg> = subcommand.get_arguments().collect();
-        assert_eq!(subcommand_args.len(), 2);
+        assert_eq!(subcommand_args.len(), 3);
 
         let parent_arg = subcommand_args
             .iter()
@@ -557,7 +1225,7 @@ mod tests {
                 name: None,
                 platform: None,
                 description: None,
-                hidden: false,
+                hidden: None,
                 variables: Default::default(),
                 commands: Default::default(),
                 action: Some(ActionConfig::SingleStep(SingleActionConfig {
@@ -565,6 +1233,7 @@ mod tests {
                         "echo \"Hello, World!\"".to_string(),
                     )),
                 })),
+                ..Default::default()
             },
         );
 
@@ -575,10 +1244,11 @@ mod tests {
                 name: None,
                 platform: None,
                 description: None,
-                hidden: false,
+                hidden: None,
                 variables: Default::default(),
                 commands: subsubcommands,
                 action: None,
+                ..Default::default()
             },
         );
 
@@ -611,12 +1281,13 @@ mod tests {
                 name: None,
                 platform: None,
                 description: None,
-                hidden: false,
+                hidden: None,
                 variables: Default::default(),
                 commands: Default::default(),
                 action: Some(ActionConfig::Alias(AliasActionConfig {
                     alias: "docker compose".to_string(),
                 })),
+                ..Default::default()
             },
         );
 
@@ -647,6 +1318,128 @@ mod tests {
         assert_eq!(alias_arg.is_trailing_var_arg_set(), true);
     }
 
+    #[test]
+    fn create_commands_accepts_any_args_for_ambiguous_command() {
+        // Arrange
+        let mut commands = CommandConfigMap::new();
+        commands.insert(
+            "deploy".to_string(),
+            CommandConfig {
+                action: Some(ActionConfig::SingleStep(SingleActionConfig {
+                    action: ExecutionConfigVariant::RawCommand(Shorthand(
+                        "./deploy.sh".to_string(),
+                    )),
+                })),
+                candidates: vec![CommandConfig::default()],
+                ..Default::default()
+            },
+        );
+
+        let platform_provider = mock_platform_provider();
+
+        // Act
+        let created_commands = create_commands(
+            &DingusOptions::default(),
+            &commands,
+            &VariableConfigMap::new(),
+            &Box::new(platform_provider),
+        );
+
+        // Assert
+        let result = Command::new("dingus")
+            .subcommands(created_commands)
+            .try_get_matches_from(vec!["dingus", "deploy", "build", "--release"]);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn create_commands_captures_passthrough_args() {
+        // Arrange
+        let mut commands = CommandConfigMap::new();
+        commands.insert(
+            "test".to_string(),
+            CommandConfig {
+                passthrough: Some(true),
+                action: Some(ActionConfig::SingleStep(SingleActionConfig {
+                    action: ExecutionConfigVariant::RawCommand(Shorthand("cargo test".to_string())),
+                })),
+                ..Default::default()
+            },
+        );
+
+        let platform_provider = mock_platform_provider();
+
+        // Act
+        let created_commands = create_commands(
+            &DingusOptions::default(),
+            &commands,
+            &VariableConfigMap::new(),
+            &Box::new(platform_provider),
+        );
+
+        // Assert
+        let arg_matches = Command::new("dingus")
+            .subcommands(created_commands)
+            .get_matches_from(vec!["dingus", "test", "--", "--nocapture", "parser"]);
+        let (_, subcommand_matches) = arg_matches.subcommand().unwrap();
+        let passthrough_args: Vec<&String> = subcommand_matches
+            .get_many::<String>(PASSTHROUGH_ARGS_NAME)
+            .unwrap()
+            .collect();
+        assert_eq!(passthrough_args, vec!["--nocapture", "parser"]);
+    }
+
+    #[test]
+    fn create_commands_captures_extra_args() {
+        // Arrange
+        let mut commands = CommandConfigMap::new();
+        commands.insert(
+            "run".to_string(),
+            CommandConfig {
+                action: Some(ActionConfig::SingleStep(SingleActionConfig {
+                    action: ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+                        BashCommandConfig {
+                            working_directory: None,
+                            command: "cargo run".to_string(),
+                            run_as: None,
+                            stdout_file: None,
+                            stderr_file: None,
+                            append: false,
+                            allow_extra_args: true,
+                            banner: None,
+                            stdin: StdinConfig::Inherit,
+                            label: None,
+                            max_output_bytes: None,
+                            extra_args: vec![],
+                        },
+                    )),
+                })),
+                ..Default::default()
+            },
+        );
+
+        let platform_provider = mock_platform_provider();
+
+        // Act
+        let created_commands = create_commands(
+            &DingusOptions::default(),
+            &commands,
+            &VariableConfigMap::new(),
+            &Box::new(platform_provider),
+        );
+
+        // Assert
+        let arg_matches = Command::new("dingus")
+            .subcommands(created_commands)
+            .get_matches_from(vec!["dingus", "run", "--release", "name=Dingus"]);
+        let (_, subcommand_matches) = arg_matches.subcommand().unwrap();
+        let extra_args: Vec<&String> = subcommand_matches
+            .get_many::<String>(EXTRA_ARGS_NAME)
+            .unwrap()
+            .collect();
+        assert_eq!(extra_args, vec!["--release", "name=Dingus"]);
+    }
+
     #[test]
     fn create_commands_creates_correct_command_with_custom_name() {
         // Arrange
@@ -657,7 +1450,7 @@ mod tests {
                 name: Some("demonstration".to_string()),
                 platform: None,
                 description: None,
-                hidden: false,
+                hidden: None,
                 variables: Default::default(),
                 commands: Default::default(),
                 action: Some(ActionConfig::SingleStep(SingleActionConfig {
@@ -665,6 +1458,7 @@ mod tests {
                         "echo \"Hello, World!\"".to_string(),
                     )),
                 })),
+                ..Default::default()
             },
         );
 
@@ -683,6 +1477,55 @@ mod tests {
         assert_eq!(target_command.get_name(), "demonstration");
     }
 
+    #[test]
+    fn create_commands_falls_back_to_leading_comment_for_description() {
+        // Arrange
+        let config = crate::config::parse_config(
+            &"commands:
+  build:
+    action:
+      bash: |
+        # Builds the app
+        cargo build
+  test:
+    description: Runs the tests
+    action:
+      bash: |
+        # Not the description
+        cargo test
+  lint:
+    action:
+      bash: |
+        #!/usr/bin/env bash
+        cargo clippy
+"
+            .to_string(),
+            Platform::Linux,
+        )
+        .unwrap();
+
+        // Act
+        let created_commands = create_commands(
+            &DingusOptions::default(),
+            &config.commands,
+            &VariableConfigMap::new(),
+            &mock_platform_provider(),
+        );
+
+        // Assert
+        let about = |name: &str| {
+            created_commands
+                .iter()
+                .find(|command| command.get_name() == name)
+                .unwrap()
+                .get_about()
+                .map(|about| about.to_string())
+        };
+        assert_eq!(about("build"), Some("Builds the app".to_string()));
+        assert_eq!(about("test"), Some("Runs the tests".to_string()));
+        assert_eq!(about("lint"), None);
+    }
+
     #[test]
     fn create_commands_excludes_commands_for_other_platforms() {
         // Arrange
@@ -692,10 +1535,10 @@ mod tests {
             CommandConfig {
                 name: Some("demo".to_string()),
                 platform: Some(One(OnePlatform {
-                    platform: Platform::Linux,
+                    platform: Platform::Linux.into(),
                 })),
                 description: Some("Demo command on Linux.".to_string()),
-                hidden: false,
+                hidden: None,
                 variables: Default::default(),
                 commands: Default::default(),
                 action: Some(ActionConfig::SingleStep(SingleActionConfig {
@@ -703,6 +1546,7 @@ mod tests {
                         "echo \"Hello, World!\"".to_string(),
                     )),
                 })),
+                ..Default::default()
             },
         );
 
@@ -711,10 +1555,10 @@ mod tests {
             CommandConfig {
                 name: Some("demo".to_string()),
                 platform: Some(One(OnePlatform {
-                    platform: Platform::MacOS,
+                    platform: Platform::MacOS.into(),
                 })),
                 description: Some("Demo command on macOS.".to_string()),
-                hidden: false,
+                hidden: None,
                 variables: Default::default(),
                 commands: Default::default(),
                 action: Some(ActionConfig::SingleStep(SingleActionConfig {
@@ -722,6 +1566,7 @@ mod tests {
                         "echo \"Hello, World!\"".to_string(),
                     )),
                 })),
+                ..Default::default()
             },
         );
 
@@ -730,10 +1575,10 @@ mod tests {
             CommandConfig {
                 name: Some("demo-nix".to_string()),
                 platform: Some(Many(ManyPlatforms {
-                    platforms: vec![Platform::Linux, Platform::MacOS],
+                    platforms: vec![Platform::Linux.into(), Platform::MacOS.into()],
                 })),
                 description: Some("Demo command on Unix.".to_string()),
-                hidden: false,
+                hidden: None,
                 variables: Default::default(),
                 commands: Default::default(),
                 action: Some(ActionConfig::SingleStep(SingleActionConfig {
@@ -741,6 +1586,7 @@ mod tests {
                         "echo \"Hello, World!\"".to_string(),
                     )),
                 })),
+                ..Default::default()
             },
         );
 
@@ -749,10 +1595,10 @@ mod tests {
             CommandConfig {
                 name: Some("demo".to_string()),
                 platform: Some(One(OnePlatform {
-                    platform: Platform::Windows,
+                    platform: Platform::Windows.into(),
                 })),
                 description: Some("Demo command on Windows.".to_string()),
-                hidden: false,
+                hidden: None,
                 variables: Default::default(),
                 commands: Default::default(),
                 action: Some(ActionConfig::SingleStep(SingleActionConfig {
@@ -760,6 +1606,7 @@ mod tests {
                         "Write-Host \"Hello, World!\"".to_string(),
                     )),
                 })),
+                ..Default::default()
             },
         );
 
@@ -812,6 +1659,10 @@ mod tests {
                 value: "bar".to_string(),
                 argument: None,
                 environment_variable_name: None,
+                prompt_if_missing: None,
+                transform: vec![],
+                export: true,
+                sensitive: false,
             }),
         );
         variables.insert(
@@ -822,6 +1673,15 @@ mod tests {
                 )),
                 argument: Some(ArgumentConfigVariant::Shorthand("var-3".to_string())),
                 environment_variable_name: None,
+                encoding: Encoding::Strict,
+                trim: Trim::End,
+                prompt_if_missing: None,
+                transform: vec![],
+                export: true,
+                r#match: None,
+                group: None,
+                lazy: false,
+                sensitive: false,
             }),
         );
         variables.insert(
@@ -831,12 +1691,19 @@ mod tests {
                     description: Some("Fourth variable".to_string()),
                     long: "name".to_string(),
                     short: Some('v'),
+                    aliases: vec![],
+                    conflicts_with: vec![],
+                    requires: vec![],
                 })),
                 environment_variable_name: None,
                 prompt: PromptConfig {
                     message: "What's your name?".to_string(),
                     options: Default::default(),
+                    help: None,
                 },
+                transform: vec![],
+                export: true,
+                sensitive: false,
             }),
         );
         variables.insert(
@@ -846,13 +1713,19 @@ mod tests {
                     PositionalArgumentConfig {
                         description: Some("Fifth variable".to_string()),
                         position: 1,
+                        conflicts_with: vec![],
+                        requires: vec![],
                     },
                 )),
                 environment_variable_name: None,
                 prompt: PromptConfig {
                     message: "What's your age?".to_string(),
                     options: Default::default(),
+                    help: None,
                 },
+                transform: vec![],
+                export: true,
+                sensitive: false,
             }),
         );
 
@@ -886,6 +1759,16 @@ mod tests {
             print_commands: false,
             print_variables: false,
             auto_args: true,
+            change_directory: true,
+            workdir_anchor: Default::default(),
+            bash_path: "bash".to_string(),
+            watch: Default::default(),
+            values_file: None,
+            cancel_exit_code: 130,
+            max_output_bytes: None,
+            program_name: None,
+            on_success: None,
+            on_failure: None,
         };
 
         let mut variables = VariableConfigMap::new();
@@ -895,6 +1778,10 @@ mod tests {
                 value: "foo".to_string(),
                 argument: None,
                 environment_variable_name: None,
+                prompt_if_missing: None,
+                transform: vec![],
+                export: true,
+                sensitive: false,
             }),
         );
 
@@ -904,6 +1791,10 @@ mod tests {
                 value: "bar".to_string(),
                 argument: Some(ArgumentConfigVariant::Shorthand("existing".to_string())),
                 environment_variable_name: None,
+                prompt_if_missing: None,
+                transform: vec![],
+                export: true,
+                sensitive: false,
             }),
         );
 
@@ -921,6 +1812,129 @@ mod tests {
         assert_eq!(var2.get_default_values(), ["bar"]);
     }
 
+    #[test]
+    fn create_args_registers_aliases() {
+        // Arrange
+        let options = DingusOptions::default();
+
+        let mut variables = VariableConfigMap::new();
+        variables.insert(
+            "message".to_string(),
+            VariableConfig::Argument(ArgumentVariableConfig {
+                argument: Named(NamedArgumentConfig {
+                    description: None,
+                    long: "message".to_string(),
+                    short: None,
+                    aliases: vec!["msg".to_string()],
+                    conflicts_with: vec![],
+                    requires: vec![],
+                }),
+                environment_variable_name: None,
+                transform: vec![],
+                export: true,
+                default_from_command: None,
+                trim: Trim::End,
+                sensitive: false,
+            }),
+        );
+
+        let command = Command::new("dingus").args(create_args(&options, &variables));
+
+        // Act
+        let long_matches = command
+            .clone()
+            .get_matches_from(vec!["dingus", "--message", "Hello"]);
+        let alias_matches = command.get_matches_from(vec!["dingus", "--msg", "Hello"]);
+
+        // Assert
+        for matches in [long_matches, alias_matches] {
+            let arg_resolver = ClapArgumentResolver::from_arg_matches(&matches);
+            assert_eq!(
+                arg_resolver.get(&"message".to_string()),
+                Some("Hello".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn create_args_rejects_conflicting_args() {
+        // Arrange
+        let config = crate::config::parse_config(
+            &"variables:
+  version:
+    arg:
+      long: version
+      conflicts_with: [branch, commit]
+  branch:
+    arg: branch
+  commit: HEAD
+commands: {}
+"
+            .to_string(),
+            Platform::Linux,
+        )
+        .unwrap();
+
+        let command = Command::new("dingus").args(create_args(&config.options, &config.variables));
+
+        // Act
+        let conflicting_result = command.clone().try_get_matches_from(vec![
+            "dingus",
+            "--version",
+            "1.2.3",
+            "--branch",
+            "main",
+        ]);
+        let single_result = command.try_get_matches_from(vec!["dingus", "--version", "1.2.3"]);
+
+        // Assert
+        assert_eq!(
+            conflicting_result.unwrap_err().kind(),
+            clap::error::ErrorKind::ArgumentConflict
+        );
+        assert!(single_result.is_ok());
+    }
+
+    #[test]
+    fn create_args_rejects_args_without_their_requirements() {
+        // Arrange
+        let config = crate::config::parse_config(
+            &"variables:
+  deploy:
+    arg:
+      long: deploy
+      requires: [target]
+  target:
+    arg: target
+commands: {}
+"
+            .to_string(),
+            Platform::Linux,
+        )
+        .unwrap();
+
+        let command = Command::new("dingus").args(create_args(&config.options, &config.variables));
+
+        // Act
+        let missing_result = command
+            .clone()
+            .try_get_matches_from(vec!["dingus", "--deploy", "yes"]);
+        let satisfied_result = command.try_get_matches_from(vec![
+            "dingus",
+            "--deploy",
+            "yes",
+            "--target",
+            "production",
+        ]);
+
+        // Assert
+        assert_eq!(
+            missing_result.unwrap_err().kind(),
+            clap::error::ErrorKind::MissingRequiredArgument
+        );
+        assert!(satisfied_result.is_ok());
+    }
+
     #[test]
     fn find_subcommand_finds_top_level_command() {
         // Arrange
@@ -943,7 +1957,7 @@ mod tests {
                 name: None,
                 platform: None,
                 description: Some("Top-level command".to_string()),
-                hidden: false,
+                hidden: None,
                 variables: subcommand_variables,
                 commands: Default::default(),
                 action: Some(ActionConfig::SingleStep(SingleActionConfig {
@@ -951,6 +1965,7 @@ mod tests {
                         "echo \"Hello, World!\"".to_string(),
                     )),
                 })),
+                ..Default::default()
             },
         );
 
@@ -960,6 +1975,7 @@ mod tests {
             variables: root_variables,
             commands: commands,
             options: DingusOptions::default(),
+            ..Default::default()
         };
 
         let platform_provider = mock_platform_provider();
@@ -1014,7 +2030,7 @@ mod tests {
                 name: None,
                 platform: None,
                 description: Some("Subcommand".to_string()),
-                hidden: false,
+                hidden: None,
                 variables: subcommand_variables,
                 commands: CommandConfigMap::default(),
                 action: Some(ActionConfig::SingleStep(SingleActionConfig {
@@ -1022,6 +2038,7 @@ mod tests {
                         "echo \"Hello, World!\"".to_string(),
                     )),
                 })),
+                ..Default::default()
             },
         );
 
@@ -1032,7 +2049,7 @@ mod tests {
                 name: None,
                 platform: None,
                 description: Some("Mid-level command".to_string()),
-                hidden: false,
+                hidden: None,
                 variables: command_variables,
                 commands: subcommands,
                 action: Some(ActionConfig::SingleStep(SingleActionConfig {
@@ -1040,6 +2057,7 @@ mod tests {
                         "echo \"Hello, World!\"".to_string(),
                     )),
                 })),
+                ..Default::default()
             },
         );
 
@@ -1050,7 +2068,7 @@ mod tests {
                 name: None,
                 platform: None,
                 description: Some("Top-level command".to_string()),
-                hidden: false,
+                hidden: None,
                 variables: parent_command_variables,
                 commands: target_commands,
                 action: Some(ActionConfig::SingleStep(SingleActionConfig {
@@ -1058,6 +2076,7 @@ mod tests {
                         "echo \"Hello, World!\"".to_string(),
                     )),
                 })),
+                ..Default::default()
             },
         );
 
@@ -1067,6 +2086,7 @@ mod tests {
             variables: root_variables,
             commands: parent_commands,
             options: DingusOptions::default(),
+            ..Default::default()
         };
 
         let platform_provider = mock_platform_provider();
@@ -1119,7 +2139,7 @@ mod tests {
                 name: None,
                 platform: None,
                 description: Some("Bottom-level command".to_string()),
-                hidden: false,
+                hidden: None,
                 variables: command_variables,
                 commands: CommandConfigMap::new(),
                 action: Some(ActionConfig::SingleStep(SingleActionConfig {
@@ -1127,6 +2147,7 @@ mod tests {
                         "echo \"Hello, World!\"".to_string(),
                     )),
                 })),
+                ..Default::default()
             },
         );
 
@@ -1137,7 +2158,7 @@ mod tests {
                 name: None,
                 platform: None,
                 description: Some("Top-level command".to_string()),
-                hidden: false,
+                hidden: None,
                 variables: parent_command_variables,
                 commands: target_commands,
                 action: Some(ActionConfig::SingleStep(SingleActionConfig {
@@ -1145,6 +2166,7 @@ mod tests {
                         "echo \"Hello, World!\"".to_string(),
                     )),
                 })),
+                ..Default::default()
             },
         );
 
@@ -1154,6 +2176,7 @@ mod tests {
             variables: root_variables,
             commands: parent_commands,
             options: DingusOptions::default(),
+            ..Default::default()
         };
 
         let platform_provider = mock_platform_provider();
@@ -1186,7 +2209,7 @@ mod tests {
                 name: Some("command".to_string()),
                 platform: None,
                 description: Some("Command with custom name".to_string()),
-                hidden: false,
+                hidden: None,
                 variables: Default::default(),
                 commands: Default::default(),
                 action: Some(ActionConfig::SingleStep(SingleActionConfig {
@@ -1194,6 +2217,7 @@ mod tests {
                         "echo \"Hello, World!\"".to_string(),
                     )),
                 })),
+                ..Default::default()
             },
         );
 
@@ -1203,6 +2227,7 @@ mod tests {
             variables: Default::default(),
             commands: commands,
             options: DingusOptions::default(),
+            ..Default::default()
         };
 
         let platform_provider = mock_platform_provider();
@@ -1230,7 +2255,7 @@ mod tests {
             "cmd".to_string(),
             CommandConfig {
                 name: Some("command".to_string()),
-                hidden: true,
+                hidden: Some(true),
                 platform: None,
                 description: Some("Command with custom name".to_string()),
                 variables: Default::default(),
@@ -1240,6 +2265,7 @@ mod tests {
                         "echo \"Hello, World!\"".to_string(),
                     )),
                 })),
+                ..Default::default()
             },
         );
 
@@ -1249,6 +2275,7 @@ mod tests {
             variables: Default::default(),
             commands: commands,
             options: DingusOptions::default(),
+            ..Default::default()
         };
 
         let platform_provider = mock_platform_provider();
@@ -1268,4 +2295,371 @@ mod tests {
             Some("Command with custom name".to_string())
         );
     }
+
+    #[test]
+    fn create_root_command_lists_grouped_commands_under_their_group() {
+        // Arrange
+        let mut commands = CommandConfigMap::new();
+        commands.insert(
+            "deploy".to_string(),
+            CommandConfig {
+                group: Some("Deployment".to_string()),
+                description: Some("Deploys the app".to_string()),
+                action: Some(ActionConfig::SingleStep(SingleActionConfig {
+                    action: ExecutionConfigVariant::RawCommand(Shorthand(
+                        "./deploy.sh".to_string(),
+                    )),
+                })),
+                ..Default::default()
+            },
+        );
+        commands.insert(
+            "build".to_string(),
+            CommandConfig {
+                description: Some("Builds the app".to_string()),
+                action: Some(ActionConfig::SingleStep(SingleActionConfig {
+                    action: ExecutionConfigVariant::RawCommand(Shorthand(
+                        "cargo build".to_string(),
+                    )),
+                })),
+                ..Default::default()
+            },
+        );
+
+        let config = Config {
+            commands,
+            ..Default::default()
+        };
+
+        let platform_provider = mock_platform_provider();
+
+        // Act
+        let mut root_command = create_root_command(&config, &Box::new(platform_provider));
+        let help = root_command.render_help().to_string();
+
+        // Assert
+        assert!(help.contains("Deployment:\n  deploy  Deploys the app\n"));
+
+        let (_, default_group) = help.split_once("Commands:").unwrap();
+        assert!(default_group.contains("build"));
+        assert!(!default_group.contains("deploy"));
+
+        // Grouped commands can still be executed
+        let matches = root_command
+            .clone()
+            .get_matches_from(vec!["dingus", "deploy"]);
+        let (found_command, _, _) =
+            find_subcommand(&matches, &root_command, &config.commands, &config.variables).unwrap();
+        assert_eq!(found_command.group, Some("Deployment".to_string()));
+
+        // Grouped commands are still suggested by clap
+        let error = root_command
+            .try_get_matches_from(vec!["dingus", "deplo"])
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("'deploy'"));
+    }
+
+    #[test]
+    fn create_root_command_uses_program_name() {
+        // Arrange
+        let mut commands = CommandConfigMap::new();
+        commands.insert(
+            "build".to_string(),
+            CommandConfig {
+                action: Some(ActionConfig::SingleStep(SingleActionConfig {
+                    action: ExecutionConfigVariant::RawCommand(Shorthand(
+                        "cargo build".to_string(),
+                    )),
+                })),
+                ..Default::default()
+            },
+        );
+
+        let config = Config {
+            commands,
+            options: DingusOptions {
+                program_name: Some("mytool".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let platform_provider = mock_platform_provider();
+
+        // Act
+        let mut root_command = create_root_command(&config, &Box::new(platform_provider));
+        root_command.build();
+        let usage = root_command.render_usage().to_string();
+        let build_usage = root_command
+            .find_subcommand_mut("build")
+            .unwrap()
+            .render_usage()
+            .to_string();
+
+        // Assert
+        assert_eq!(root_command.get_name(), "mytool");
+        assert!(usage.contains("mytool [OPTIONS] <COMMAND>"));
+        assert!(build_usage.contains("mytool build"));
+    }
+
+    #[test]
+    fn matched_command_path_includes_every_level() {
+        // Arrange
+        let arg_matches = Command::new("dingus")
+            .subcommand(Command::new("docker").subcommand(Command::new("deploy")))
+            .get_matches_from(vec!["dingus", "docker", "deploy"]);
+
+        // Act
+        let command_path = matched_command_path(&arg_matches);
+
+        // Assert
+        assert_eq!(
+            command_path,
+            vec!["docker".to_string(), "deploy".to_string()]
+        );
+    }
+
+    #[test]
+    fn color_choice_finds_color_arg() {
+        // Arrange
+        let args: Vec<String> = ["dingus", "--color=never", "deploy"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let default_args: Vec<String> = ["dingus", "deploy", "--", "--color", "always"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+
+        // Act
+        let choice = color_choice(&args);
+        let default_choice = color_choice(&default_args);
+
+        // Assert
+        assert_eq!(choice, ColorChoice::Never);
+        assert_eq!(default_choice, ColorChoice::Auto);
+    }
+
+    #[test]
+    fn apply_color_choice_always_forces_colors() {
+        // Act
+        apply_color_choice(ColorChoice::Always);
+        let colored_text = "Dingus".green().to_string();
+        apply_color_choice(ColorChoice::Auto);
+
+        // Assert
+        assert_eq!(colored_text, "\u{1b}[32mDingus\u{1b}[0m");
+    }
+
+    #[test]
+    fn trace_config_ignores_args_after_separator() {
+        // Arrange
+        let args: Vec<String> = ["dingus", "deploy", "--", "--trace-config"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let traced_args: Vec<String> = ["dingus", "--trace-config", "deploy"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+
+        // Act
+        let traced = trace_config(&traced_args);
+        let not_traced = trace_config(&args);
+
+        // Assert
+        assert!(traced);
+        assert!(!not_traced);
+    }
+
+    #[test]
+    fn dump_schema_yaml_ignores_args_after_subcommand() {
+        // Arrange
+        let args =
+            |args: &[&str]| -> Vec<String> { args.iter().map(|arg| arg.to_string()).collect() };
+
+        // Act
+        let dumped = dump_schema_yaml(&args(&["dingus", "--dump-schema-yaml"]));
+        let not_dumped = dump_schema_yaml(&args(&["dingus", "run", "--dump-schema-yaml"]));
+
+        // Assert
+        assert!(dumped);
+        assert!(!not_dumped);
+    }
+
+    #[test]
+    fn tty_override_uses_last_tty_arg() {
+        // Arrange
+        let args =
+            |args: &[&str]| -> Vec<String> { args.iter().map(|arg| arg.to_string()).collect() };
+
+        // Act
+        let no_tty = tty_override(&args(&["dingus", "--force-tty", "--no-tty", "deploy"]));
+        let force_tty = tty_override(&args(&["dingus", "--force-tty", "deploy"]));
+        let not_overridden = tty_override(&args(&["dingus", "deploy", "--no-tty"]));
+
+        // Assert
+        assert_eq!(no_tty, Some(false));
+        assert_eq!(force_tty, Some(true));
+        assert_eq!(not_overridden, None);
+        assert!(!stdin_is_terminal(no_tty));
+        assert!(stdin_is_terminal(force_tty));
+    }
+
+    #[test]
+    fn from_directory_finds_last_from_arg_before_subcommand() {
+        // Arrange
+        let args: Vec<String> = [
+            "dingus",
+            "--from",
+            "./service-a",
+            "--color",
+            "never",
+            "--from=./service-b",
+            "deploy",
+            "--from",
+            "main",
+            "--",
+            "--from",
+            "./service-c",
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+
+        // Act
+        let directory = from_directory(&args);
+        let missing_directory = from_directory(&args[..1]);
+
+        // Assert
+        assert_eq!(directory, Some(PathBuf::from("./service-b")));
+        assert_eq!(missing_directory, None);
+    }
+
+    #[test]
+    fn describe_arg_matches_includes_provided_values() {
+        // Arrange
+        let mut variables = VariableConfigMap::new();
+        variables.insert(
+            "name".to_string(),
+            VariableConfig::Literal(LiteralVariableConfig {
+                argument: Some(ArgumentConfigVariant::Shorthand("name".to_string())),
+                environment_variable_name: None,
+                value: "Dingus".to_string(),
+                prompt_if_missing: None,
+                transform: vec![],
+                export: true,
+                sensitive: false,
+            }),
+        );
+
+        let mut commands = CommandConfigMap::new();
+        commands.insert(
+            "greet".to_string(),
+            CommandConfig {
+                action: Some(ActionConfig::SingleStep(SingleActionConfig {
+                    action: ExecutionConfigVariant::RawCommand(Shorthand(
+                        "echo \"Hello, $name!\"".to_string(),
+                    )),
+                })),
+                ..Default::default()
+            },
+        );
+
+        let config = Config {
+            variables,
+            commands,
+            ..Default::default()
+        };
+
+        let platform_provider = mock_platform_provider();
+        let root_command = create_root_command(&config, &Box::new(platform_provider));
+
+        let matches = root_command.clone().get_matches_from(vec![
+            "dingus",
+            "greet",
+            "--name",
+            "Godzilla",
+            "--debug-args",
+        ]);
+        let (_, _, subcommand_matches) =
+            find_subcommand(&matches, &root_command, &config.commands, &config.variables).unwrap();
+
+        // Act
+        let lines = describe_arg_matches(&subcommand_matches);
+
+        // Assert
+        assert!(lines.contains(&"name = [\"Godzilla\"] (command line)".to_string()));
+    }
+
+    #[test]
+    fn find_subcommand_applies_overrides_to_inherited_variables() {
+        // Arrange
+        let mut root_variables = VariableConfigMap::new();
+        root_variables.insert(
+            "region".to_string(),
+            VariableConfig::Literal(LiteralVariableConfig {
+                argument: Some(ArgumentConfigVariant::Shorthand("region".to_string())),
+                environment_variable_name: None,
+                value: "us-east-1".to_string(),
+                prompt_if_missing: None,
+                transform: vec![],
+                export: true,
+                sensitive: false,
+            }),
+        );
+
+        let mut overrides = VariableOverrideMap::new();
+        overrides.insert("region".to_string(), "eu-west-1".to_string());
+
+        let mut commands = CommandConfigMap::new();
+        commands.insert(
+            "deploy".to_string(),
+            CommandConfig {
+                overrides,
+                action: Some(ActionConfig::SingleStep(SingleActionConfig {
+                    action: ExecutionConfigVariant::RawCommand(Shorthand(
+                        "echo $region".to_string(),
+                    )),
+                })),
+                ..Default::default()
+            },
+        );
+
+        let config = Config {
+            variables: root_variables,
+            commands,
+            ..Default::default()
+        };
+
+        let platform_provider = mock_platform_provider();
+
+        let root_command = create_root_command(&config, &Box::new(platform_provider));
+
+        // Act
+        let matches = root_command
+            .clone()
+            .get_matches_from(vec!["dingus", "deploy"]);
+        let (_, found_variables, found_matches) =
+            find_subcommand(&matches, &root_command, &config.commands, &config.variables).unwrap();
+
+        // Assert
+        assert_eq!(
+            found_variables.get("region"),
+            Some(&VariableConfig::Literal(LiteralVariableConfig {
+                argument: Some(ArgumentConfigVariant::Shorthand("region".to_string())),
+                environment_variable_name: None,
+                value: "eu-west-1".to_string(),
+                prompt_if_missing: None,
+                transform: vec![],
+                export: true,
+                sensitive: false,
+            }))
+        );
+        assert_eq!(
+            found_matches.get_one::<String>("region"),
+            Some(&"eu-west-1".to_string())
+        );
+    }
 }
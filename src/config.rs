@@ -1,10 +1,11 @@
+use crate::cli::{argument_config, reserved_arg_names};
+use crate::git_source::{cache_directory, fetch_git_source, parse_git_source, GitSourceError};
 use crate::platform::{current_platform_provider, is_current_platform};
+use crate::workdir::expand_tilde;
 use linked_hash_map::LinkedHashMap;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::io::IsTerminal;
 use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::{env, fs, io};
 use thiserror::Error;
 
@@ -19,121 +20,710 @@ commands:
   greet:
     action: echo \"Hello, $name!\"";
 
+/// Built-in configs for common kinds of projects, which can be used with `--init-with`.
+const BUILT_IN_TEMPLATES: [(&str, &str); 3] = [
+    (
+        "node",
+        "description: Node project tasks
+
+commands:
+  install:
+    description: Install dependencies
+    action: npm install
+
+  build:
+    description: Build the project
+    action: npm run build
+
+  test:
+    description: Run the tests
+    action: npm test",
+    ),
+    (
+        "rust",
+        "description: Rust project tasks
+
+commands:
+  build:
+    description: Build the project
+    action: cargo build
+
+  test:
+    description: Run the tests
+    action: cargo test
+
+  lint:
+    description: Check the code for common mistakes
+    action: cargo clippy --all-targets",
+    ),
+    (
+        "go",
+        "description: Go project tasks
+
+commands:
+  build:
+    description: Build the project
+    action: go build ./...
+
+  test:
+    description: Run the tests
+    action: go test ./...
+
+  lint:
+    description: Check the code for common mistakes
+    action: go vet ./...",
+    ),
+];
+
 pub enum Source {
-    Unknown,
     Stdin,
     File(PathBuf),
 }
 
+impl Source {
+    /// The path to the config file, or "stdin" if it wasn't loaded from a file.
+    pub fn display(&self) -> String {
+        match self {
+            Source::File(config_file_path) => config_file_path.display().to_string(),
+            Source::Stdin => "stdin".to_string(),
+        }
+    }
+}
+
 pub struct FoundConfig {
     pub source: Source,
     pub config: Config,
 }
 
-/// Loads the [`Config`] from stdin, or a file in the current directory.
-pub fn load() -> Result<FoundConfig, ConfigError> {
-    let input = io::stdin();
+/// Loads the [`Config`] from stdin, or a file in the current directory when stdin is a terminal.
+/// Config files are overlaid with a local config file next to them, such as `dingus.local.yaml`,
+/// if there is one.
+/// When `trace` is `true`, each import is printed to stderr as it's parsed.
+pub fn load(trace: bool, stdin_is_terminal: bool) -> Result<FoundConfig, ConfigError> {
+    let (source, mut config_text) = read_config_text(
+        &mut io::stdin().lock(),
+        stdin_is_terminal,
+        &env::current_dir().unwrap(),
+    )?;
+
+    if trace {
+        eprintln!("{}", source.display());
+    }
 
-    let mut source = Source::Unknown;
-    let mut config_text = String::new();
+    if let Source::File(config_file_path) = &source {
+        let local_config_path = local_config_path(config_file_path);
+        if local_config_path.is_file() {
+            if trace {
+                eprintln!("{}", local_config_path.display());
+            }
 
-    if input.is_terminal() {
-        let mut found = false;
-        let mut directory = env::current_dir().unwrap();
-        while !found {
-            for config_file_name in CONFIG_FILE_NAMES {
-                let config_file_path = directory.join(config_file_name);
-                if !config_file_path.exists() {
-                    continue;
+            let local_config_text =
+                fs::read_to_string(&local_config_path).map_err(ConfigError::ReadFailed)?;
+            config_text = overlay_config_text(&config_text, &local_config_text).map_err(|err| {
+                ConfigError::OverlayFailed {
+                    path: local_config_path.display().to_string(),
+                    source: err,
                 }
+            })?;
+        }
+    }
 
-                source = Source::File(config_file_path.clone());
-                config_text = fs::read_to_string(config_file_path)
-                    .map_err(|err| ConfigError::ReadFailed(err))?;
-                found = true;
-                break;
-            }
+    let current_platform = current_platform_provider().get_platform();
+    let mut config = parse_config_traced(&config_text, current_platform, trace.then_some(1))?;
 
-            if found {
-                break;
-            }
+    if let Source::File(config_file_path) = &source {
+        set_source(&mut config.commands, config_file_path);
+    }
 
-            if let Some(parent) = directory.parent() {
-                directory = parent.to_owned();
-            } else {
-                break;
+    Ok(FoundConfig { source, config })
+}
+
+/// Reads the config text from `input`, or from the config file in `directory` or its parent
+/// directories when `input` is a terminal.
+fn read_config_text(
+    input: &mut impl Read,
+    is_terminal: bool,
+    directory: &Path,
+) -> Result<(Source, String), ConfigError> {
+    let mut config_text = String::new();
+
+    if is_terminal {
+        let config_file_path = find_config_file(directory).ok_or(ConfigError::FileNotFound)?;
+
+        config_text =
+            fs::read_to_string(&config_file_path).map_err(|err| ConfigError::ReadFailed(err))?;
+        return Ok((Source::File(config_file_path), config_text));
+    }
+
+    input
+        .read_to_string(&mut config_text)
+        .map_err(|err| ConfigError::ReadFailed(err))?;
+    Ok((Source::Stdin, config_text))
+}
+
+/// The path to the local config file which overlays the provided config file, such as
+/// `dingus.local.yaml` for `dingus.yaml`.
+fn local_config_path(config_file_path: &Path) -> PathBuf {
+    let stem = config_file_path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let extension = config_file_path
+        .extension()
+        .unwrap_or_default()
+        .to_string_lossy();
+
+    config_file_path.with_file_name(format!("{stem}.local.{extension}"))
+}
+
+/// Deep-merges `local_config_text` over `config_text`.
+/// Maps are merged key by key, anything else in the local config replaces the value in the base
+/// config, including lists.
+fn overlay_config_text(
+    config_text: &str,
+    local_config_text: &str,
+) -> Result<String, serde_yaml::Error> {
+    let mut config: serde_yaml::Value = serde_yaml::from_str(config_text)?;
+    let local_config: serde_yaml::Value = serde_yaml::from_str(local_config_text)?;
+
+    // An empty local config doesn't change anything
+    if !local_config.is_null() {
+        overlay_value(&mut config, local_config);
+    }
+
+    serde_yaml::to_string(&config)
+}
+
+fn overlay_value(value: &mut serde_yaml::Value, overlay: serde_yaml::Value) {
+    match (value, overlay) {
+        (serde_yaml::Value::Mapping(mapping), serde_yaml::Value::Mapping(overlay_mapping)) => {
+            for (key, overlay_value_for_key) in overlay_mapping {
+                match mapping.get_mut(&key) {
+                    Some(existing) => overlay_value(existing, overlay_value_for_key),
+                    None => {
+                        mapping.insert(key, overlay_value_for_key);
+                    }
+                }
             }
         }
 
-        if !found {
-            return Err(ConfigError::FileNotFound);
-        }
-    } else {
-        source = Source::Stdin;
-        input
-            .lock()
-            .read_to_string(&mut config_text)
-            .map_err(|err| ConfigError::ReadFailed(err))?;
-    };
+        (value, overlay) => *value = overlay,
+    }
+}
 
-    let current_platform = current_platform_provider().get_platform();
-    let config = parse_config(&config_text, current_platform)?;
-    Ok(FoundConfig { source, config })
+/// Finds the config file in `directory`, or the closest of its parent directories.
+fn find_config_file(directory: &Path) -> Option<PathBuf> {
+    directory.ancestors().find_map(|directory| {
+        CONFIG_FILE_NAMES
+            .iter()
+            .map(|config_file_name| directory.join(config_file_name))
+            .find(|config_file_path| config_file_path.exists())
+    })
 }
 
 /// Creates a new config file in the current directory.
+/// The template file from the `DINGUS_TEMPLATE` environment variable, or the user's
+/// `dingus/template.yaml` config file, is used if present. Otherwise, a default config is written.
 pub fn init() -> Result<String, ConfigError> {
     let file_name = CONFIG_FILE_NAMES[0];
 
-    fs::write(file_name, DEFAULT_CONFIG_FILE).map_err(|io_err| ConfigError::WriteFailed(io_err))?;
+    let template_path = env::var("DINGUS_TEMPLATE").ok().map(PathBuf::from);
+    let config_text = read_init_template(template_path, default_template_path())?;
+
+    fs::write(file_name, config_text).map_err(|io_err| ConfigError::WriteFailed(io_err))?;
+    Ok(file_name.to_string())
+}
+
+/// Creates a new config file in `directory` using the built-in template named `template_name`.
+/// Fails if there's already a config file in `directory`.
+pub fn init_with(directory: &Path, template_name: &str) -> Result<String, ConfigError> {
+    let config_text = BUILT_IN_TEMPLATES
+        .iter()
+        .find(|(name, _)| *name == template_name)
+        .map(|(_, config_text)| *config_text)
+        .ok_or_else(|| ConfigError::UnknownInitTemplate {
+            name: template_name.to_string(),
+            available: BUILT_IN_TEMPLATES
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<&str>>()
+                .join(", "),
+        })?;
+
+    if let Some(existing_file_name) = CONFIG_FILE_NAMES
+        .iter()
+        .find(|file_name| directory.join(file_name).exists())
+    {
+        return Err(ConfigError::AlreadyExists {
+            file_name: existing_file_name.to_string(),
+        });
+    }
+
+    let file_name = CONFIG_FILE_NAMES[0];
+    fs::write(directory.join(file_name), config_text).map_err(ConfigError::WriteFailed)?;
     Ok(file_name.to_string())
 }
 
-fn parse_config_from(path: &String, current_platform: Platform) -> Result<Config, ConfigError> {
+/// Reads the config text to use for a new config file.
+/// `template_path` must exist if it's provided, whereas `default_template_path` is only used if it
+/// exists. If neither are used, the built-in default is returned.
+fn read_init_template(
+    template_path: Option<PathBuf>,
+    default_template_path: Option<PathBuf>,
+) -> Result<String, ConfigError> {
+    let path = match template_path {
+        Some(template_path) => template_path,
+        None => match default_template_path {
+            Some(default_template_path) if default_template_path.exists() => default_template_path,
+            _ => return Ok(DEFAULT_CONFIG_FILE.to_string()),
+        },
+    };
+
+    fs::read_to_string(path).map_err(ConfigError::ReadFailed)
+}
+
+/// The path to the user's template file, `$XDG_CONFIG_HOME/dingus/template.yaml`, falling back to
+/// `~/.config/dingus/template.yaml`.
+fn default_template_path() -> Option<PathBuf> {
+    let config_directory = match env::var("XDG_CONFIG_HOME") {
+        Ok(config_home) => PathBuf::from(config_home),
+        Err(_) => PathBuf::from(env::var("HOME").ok()?).join(".config"),
+    };
+
+    Some(config_directory.join("dingus").join("template.yaml"))
+}
+
+fn parse_config_from(
+    path: &String,
+    current_platform: Platform,
+    trace_depth: Option<usize>,
+) -> Result<Config, ConfigError> {
     let config_text = fs::read_to_string(path).map_err(|err| ConfigError::ReadFailed(err))?;
 
-    parse_config(&config_text, current_platform)
+    let mut config = parse_config_traced(&config_text, current_platform, trace_depth)?;
+    set_source(&mut config.commands, &PathBuf::from(path));
+
+    Ok(config)
+}
+
+/// Sets the source of all commands which don't already have one.
+/// Commands from nested imports will have already had their source set by the time this is called
+/// for the parent config, so they're left alone.
+fn set_source(commands: &mut CommandConfigMap, source: &PathBuf) {
+    for (_, command_config) in commands.iter_mut() {
+        set_command_source(command_config, source);
+    }
+}
+
+fn set_command_source(command_config: &mut CommandConfig, source: &PathBuf) {
+    if command_config.source.is_none() {
+        command_config.source = Some(source.clone());
+    }
+
+    set_source(&mut command_config.commands, source);
+    for candidate in command_config.candidates.iter_mut() {
+        set_command_source(candidate, source);
+    }
+}
+
+/// Parses the config without tracing imports.
+#[cfg(test)]
+pub fn parse_config(text: &String, current_platform: Platform) -> Result<Config, ConfigError> {
+    parse_config_traced(text, current_platform, None)
 }
 
-fn parse_config(text: &String, current_platform: Platform) -> Result<Config, ConfigError> {
+/// Parses the config like [`parse_config`], printing each import to stderr, indented by
+/// `trace_depth`, if it's provided.
+fn parse_config_traced(
+    text: &String,
+    current_platform: Platform,
+    trace_depth: Option<usize>,
+) -> Result<Config, ConfigError> {
     // Parse the base config
     let mut base_config: Config =
         serde_yaml::from_str(text.as_str()).map_err(|err| ConfigError::ParseFailed(err))?;
 
+    // Expand any templates before the imports are added, imported configs expand their own.
+    expand_templates(&mut base_config.commands, &base_config.templates)?;
+
     // Parse the imports too
     for import in &base_config.imports {
+        let skipped = import.platform.as_ref().is_some_and(|import_platform| {
+            !is_current_platform(current_platform.clone(), import_platform)
+        });
+
+        if let Some(depth) = trace_depth {
+            eprintln!(
+                "{}",
+                describe_import(import, &current_platform, skipped, depth)
+            );
+        }
+
         // Don't even try parsing the import if it's not for the current platform
-        if let Some(import_platform) = &import.platform {
-            if !is_current_platform(current_platform.clone(), import_platform) {
-                continue;
-            }
+        if skipped {
+            continue;
         }
 
-        let child_config =
-            parse_config_from(&import.source, current_platform.clone()).map_err(|err| {
-                ConfigError::ImportFailed {
-                    alias: import.alias.clone(),
-                    source: Box::new(err),
-                }
+        let child_config = import_path(&import.source)
+            .and_then(|path| {
+                parse_config_from(
+                    &path,
+                    current_platform.clone(),
+                    trace_depth.map(|depth| depth + 1),
+                )
+            })
+            .map_err(|err| ConfigError::ImportFailed {
+                alias: import.alias.clone(),
+                source: Box::new(err),
             })?;
 
         // Create a top-level command for every import
         let command = CommandConfig {
-            name: None,
             description: child_config.description,
-            hidden: import.hidden,
+            hidden: Some(import.hidden),
+            group: import.group.clone(),
             platform: import.platform.clone(),
             variables: child_config.variables,
             commands: child_config.commands,
-            action: None,
+            ..Default::default()
         };
 
-        base_config.commands.insert(import.alias.clone(), command);
+        // Don't overwrite commands with the same name, the user picks one when running it
+        match base_config.commands.get_mut(&import.alias) {
+            Some(existing_command) => existing_command.candidates.push(CommandConfig {
+                // So the candidates can be told apart
+                source: Some(PathBuf::from(&import.source)),
+                ..command
+            }),
+            None => {
+                base_config.commands.insert(import.alias.clone(), command);
+            }
+        }
     }
 
     Ok(base_config)
 }
 
+/// The local path to the file described by an import's `source`.
+/// Git sources are cloned into the cache first.
+fn import_path(source: &str) -> Result<String, ConfigError> {
+    match parse_git_source(source) {
+        Some(git_source) => {
+            let file_path = fetch_git_source(&git_source?, &cache_directory())?;
+            Ok(file_path.display().to_string())
+        }
+        None => Ok(expand_tilde(source)),
+    }
+}
+
+/// Describes an import for `--trace-config`, indented to reflect how deeply it's nested.
+fn describe_import(
+    import: &Import,
+    current_platform: &Platform,
+    skipped: bool,
+    depth: usize,
+) -> String {
+    let indent = "  ".repeat(depth);
+    let description = format!("{indent}{} ({})", import.alias, import.source);
+
+    if skipped {
+        format!("{description}: skipped, not for {current_platform:?}")
+    } else if import.platform.is_some() {
+        format!("{description}: imported, matches {current_platform:?}")
+    } else {
+        format!("{description}: imported")
+    }
+}
+
+/// Replaces any commands which `use` a template with the result of merging the command over the
+/// template.
+fn expand_templates(
+    commands: &mut CommandConfigMap,
+    templates: &CommandConfigMap,
+) -> Result<(), ConfigError> {
+    for (key, command_config) in commands.iter_mut() {
+        if let Some(template_name) = &command_config.template {
+            let template =
+                templates
+                    .get(template_name)
+                    .ok_or_else(|| ConfigError::UnknownTemplate {
+                        command: key.clone(),
+                        template: template_name.clone(),
+                    })?;
+
+            *command_config = command_config.clone().merged_over(template);
+        }
+
+        expand_templates(&mut command_config.commands, templates)?;
+    }
+
+    Ok(())
+}
+
+/// Checks the provided [`Config`] for any problems that can't be caught while parsing.
+pub fn validate(config: &Config) -> Result<(), ConfigError> {
+    validate_argument_names(config)?;
+    validate_commands(&config.commands, &config.variables, None)
+}
+
+/// Checks that no variable's argument uses the same name as one of dingus's own options, since
+/// clap can't tell them apart.
+/// Unlike the rest of [`validate`], this is checked every time the config is loaded.
+pub fn validate_argument_names(config: &Config) -> Result<(), ConfigError> {
+    check_argument_names(
+        &config.variables,
+        &reserved_arg_names(config, true),
+        &config.options,
+    )?;
+    validate_command_argument_names(
+        &config.commands,
+        &reserved_arg_names(config, false),
+        &config.options,
+    )
+}
+
+fn validate_command_argument_names(
+    commands: &CommandConfigMap,
+    reserved_names: &[String],
+    dingus_options: &DingusOptions,
+) -> Result<(), ConfigError> {
+    for command_config in commands.values() {
+        check_argument_names(&command_config.variables, reserved_names, dingus_options)?;
+        validate_command_argument_names(&command_config.commands, reserved_names, dingus_options)?;
+    }
+
+    Ok(())
+}
+
+fn check_argument_names(
+    variables: &VariableConfigMap,
+    reserved_names: &[String],
+    dingus_options: &DingusOptions,
+) -> Result<(), ConfigError> {
+    for (variable_key, variable_config) in variables.iter() {
+        let names = match argument_config(dingus_options, variable_key, variable_config) {
+            Some(ArgumentConfigVariant::Shorthand(long)) => vec![long],
+            Some(ArgumentConfigVariant::Named(named_conf)) => {
+                let mut names = named_conf.aliases;
+                names.push(named_conf.long);
+                names
+            }
+            Some(ArgumentConfigVariant::Positional(_)) | None => vec![],
+        };
+
+        if let Some(name) = names.into_iter().find(|name| reserved_names.contains(name)) {
+            return Err(ConfigError::ReservedArgument {
+                variable: variable_key.clone(),
+                name,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_commands(
+    commands: &CommandConfigMap,
+    parent_variables: &VariableConfigMap,
+    parent_path: Option<&str>,
+) -> Result<(), ConfigError> {
+    for (key, command_config) in commands.iter() {
+        let path = match parent_path {
+            Some(parent_path) => format!("{parent_path}.{key}"),
+            None => key.clone(),
+        };
+
+        if command_config.action.is_none()
+            && command_config.platform_actions.is_empty()
+            && command_config.commands.is_empty()
+        {
+            return Err(ConfigError::EmptyCommand { command: path });
+        }
+
+        for action_config in command_config
+            .action
+            .iter()
+            .chain(command_config.platform_actions.values())
+        {
+            let ActionConfig::MultiStep(multi_action_config) = action_config else {
+                continue;
+            };
+            if !multi_action_config.shared_shell {
+                continue;
+            }
+
+            if let Some(field) = multi_action_config
+                .actions
+                .iter()
+                .find_map(|action| action.shared_shell_unsupported_field())
+            {
+                return Err(ConfigError::UnsupportedInSharedShell {
+                    command: path,
+                    field: field.to_string(),
+                });
+            }
+        }
+
+        let mut variables = parent_variables.clone();
+        variables.extend(command_config.variables.clone());
+
+        // Maps each position to the variable using it
+        let mut positions: Vec<(usize, String)> = vec![];
+        for (variable_key, variable_config) in variables.iter() {
+            let argument = match variable_config {
+                VariableConfig::ShorthandLiteral(_) => None,
+                VariableConfig::Literal(literal_conf) => literal_conf.argument.as_ref(),
+                VariableConfig::Execution(execution_conf) => execution_conf.argument.as_ref(),
+                VariableConfig::Prompt(prompt_conf) => prompt_conf.argument.as_ref(),
+                VariableConfig::Random(random_conf) => random_conf.argument.as_ref(),
+                VariableConfig::File(file_conf) => file_conf.argument.as_ref(),
+                VariableConfig::Argument(argument_conf) => Some(&argument_conf.argument),
+            };
+
+            let (conflicts_with, requires) = match argument {
+                Some(ArgumentConfigVariant::Named(named_conf)) => (
+                    named_conf.conflicts_with.as_slice(),
+                    named_conf.requires.as_slice(),
+                ),
+                Some(ArgumentConfigVariant::Positional(positional_conf)) => (
+                    positional_conf.conflicts_with.as_slice(),
+                    positional_conf.requires.as_slice(),
+                ),
+                _ => (&[][..], &[][..]),
+            };
+            if let Some(conflict) = conflicts_with
+                .iter()
+                .find(|conflict| !variables.contains_key(*conflict))
+            {
+                return Err(ConfigError::UnknownConflict {
+                    command: path,
+                    variable: variable_key.clone(),
+                    conflict: conflict.clone(),
+                });
+            }
+            if let Some(requirement) = requires
+                .iter()
+                .find(|requirement| !variables.contains_key(*requirement))
+            {
+                return Err(ConfigError::UnknownRequirement {
+                    command: path,
+                    variable: variable_key.clone(),
+                    requirement: requirement.clone(),
+                });
+            }
+
+            if let Some(ArgumentConfigVariant::Positional(positional_conf)) = argument {
+                let existing = positions
+                    .iter()
+                    .find(|(position, _)| *position == positional_conf.position);
+                if let Some((position, existing_key)) = existing {
+                    return Err(ConfigError::DuplicatePosition {
+                        command: path,
+                        position: *position,
+                        first: existing_key.clone(),
+                        second: variable_key.clone(),
+                    });
+                }
+
+                positions.push((positional_conf.position, variable_key.clone()));
+            }
+        }
+
+        // Positions need to start at 1 without any gaps, otherwise clap can't tell which value
+        // belongs to which argument
+        positions.sort();
+        for (expected_position, (position, variable_key)) in (1..).zip(positions) {
+            if position != expected_position {
+                return Err(ConfigError::PositionGap {
+                    command: path,
+                    variable: variable_key,
+                    position,
+                    expected_position,
+                });
+            }
+        }
+
+        validate_commands(&command_config.commands, &variables, Some(&path))?;
+    }
+
+    Ok(())
+}
+
+impl CommandConfig {
+    /// Whether the command should be hidden from the --help output.
+    pub fn is_hidden(&self) -> bool {
+        self.hidden.unwrap_or(false)
+    }
+
+    /// Whether any arguments after `--` should be appended to the command of the last step in
+    /// this command's action.
+    pub fn is_passthrough(&self) -> bool {
+        self.passthrough.unwrap_or(false)
+    }
+
+    /// Shallow-merges this command over the provided `template`.
+    /// Fields set on this command win, otherwise the template's value is used.
+    /// The name is never inherited since it would cause every user of the template to collide.
+    fn merged_over(self, template: &CommandConfig) -> CommandConfig {
+        CommandConfig {
+            name: self.name,
+            template: self.template,
+            description: self.description.or(template.description.clone()),
+            group: self.group.or(template.group.clone()),
+            hidden: self.hidden.or(template.hidden),
+            meta: self.meta.merged_over(&template.meta),
+            workdir_anchor: self.workdir_anchor.or(template.workdir_anchor.clone()),
+            platform: self.platform.or(template.platform.clone()),
+            variables: if self.variables.is_empty() {
+                template.variables.clone()
+            } else {
+                self.variables
+            },
+            overrides: if self.overrides.is_empty() {
+                template.overrides.clone()
+            } else {
+                self.overrides
+            },
+            commands: if self.commands.is_empty() {
+                template.commands.clone()
+            } else {
+                self.commands
+            },
+            depends_on: if self.depends_on.is_empty() {
+                template.depends_on.clone()
+            } else {
+                self.depends_on
+            },
+            sources: if self.sources.is_empty() {
+                template.sources.clone()
+            } else {
+                self.sources
+            },
+            lock: self.lock.or(template.lock.clone()),
+            passthrough: self.passthrough.or(template.passthrough),
+            path_prepend: if self.path_prepend.is_empty() {
+                template.path_prepend.clone()
+            } else {
+                self.path_prepend
+            },
+            path_append: if self.path_append.is_empty() {
+                template.path_append.clone()
+            } else {
+                self.path_append
+            },
+            timeout: self.timeout.or(template.timeout),
+            action: self.action.or(template.action.clone()),
+            platform_actions: if self.platform_actions.is_empty() {
+                template.platform_actions.clone()
+            } else {
+                self.platform_actions
+            },
+            source: self.source,
+            candidates: self.candidates,
+        }
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum ConfigError {
     #[error("config file not found")]
@@ -145,18 +735,75 @@ pub enum ConfigError {
     #[error("failed to write config file")]
     WriteFailed(#[source] io::Error),
 
+    #[error("{file_name} already exists")]
+    AlreadyExists { file_name: String },
+
+    #[error("unknown template \"{name}\", expected one of: {available}")]
+    UnknownInitTemplate { name: String, available: String },
+
     #[error("failed to parse config file")]
     ParseFailed(#[source] serde_yaml::Error),
 
+    #[error("failed to overlay {path}")]
+    OverlayFailed {
+        path: String,
+        source: serde_yaml::Error,
+    },
+
     #[error("failed to import {alias}")]
     ImportFailed {
         alias: String,
         source: Box<ConfigError>, // Need to box this so the size isn't infinite
     },
+
+    #[error("failed to fetch git source")]
+    GitSource(#[from] GitSourceError),
+
+    #[error("command \"{command}\" uses unknown template \"{template}\"")]
+    UnknownTemplate { command: String, template: String },
+
+    #[error("command \"{command}\" has no action and no subcommands")]
+    EmptyCommand { command: String },
+
+    #[error("command \"{command}\" has multiple arguments at position {position}: \"{first}\" and \"{second}\"")]
+    DuplicatePosition {
+        command: String,
+        position: usize,
+        first: String,
+        second: String,
+    },
+
+    #[error("command \"{command}\" uses shared_shell, so its actions can't set {field}")]
+    UnsupportedInSharedShell { command: String, field: String },
+
+    #[error("variable \"{variable}\" can't use \"--{name}\" for its argument since dingus already uses it")]
+    ReservedArgument { variable: String, name: String },
+
+    #[error("command \"{command}\" has an argument for \"{variable}\" which conflicts with unknown variable \"{conflict}\"")]
+    UnknownConflict {
+        command: String,
+        variable: String,
+        conflict: String,
+    },
+
+    #[error("command \"{command}\" has an argument for \"{variable}\" which requires unknown variable \"{requirement}\"")]
+    UnknownRequirement {
+        command: String,
+        variable: String,
+        requirement: String,
+    },
+
+    #[error("command \"{command}\" has an argument at position {position} for \"{variable}\", but the next position should be {expected_position}")]
+    PositionGap {
+        command: String,
+        variable: String,
+        position: usize,
+        expected_position: usize,
+    },
 }
 
 /// The root-level of the Configuration.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct Config {
     /// A list of additional config files to import.
     #[serde(default = "default_imports")]
@@ -175,6 +822,27 @@ pub struct Config {
     #[serde(alias = "cmds")]
     pub commands: CommandConfigMap,
 
+    /// Reusable [`CommandConfig`]s that commands can inherit from via their `use` field.
+    #[serde(default = "default_commands")]
+    pub templates: CommandConfigMap,
+
+    /// Named sets of variable values that can be selected with `--profile`.
+    #[serde(default)]
+    pub profiles: ProfileConfigMap,
+
+    /// An optional [`Shell`] to execute shorthand commands with.
+    /// When not set, shorthand commands are executed directly without a shell.
+    pub default_shell: Option<Shell>,
+
+    /// Actions to execute before any command, including its dependencies.
+    /// If any of these fail, the command isn't executed.
+    #[serde(default)]
+    pub pre_run: Vec<ExecutionConfigVariant>,
+
+    /// Actions to execute after any command, even if the command fails.
+    #[serde(default)]
+    pub post_run: Vec<ExecutionConfigVariant>,
+
     #[serde(default)]
     #[serde(alias = "opts")]
     pub options: DingusOptions,
@@ -201,6 +869,9 @@ pub struct Import {
     #[serde(default = "default_hidden")]
     pub hidden: bool,
 
+    /// An optional heading to list the imported commands under in the --help output.
+    pub group: Option<String>,
+
     /// An optional platform to restrict this import to.
     /// When specified, the config will only be imported on the specified platforms.
     #[serde(flatten)]
@@ -223,6 +894,54 @@ pub struct DingusOptions {
     /// Defaults to `false`.
     #[serde(default = "default_auto_args")]
     pub auto_args: bool,
+
+    /// When set to `true`, the working directory is changed to the directory containing the config
+    /// file before executing commands.
+    /// When set to `false`, commands are executed from the directory dingus was started in.
+    /// Defaults to `true`.
+    #[serde(default = "default_change_directory")]
+    pub change_directory: bool,
+
+    /// The directory that commands are executed from.
+    /// Defaults to [`WorkdirAnchor::ConfigDir`].
+    #[serde(default)]
+    pub workdir_anchor: WorkdirAnchor,
+
+    /// The path to the bash binary used for bash commands.
+    /// Defaults to the `DINGUS_BASH` environment variable, or `bash` if it isn't set.
+    #[serde(default = "default_bash_path")]
+    pub bash_path: String,
+
+    /// Options for `--watch` mode.
+    #[serde(default)]
+    pub watch: WatchOptions,
+
+    /// The path to a YAML, JSON, or TOML file containing default variable values.
+    /// If not set, `values.yaml`, `values.yml`, `values.json`, or `values.toml` will be used if
+    /// present.
+    pub values_file: Option<String>,
+
+    /// The exit code to use when the user cancels a prompt.
+    /// Defaults to `130`, the same exit code as interrupting a command with Ctrl-C.
+    #[serde(default = "default_cancel_exit_code")]
+    pub cancel_exit_code: i32,
+
+    /// The maximum number of bytes that the commands for execution variables can write to stdout
+    /// or stderr before they're stopped.
+    /// Defaults to no limit.
+    pub max_output_bytes: Option<u64>,
+
+    /// An optional name to show in place of `dingus` in the help and usage output.
+    /// If not set, the usage output uses the name dingus was invoked with.
+    pub program_name: Option<String>,
+
+    /// An optional command to execute after a command succeeds, such as to send a notification.
+    /// `$DINGUS_COMMAND` and `$DINGUS_EXIT_CODE` are set to the command that ran and its exit code.
+    pub on_success: Option<ExecutionConfigVariant>,
+
+    /// An optional command to execute after a command fails, with the same variables as
+    /// `on_success`.
+    pub on_failure: Option<ExecutionConfigVariant>,
 }
 
 impl Default for DingusOptions {
@@ -231,10 +950,81 @@ impl Default for DingusOptions {
             print_commands: default_print_commands(),
             print_variables: default_print_variables(),
             auto_args: default_auto_args(),
+            change_directory: default_change_directory(),
+            workdir_anchor: WorkdirAnchor::default(),
+            bash_path: default_bash_path(),
+            watch: WatchOptions::default(),
+            values_file: None,
+            cancel_exit_code: default_cancel_exit_code(),
+            max_output_bytes: None,
+            program_name: None,
+            on_success: None,
+            on_failure: None,
+        }
+    }
+}
+
+/// A shell that commands can be executed with.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum Shell {
+    /// Uses [`DingusOptions::bash_path`].
+    Bash,
+    Zsh,
+    Sh,
+}
+
+/// The directory that commands are executed from.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkdirAnchor {
+    /// The directory containing the config file.
+    #[default]
+    ConfigDir,
+
+    /// The root of the git repository containing the config file.
+    GitRoot,
+}
+
+/// Options for `--watch` mode.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct WatchOptions {
+    /// How long to wait for changes to settle before re-running the command, in milliseconds.
+    /// Defaults to `500`.
+    #[serde(default = "default_watch_debounce")]
+    pub debounce: u64,
+
+    /// The names of files and directories that should not trigger a re-run.
+    /// Defaults to `.git`, `target`, and `node_modules`.
+    #[serde(default = "default_watch_ignore")]
+    pub ignore: Vec<String>,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        WatchOptions {
+            debounce: default_watch_debounce(),
+            ignore: default_watch_ignore(),
         }
     }
 }
 
+fn default_watch_debounce() -> u64 {
+    500
+}
+
+fn default_watch_ignore() -> Vec<String> {
+    vec![
+        ".git".to_string(),
+        "target".to_string(),
+        "node_modules".to_string(),
+    ]
+}
+
+fn default_change_directory() -> bool {
+    true
+}
+
 fn default_print_commands() -> bool {
     match env::var("DINGUS_PRINT_COMMANDS") {
         Ok(str) => is_truthy(str),
@@ -256,6 +1046,14 @@ fn default_auto_args() -> bool {
     }
 }
 
+fn default_cancel_exit_code() -> i32 {
+    130
+}
+
+fn default_bash_path() -> String {
+    env::var("DINGUS_BASH").unwrap_or("bash".to_string())
+}
+
 fn is_truthy(s: String) -> bool {
     s == "true" || s == "TRUE" || s == "t" || s == "T"
 }
@@ -264,7 +1062,13 @@ fn is_truthy(s: String) -> bool {
 /// Note that this uses a [`LinkedHashMap`] so that the order of insertion is retained.
 pub type VariableConfigMap = LinkedHashMap<String, VariableConfig>;
 
+/// A [`LinkedHashMap`] where the key is the variable name, and the value is the new value for that
+/// variable.
+pub type VariableOverrideMap = LinkedHashMap<String, String>;
+
 /// The kind of variable.
+// Variables are only created when the config is loaded, so the size isn't worth boxing for
+#[allow(clippy::large_enum_variant)]
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 #[serde(untagged)]
 pub enum VariableConfig {
@@ -280,11 +1084,61 @@ pub enum VariableConfig {
     /// Encapsulates a [`PromptVariableConfig`].
     Prompt(PromptVariableConfig),
 
+    /// Encapsulates a [`RandomVariableConfig`].
+    Random(RandomVariableConfig),
+
+    /// Encapsulates a [`FileVariableConfig`].
+    File(FileVariableConfig),
+
     /// Encapsulates a [`ArgumentVariableConfig`].
     Argument(ArgumentVariableConfig),
 }
 
 impl VariableConfig {
+    /// The [`Transform`]s to apply to the value of this variable.
+    pub fn transforms(&self) -> &[Transform] {
+        match self {
+            VariableConfig::ShorthandLiteral(_) => &[],
+            VariableConfig::Literal(literal_conf) => &literal_conf.transform,
+            VariableConfig::Execution(execution_conf) => &execution_conf.transform,
+            VariableConfig::Prompt(prompt_conf) => &prompt_conf.transform,
+            VariableConfig::Random(random_conf) => &random_conf.transform,
+            VariableConfig::File(file_conf) => &file_conf.transform,
+            VariableConfig::Argument(argument_conf) => &argument_conf.transform,
+        }
+    }
+
+    /// Whether this variable is only resolved when it's referenced.
+    pub fn is_lazy(&self) -> bool {
+        matches!(self, VariableConfig::Execution(execution_conf) if execution_conf.lazy)
+    }
+
+    /// Whether this variable is passed to commands as an environment variable.
+    pub fn is_exported(&self) -> bool {
+        match self {
+            VariableConfig::ShorthandLiteral(_) => true,
+            VariableConfig::Literal(literal_conf) => literal_conf.export,
+            VariableConfig::Execution(execution_conf) => execution_conf.export,
+            VariableConfig::Prompt(prompt_conf) => prompt_conf.export,
+            VariableConfig::Random(random_conf) => random_conf.export,
+            VariableConfig::File(file_conf) => file_conf.export,
+            VariableConfig::Argument(argument_conf) => argument_conf.export,
+        }
+    }
+
+    /// Whether this variable has been marked as sensitive with the `sensitive` field.
+    pub fn is_sensitive(&self) -> bool {
+        match self {
+            VariableConfig::ShorthandLiteral(_) => false,
+            VariableConfig::Literal(literal_conf) => literal_conf.sensitive,
+            VariableConfig::Execution(execution_conf) => execution_conf.sensitive,
+            VariableConfig::Prompt(prompt_conf) => prompt_conf.sensitive,
+            VariableConfig::Random(random_conf) => random_conf.sensitive,
+            VariableConfig::File(file_conf) => file_conf.sensitive,
+            VariableConfig::Argument(argument_conf) => argument_conf.sensitive,
+        }
+    }
+
     pub fn environment_variable_name(&self, key: &str) -> String {
         match self {
             VariableConfig::ShorthandLiteral(_) => None,
@@ -293,6 +1147,8 @@ impl VariableConfig {
                 execution_conf.clone().environment_variable_name
             }
             VariableConfig::Prompt(prompt_conf) => prompt_conf.clone().environment_variable_name,
+            VariableConfig::Random(random_conf) => random_conf.clone().environment_variable_name,
+            VariableConfig::File(file_conf) => file_conf.clone().environment_variable_name,
             VariableConfig::Argument(argument_conf) => {
                 argument_conf.clone().environment_variable_name
             }
@@ -328,6 +1184,24 @@ pub struct LiteralVariableConfig {
 
     /// The value of the variable
     pub value: String,
+
+    /// An optional [`PromptConfig`] to use when the value is empty.
+    pub prompt_if_missing: Option<PromptConfig>,
+
+    /// Any [`Transform`]s to apply to the value, in order.
+    #[serde(default)]
+    pub transform: Vec<Transform>,
+
+    /// Whether the variable is passed to commands as an environment variable.
+    /// When `false`, the variable can still be substituted into commands.
+    /// Defaults to `true`.
+    #[serde(default = "default_export")]
+    pub export: bool,
+
+    /// Whether the variable's value should be kept secret, such as when printing the variables.
+    /// Defaults to `false`.
+    #[serde(default)]
+    pub sensitive: bool,
 }
 
 /// Denotes a variable whose value is determined by the output of a command.
@@ -359,7 +1233,115 @@ pub struct ExecutionVariableConfig {
     #[serde(rename = "execute")]
     #[serde(alias = "exec")]
     pub execution: ExecutionConfigVariant,
-}
+
+    /// How the output of the command should be decoded.
+    /// Defaults to [`Encoding::Strict`].
+    #[serde(default)]
+    pub encoding: Encoding,
+
+    /// Which whitespace should be trimmed from the output of the command.
+    /// Defaults to [`Trim::End`].
+    #[serde(default)]
+    pub trim: Trim,
+
+    /// An optional regex to extract the value from the output of the command with, applied after
+    /// the output is trimmed.
+    #[serde(rename = "match")]
+    pub r#match: Option<String>,
+
+    /// The capture group of the `match` regex to use as the value.
+    /// Defaults to the first capture group, or the whole match if the regex has no groups.
+    pub group: Option<usize>,
+
+    /// An optional [`PromptConfig`] to use when the command doesn't output anything.
+    pub prompt_if_missing: Option<PromptConfig>,
+
+    /// Any [`Transform`]s to apply to the value, in order.
+    #[serde(default)]
+    pub transform: Vec<Transform>,
+
+    /// Whether the variable is passed to commands as an environment variable.
+    /// When `false`, the variable can still be substituted into commands.
+    /// Defaults to `true`.
+    #[serde(default = "default_export")]
+    pub export: bool,
+
+    /// Whether the variable's value should be kept secret, such as when printing the variables.
+    /// Defaults to `false`.
+    #[serde(default)]
+    pub sensitive: bool,
+
+    /// When set to `true`, the command is only executed if the variable is referenced by the
+    /// command being run, its hooks, or another variable that's used.
+    /// Only applies when `export` is `false`, since exported variables can be read by scripts
+    /// without being referenced.
+    /// Defaults to `false`.
+    #[serde(default)]
+    pub lazy: bool,
+}
+
+/// Which whitespace is trimmed from the output of a command.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Trim {
+    /// Trailing whitespace is trimmed.
+    #[default]
+    End,
+
+    /// Leading whitespace is trimmed.
+    Start,
+
+    /// Leading and trailing whitespace is trimmed.
+    Both,
+
+    /// Nothing is trimmed.
+    None,
+}
+
+impl Trim {
+    /// Trims `value` according to this [`Trim`].
+    pub fn apply(&self, value: &str) -> String {
+        match self {
+            Trim::End => value.trim_end(),
+            Trim::Start => value.trim_start(),
+            Trim::Both => value.trim(),
+            Trim::None => value,
+        }
+        .to_string()
+    }
+}
+
+/// A transformation applied to the value of a variable once it has been resolved.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum Transform {
+    /// Converts the value to uppercase.
+    Upper,
+
+    /// Converts the value to lowercase.
+    Lower,
+
+    /// Removes leading and trailing whitespace.
+    Trim,
+
+    /// Encodes the value as base64.
+    Base64,
+
+    /// Decodes the value from base64.
+    Base64d,
+}
+
+/// How the output of a command is decoded into a string.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    /// The output must be valid UTF-8, otherwise an error is returned.
+    #[default]
+    Strict,
+
+    /// Any invalid UTF-8 sequences are replaced with `U+FFFD REPLACEMENT CHARACTER`.
+    Lossy,
+}
 
 /// Denotes a variable whose value is determined by prompting the user for input.
 ///
@@ -389,6 +1371,138 @@ pub struct PromptVariableConfig {
 
     /// The [`PromptConfig`] to use for the prompt.
     pub prompt: PromptConfig,
+
+    /// Any [`Transform`]s to apply to the value, in order.
+    #[serde(default)]
+    pub transform: Vec<Transform>,
+
+    /// Whether the variable is passed to commands as an environment variable.
+    /// When `false`, the variable can still be substituted into commands.
+    /// Defaults to `true`.
+    #[serde(default = "default_export")]
+    pub export: bool,
+
+    /// Whether the variable's value should be kept secret, such as when printing the variables.
+    /// Defaults to `false`.
+    #[serde(default)]
+    pub sensitive: bool,
+}
+
+/// Denotes a variable whose value is randomly generated every time it's resolved.
+///
+/// Example:
+/// ```yaml
+/// container_name:
+///     random:
+///         kind: hex
+///         length: 8
+/// ```
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct RandomVariableConfig {
+    /// An optional argument configuration.
+    #[serde(rename(deserialize = "argument"))]
+    #[serde(alias = "arg")]
+    pub argument: Option<ArgumentConfigVariant>,
+
+    /// An optional environment variable name.
+    /// If specified, the environment variable for this variable will have the specified name.
+    ///
+    /// This is **not** the name of the environment variable to source the value from.
+    /// If you want to source a variables value from an environment variable,
+    /// use an [`ExecutionVariableConfig`].
+    #[serde(rename(deserialize = "environment_variable"))]
+    #[serde(alias = "env")]
+    pub environment_variable_name: Option<String>,
+
+    /// The [`RandomConfig`] to generate the value with.
+    pub random: RandomConfig,
+
+    /// Any [`Transform`]s to apply to the value, in order.
+    #[serde(default)]
+    pub transform: Vec<Transform>,
+
+    /// Whether the variable is passed to commands as an environment variable.
+    /// When `false`, the variable can still be substituted into commands.
+    /// Defaults to `true`.
+    #[serde(default = "default_export")]
+    pub export: bool,
+
+    /// Whether the variable's value should be kept secret, such as when printing the variables.
+    /// Defaults to `false`.
+    #[serde(default)]
+    pub sensitive: bool,
+}
+
+/// Denotes a variable whose value is the content of a file.
+///
+/// Example:
+/// ```yaml
+/// version:
+///     file: ./VERSION
+/// ```
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct FileVariableConfig {
+    /// An optional argument configuration.
+    #[serde(rename(deserialize = "argument"))]
+    #[serde(alias = "arg")]
+    pub argument: Option<ArgumentConfigVariant>,
+
+    /// An optional environment variable name.
+    /// If specified, the environment variable for this variable will have the specified name.
+    ///
+    /// This is **not** the name of the environment variable to source the value from.
+    /// If you want to source a variables value from an environment variable,
+    /// use an [`ExecutionVariableConfig`].
+    #[serde(rename(deserialize = "environment_variable"))]
+    #[serde(alias = "env")]
+    pub environment_variable_name: Option<String>,
+
+    /// The path of the file to read the value from.
+    /// Variables are substituted into the path.
+    #[serde(rename = "file")]
+    pub path: String,
+
+    /// Which whitespace should be trimmed from the content of the file.
+    /// Defaults to [`Trim::End`].
+    #[serde(default)]
+    pub trim: Trim,
+
+    /// Any [`Transform`]s to apply to the value, in order.
+    #[serde(default)]
+    pub transform: Vec<Transform>,
+
+    /// Whether the variable is passed to commands as an environment variable.
+    /// When `false`, the variable can still be substituted into commands.
+    /// Defaults to `true`.
+    #[serde(default = "default_export")]
+    pub export: bool,
+
+    /// Whether the variable's value should be kept secret, such as when printing the variables.
+    /// Defaults to `false`.
+    #[serde(default)]
+    pub sensitive: bool,
+}
+
+/// How a random value is generated.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct RandomConfig {
+    pub kind: RandomKind,
+
+    /// The number of characters to generate for [`RandomKind::Hex`] values.
+    /// Defaults to 16.
+    #[serde(alias = "len")]
+    pub length: Option<usize>,
+}
+
+/// The kind of random value to generate.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum RandomKind {
+    /// A version 4 UUID, such as `0b4e5e3c-8a4f-4c1e-9a6b-2f1d3c4b5a69`.
+    Uuid,
+
+    /// A string of lowercase hexadecimal characters.
+    Hex,
 }
 
 /// Denotes a variable whose value is sourced from command-line arguments.
@@ -417,6 +1531,31 @@ pub struct ArgumentVariableConfig {
     #[serde(rename(deserialize = "environment_variable"))]
     #[serde(alias = "env")]
     pub environment_variable_name: Option<String>,
+
+    /// An optional [`ExecutionConfigVariant`] whose output is used as the value when the argument
+    /// isn't provided.
+    /// The command is only executed if it's needed.
+    pub default_from_command: Option<ExecutionConfigVariant>,
+
+    /// Which whitespace should be trimmed from the output of the `default_from_command`.
+    /// Defaults to [`Trim::End`].
+    #[serde(default)]
+    pub trim: Trim,
+
+    /// Any [`Transform`]s to apply to the value, in order.
+    #[serde(default)]
+    pub transform: Vec<Transform>,
+
+    /// Whether the variable is passed to commands as an environment variable.
+    /// When `false`, the variable can still be substituted into commands.
+    /// Defaults to `true`.
+    #[serde(default = "default_export")]
+    pub export: bool,
+
+    /// Whether the variable's value should be kept secret, such as when printing the variables.
+    /// Defaults to `false`.
+    #[serde(default)]
+    pub sensitive: bool,
 }
 
 /// The kind of argument configuration.
@@ -440,6 +1579,18 @@ pub struct NamedArgumentConfig {
 
     /// The short version of the argument without the preceding `-`.
     pub short: Option<char>,
+
+    /// Any additional long names for the argument without the preceding `--`.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+
+    /// The keys of any variables whose arguments can't be used alongside this one.
+    #[serde(default)]
+    pub conflicts_with: Vec<String>,
+
+    /// The keys of any variables whose arguments must be provided when this one is used.
+    #[serde(default)]
+    pub requires: Vec<String>,
 }
 
 /// The configuration for a positional command-line argument.
@@ -454,6 +1605,14 @@ pub struct PositionalArgumentConfig {
     /// It does not define the position in the argument list as a whole.
     /// https://docs.rs/clap/latest/clap/struct.Arg.html#method.index
     pub position: usize,
+
+    /// The keys of any variables whose arguments can't be used alongside this one.
+    #[serde(default)]
+    pub conflicts_with: Vec<String>,
+
+    /// The keys of any variables whose arguments must be provided when this one is used.
+    #[serde(default)]
+    pub requires: Vec<String>,
 }
 
 /// The configuration for a prompt to the user for input.
@@ -462,6 +1621,9 @@ pub struct PromptConfig {
     /// The message to display to the user.
     pub message: String,
 
+    /// Optional help text to display below the prompt.
+    pub help: Option<String>,
+
     /// Additional, type-specific options for the prompt.
     #[serde(flatten)]
     pub options: PromptOptionsVariant,
@@ -472,6 +1634,9 @@ impl Default for PromptOptionsVariant {
         return PromptOptionsVariant::Text(TextPromptOptions {
             multi_line: false,
             sensitive: false,
+            min_length: None,
+            max_length: None,
+            default: None,
         });
     }
 }
@@ -480,12 +1645,16 @@ impl Default for PromptOptionsVariant {
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 #[serde(untagged)]
 pub enum PromptOptionsVariant {
-    // Note: Select needs to come first here because SelectPromptOptions is the most specific.
+    // Note: Select and Autocomplete need to come first here because they are the most specific.
     // Serde will use the type it matches on.
     /// Encapsulates a [`SelectPromptOptions]`, indicating that the prompt should be a select-style
     /// prompt.
     Select(SelectPromptOptions),
 
+    /// Encapsulates an [`AutocompletePromptOptions]`, indicating that the prompt should be a text
+    /// prompt with suggestions.
+    Autocomplete(AutocompletePromptOptions),
+
     /// Encapsulates a [`TextPromptOptions]`, indicating that the prompt should be a text prompt.
     Text(TextPromptOptions),
 }
@@ -501,6 +1670,19 @@ pub struct TextPromptOptions {
     /// When set to `true`, the input value will be obscured.
     #[serde(default = "default_sensitive")]
     pub sensitive: bool,
+
+    /// The minimum number of characters the input must have.
+    pub min_length: Option<usize>,
+
+    /// The maximum number of characters the input can have.
+    pub max_length: Option<usize>,
+
+    /// An optional value to use if the user doesn't enter anything.
+    pub default: Option<String>,
+}
+
+fn default_export() -> bool {
+    true
 }
 
 fn default_multi_line() -> bool {
@@ -519,7 +1701,22 @@ pub struct SelectPromptOptions {
     pub options: SelectOptionsConfig,
 }
 
+/// The options for an autocomplete prompt.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct AutocompletePromptOptions {
+    /// The [`SelectOptionsConfig`] for determining the suggestions shown to the user.
+    pub suggestions: SelectOptionsConfig,
+
+    /// Whether the user must choose one of the suggestions.
+    /// When set to `false`, any value can be entered.
+    /// Defaults to `false`.
+    #[serde(default)]
+    pub restrict_to_suggestions: bool,
+}
+
 /// The kind of select prompt options.
+// Like VariableConfig, boxing the command isn't worth it for something parsed once
+#[allow(clippy::large_enum_variant)]
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 #[serde(untagged)]
 pub enum SelectOptionsConfig {
@@ -538,23 +1735,71 @@ pub struct ExecutionSelectOptionsConfig {
     #[serde(rename = "execute")]
     #[serde(alias = "exec")]
     pub execution: ExecutionConfigVariant,
+
+    /// How the output of the command should be decoded.
+    /// Defaults to [`Encoding::Strict`].
+    #[serde(default)]
+    pub encoding: Encoding,
+
+    /// When set to `true`, the output of the command is parsed as a JSON array of strings instead
+    /// of using each line as an option.
+    #[serde(default)]
+    pub json: bool,
+}
+
+pub type ProfileConfigMap = LinkedHashMap<String, ProfileConfig>;
+
+/// A named set of variable values.
+///
+/// Example:
+/// ```yaml
+/// prod:
+///     variables:
+///         region: eu-west-1
+/// ```
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct ProfileConfig {
+    /// New values for the variables available to the executed command.
+    #[serde(default)]
+    #[serde(alias = "vars")]
+    pub variables: VariableOverrideMap,
 }
 
-pub type CommandConfigMap = HashMap<String, CommandConfig>;
+/// A set of [`CommandConfig`].
+/// Note that this uses a [`LinkedHashMap`] so that the order of insertion is retained.
+pub type CommandConfigMap = LinkedHashMap<String, CommandConfig>;
 
 /// The configuration for a command.
-#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
 pub struct CommandConfig {
     /// An optional name for the command. Setting this will override the name provided by the key.
     pub name: Option<String>,
 
+    /// An optional name of a template to inherit fields from.
+    /// Any fields set on this command take precedence over the template's.
+    #[serde(rename = "use")]
+    pub template: Option<String>,
+
     /// An optional description for the command.
     #[serde(alias = "desc")]
     pub description: Option<String>,
 
+    /// An optional override for [`DingusOptions::workdir_anchor`] when executing this command.
+    pub workdir_anchor: Option<WorkdirAnchor>,
+
+    /// An optional heading to list the command under in the --help output.
+    /// Commands without a group are listed under the default heading.
+    pub group: Option<String>,
+
     /// Whether the command should be hidden from the --help output.
-    #[serde(default = "default_hidden")]
-    pub hidden: bool,
+    /// Unset is the same as `false`, but leaves the decision to any template the command uses.
+    #[serde(default)]
+    pub hidden: Option<bool>,
+
+    /// Information about the command for the people maintaining it.
+    /// This is shown by `dingus which`, but otherwise doesn't affect the command.
+    #[serde(default)]
+    pub meta: CommandMetadata,
 
     /// An optional platform to restrict this command to.
     /// When specified, the command will only be available on the specified platforms.
@@ -566,6 +1811,11 @@ pub struct CommandConfig {
     #[serde(alias = "vars")]
     pub variables: VariableConfigMap,
 
+    /// New values for variables inherited from the root config or parent commands.
+    /// Only the value changes, the variable keeps its kind, argument and environment variable name.
+    #[serde(default)]
+    pub overrides: VariableOverrideMap,
+
     // TODO: Need to enforce an invariant here:
     // - If no action exists, then one or more subcommands _must_ exist.
     /// Any sub-[`CommandConfig`]s.
@@ -573,15 +1823,118 @@ pub struct CommandConfig {
     #[serde(alias = "cmds")]
     pub commands: CommandConfigMap,
 
+    /// Paths to other commands that must be executed before this command's action.
+    /// Paths start at the root of the config, with each level separated by a `.`.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+
+    /// Glob patterns for the files that this command depends on.
+    /// When `--since` is used, the command is skipped if none of these files have changed.
+    #[serde(default)]
+    pub sources: Vec<String>,
+
+    /// An optional lock to hold while this command is executed, preventing it from running more
+    /// than once at a time.
+    pub lock: Option<LockConfig>,
+
+    /// Whether any arguments after `--` should be appended to the command of the last step in
+    /// this command's action.
+    /// Unset is the same as `false`, but leaves the decision to any template the command uses.
+    #[serde(default)]
+    pub passthrough: Option<bool>,
+
+    /// Directories to search for programs before the inherited `PATH` when executing this
+    /// command.
+    #[serde(default)]
+    pub path_prepend: Vec<String>,
+
+    /// Directories to search for programs after the inherited `PATH` when executing this command.
+    #[serde(default)]
+    pub path_append: Vec<String>,
+
+    /// An optional number of seconds that this command's action can run for in total before it's
+    /// stopped.
+    /// Dependencies and the `pre_run` and `post_run` actions aren't included.
+    pub timeout: Option<u64>,
+
     /// The [`ActionConfig`] that this command will perform when executed.
     #[serde(flatten)]
     pub action: Option<ActionConfig>,
+
+    /// [`ActionConfig`]s to perform instead of the `action` on specific platforms.
+    #[serde(default)]
+    pub platform_actions: LinkedHashMap<PlatformFilter, ActionConfig>,
+
+    /// The path to the config file that this command was defined in.
+    /// This is `None` for commands that were read from stdin.
+    #[serde(skip)]
+    pub source: Option<PathBuf>,
+
+    /// Other commands with the same name as this one, such as an import with the same alias.
+    /// The user is asked to pick one of them, or this command, when running the command.
+    #[serde(skip)]
+    pub candidates: Vec<CommandConfig>,
 }
 
 fn default_hidden() -> bool {
     false
 }
 
+/// Information about a command which doesn't affect how it runs.
+/// Unknown fields are rejected so that typos aren't silently ignored.
+///
+/// Example:
+/// ```yaml
+/// meta:
+///   owner: team-payments
+///   ticket: PAY-123
+///   tags: [billing, deploy]
+/// ```
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct CommandMetadata {
+    /// The person or team responsible for the command.
+    pub owner: Option<String>,
+
+    /// A ticket or issue tracking the command.
+    pub ticket: Option<String>,
+
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl CommandMetadata {
+    /// Merges the fields set on this metadata over the fields set on the `template`'s.
+    fn merged_over(self, template: &CommandMetadata) -> CommandMetadata {
+        CommandMetadata {
+            owner: self.owner.or(template.owner.clone()),
+            ticket: self.ticket.or(template.ticket.clone()),
+            tags: if self.tags.is_empty() {
+                template.tags.clone()
+            } else {
+                self.tags
+            },
+        }
+    }
+}
+
+/// Denotes the lock held while a command is executed.
+///
+/// Example:
+/// ```yaml
+/// # Locked using the command's path
+/// lock: true
+///
+/// # Locked using a name, other commands with the same lock name can't run at the same time
+/// lock: deployments
+/// ```
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(untagged)]
+pub enum LockConfig {
+    Enabled(bool),
+    Named(String),
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 #[serde(untagged)]
 pub enum OneOrManyPlatforms {
@@ -591,21 +1944,55 @@ pub enum OneOrManyPlatforms {
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub struct OnePlatform {
-    pub platform: Platform,
+    pub platform: PlatformFilter,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub struct ManyPlatforms {
-    pub platforms: Vec<Platform>,
+    pub platforms: Vec<PlatformFilter>,
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+/// A [`Platform`], or a [`PlatformFamily`], that imports, commands and platform actions can be
+/// restricted to.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Clone)]
+#[serde(untagged)]
+pub enum PlatformFilter {
+    Platform(Platform),
+    Family(PlatformFamily),
+}
+
+impl PlatformFilter {
+    /// Whether `platform` is this platform, or belongs to this family of platforms.
+    pub fn includes(&self, platform: &Platform) -> bool {
+        match self {
+            PlatformFilter::Platform(filter_platform) => filter_platform == platform,
+            PlatformFilter::Family(PlatformFamily::Unix) => {
+                matches!(platform, Platform::Linux | Platform::MacOS)
+            }
+        }
+    }
+}
+
+impl From<Platform> for PlatformFilter {
+    fn from(platform: Platform) -> Self {
+        PlatformFilter::Platform(platform)
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Clone)]
 pub enum Platform {
     MacOS,
     Windows,
     Linux,
 }
 
+/// A group of [`Platform`]s which can be targeted at once.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Clone)]
+pub enum PlatformFamily {
+    /// Every Unix-like platform, [`Platform::Linux`] and [`Platform::MacOS`].
+    Unix,
+}
+
 /// Encapsulates either a single [`ExecutionConfigVariant`] ([`ActionConfig::SingleStep`] with a [`SingleActionConfig`])
 /// or multiple [`ExecutionConfigVariant`] ([`ActionConfig::MultiStep`] with a [`MultiActionConfig`]).
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
@@ -614,6 +2001,10 @@ pub enum ActionConfig {
     SingleStep(SingleActionConfig),
     MultiStep(MultiActionConfig),
     Alias(AliasActionConfig),
+    CommandAlias(CommandAliasActionConfig),
+    WriteFile(WriteFileActionConfig),
+    Open(OpenActionConfig),
+    Assert(AssertActionConfig),
 }
 
 /// Contains the prefix for a command to execute.
@@ -622,32 +2013,169 @@ pub struct AliasActionConfig {
     pub alias: String,
 }
 
+/// Contains the path of another command to run in place of this one, which receives any extra
+/// arguments.
+///
+/// Example:
+/// ```yaml
+/// alias:
+///     command: db.migrate
+/// ```
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
-pub struct SingleActionConfig {
-    pub action: ExecutionConfigVariant,
+pub struct CommandAliasActionConfig {
+    pub alias: CommandAliasConfig,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
-pub struct MultiActionConfig {
-    pub actions: Vec<ExecutionConfigVariant>,
+pub struct CommandAliasConfig {
+    /// The `.`-separated path of the command to run, such as `db.migrate`.
+    pub command: String,
 }
 
-/// The kind of command to execute.
+/// Contains the path and content of a file to write.
+///
+/// Example:
+/// ```yaml
+/// write_file: ./config.json
+/// content: |
+///     { "name": "$name" }
+/// ```
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
-#[serde(untagged)]
-pub enum ExecutionConfigVariant {
-    /// Encapsulates a [`ShellCommandConfigVariant`].
-    ShellCommand(ShellCommandConfigVariant),
+pub struct WriteFileActionConfig {
+    /// The path of the file to write.
+    /// Any missing parent directories will be created.
+    #[serde(rename = "write_file")]
+    pub path: String,
 
-    /// Encapsulates a [`RawCommandConfigVariant`].
-    RawCommand(RawCommandConfigVariant),
+    /// The content to write to the file.
+    pub content: String,
+
+    /// The [`TemplateEngine`] used to substitute variables into the content.
+    #[serde(default)]
+    pub template: TemplateEngine,
 }
 
-/// The configuration for a raw command.
-/// Raw commands are simply commands executed without a shell.
+/// Contains the URL or path to open with the default application for it.
+///
+/// Example:
+/// ```yaml
+/// open: https://example.com/dashboards/$environment
+/// ```
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
-#[serde(untagged)]
-pub enum RawCommandConfigVariant {
+pub struct OpenActionConfig {
+    /// The URL or path to open.
+    #[serde(rename = "open")]
+    pub target: String,
+}
+
+/// Contains a check to run, and the message to fail with if it doesn't pass.
+///
+/// Example:
+/// ```yaml
+/// assert:
+///     command: test -n "$TOKEN"
+///     message: TOKEN must be set
+/// ```
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct AssertActionConfig {
+    pub assert: AssertConfig,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct AssertConfig {
+    /// The bash command to run. The assertion fails if it exits with a non-zero exit code.
+    pub command: String,
+
+    /// The message to fail with, with variables substituted into it.
+    pub message: String,
+}
+
+/// How variables are substituted into text.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateEngine {
+    /// Bash-style `$name` substitution.
+    #[default]
+    Simple,
+
+    /// Renders the text as a [Tera](https://keats.github.io/tera/) template, with the variables as
+    /// the context.
+    /// Requires the `tera` feature.
+    Tera,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct SingleActionConfig {
+    pub action: ExecutionConfigVariant,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct MultiActionConfig {
+    pub actions: Vec<ExecutionConfigVariant>,
+
+    /// When set to `true`, all of the actions are executed in a single bash invocation so that
+    /// changes to the working directory and other shell state carry over to the next action.
+    #[serde(default)]
+    pub shared_shell: bool,
+}
+
+/// The kind of command to execute.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(untagged)]
+pub enum ExecutionConfigVariant {
+    /// Encapsulates a [`ShellCommandConfigVariant`].
+    ShellCommand(ShellCommandConfigVariant),
+
+    /// Encapsulates a [`RawCommandConfigVariant`].
+    RawCommand(RawCommandConfigVariant),
+}
+
+impl ExecutionConfigVariant {
+    /// The first field set on this step which can't be used with `shared_shell`, since the steps
+    /// are combined into a single bash script.
+    pub fn shared_shell_unsupported_field(&self) -> Option<&'static str> {
+        let (banner, label, max_output_bytes, allow_extra_args, template) = match self {
+            ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(bash_conf)) => (
+                &bash_conf.banner,
+                &bash_conf.label,
+                &bash_conf.max_output_bytes,
+                bash_conf.allow_extra_args,
+                &TemplateEngine::Simple,
+            ),
+            ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(_)) => {
+                return None
+            }
+            ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::RawCommandConfig(
+                raw_conf,
+            )) => (
+                &raw_conf.banner,
+                &raw_conf.label,
+                &raw_conf.max_output_bytes,
+                false,
+                &raw_conf.template,
+            ),
+        };
+
+        [
+            ("banner", banner.is_some()),
+            ("label", label.is_some()),
+            ("max_output_bytes", max_output_bytes.is_some()),
+            ("allow_extra_args", allow_extra_args),
+            ("template", *template != TemplateEngine::Simple),
+        ]
+        .into_iter()
+        .find(|(_, is_set)| *is_set)
+        .map(|(field, _)| field)
+    }
+}
+
+/// The configuration for a raw command.
+/// Raw commands are simply commands executed without a shell.
+// Commands are only created when the config is loaded, so the size isn't worth boxing for
+#[allow(clippy::large_enum_variant)]
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(untagged)]
+pub enum RawCommandConfigVariant {
     /// Denotes a shorthand execution.
     ///
     /// Example:
@@ -661,7 +2189,7 @@ pub enum RawCommandConfigVariant {
 }
 
 /// The configuration for a raw command.
-#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
 pub struct RawCommandConfig {
     /// An optional working directory for the command to be executed in.
     /// If not specified, then the command will be executed in the current directory.
@@ -672,6 +2200,74 @@ pub struct RawCommandConfig {
     /// The command to execute.
     #[serde(alias = "cmd")]
     pub command: String,
+
+    /// The [`TemplateEngine`] used to substitute variables into the command.
+    #[serde(default)]
+    pub template: TemplateEngine,
+
+    /// An optional user to execute the command as, using `sudo`.
+    pub run_as: Option<String>,
+
+    /// Where the command reads its stdin from.
+    #[serde(default, deserialize_with = "deserialize_stdin")]
+    pub stdin: StdinConfig,
+
+    /// An optional file to write the command's stdout to, instead of inheriting it.
+    pub stdout_file: Option<String>,
+
+    /// An optional file to write the command's stderr to, instead of inheriting it.
+    pub stderr_file: Option<String>,
+
+    /// When set to `true`, output is appended to the `stdout_file` and `stderr_file` instead of
+    /// overwriting them.
+    #[serde(default)]
+    pub append: bool,
+
+    /// An optional message to print before the command is executed.
+    pub banner: Option<String>,
+
+    /// An optional label to print before each line of the command's output, as `[label] `.
+    /// Output that's redirected to a file isn't prefixed.
+    pub label: Option<String>,
+
+    /// The maximum number of bytes that the command can write to stdout or stderr when its output
+    /// is captured, such as for execution variables, before it's stopped.
+    /// Overrides [`DingusOptions::max_output_bytes`].
+    pub max_output_bytes: Option<u64>,
+
+    /// Arguments passed to the command as-is after the ones in `command`, without any variables
+    /// substituted into them.
+    /// These are never read from the config, they're the passthrough arguments given to dingus.
+    #[serde(skip)]
+    pub extra_args: Vec<String>,
+}
+
+/// Where a command reads its stdin from.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StdinConfig {
+    /// Stdin is inherited from dingus, so the command can read from the terminal or pipeline.
+    #[default]
+    Inherit,
+
+    /// The command reads nothing from stdin, which stops non-interactive commands from waiting
+    /// on input, or consuming input meant for something else.
+    Null,
+
+    /// The provided text is written to stdin, with variables substituted into it.
+    Text(String),
+
+    /// The file at the provided path is read from stdin.
+    File(String),
+}
+
+/// Allows `stdin: null` to be written without quotes, which YAML would otherwise read as a
+/// missing value.
+fn deserialize_stdin<'de, D>(deserializer: D) -> Result<StdinConfig, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(Option::<StdinConfig>::deserialize(deserializer)?.unwrap_or(StdinConfig::Null))
 }
 
 /// The configuration for a shell command.
@@ -695,6 +2291,47 @@ pub struct BashCommandConfig {
     #[serde(rename = "bash")]
     #[serde(alias = "sh")]
     pub command: String,
+
+    /// An optional user to execute the command as, using `sudo`.
+    pub run_as: Option<String>,
+
+    /// Where the command reads its stdin from.
+    #[serde(default, deserialize_with = "deserialize_stdin")]
+    pub stdin: StdinConfig,
+
+    /// An optional file to write the command's stdout to, instead of inheriting it.
+    pub stdout_file: Option<String>,
+
+    /// An optional file to write the command's stderr to, instead of inheriting it.
+    pub stderr_file: Option<String>,
+
+    /// When set to `true`, output is appended to the `stdout_file` and `stderr_file` instead of
+    /// overwriting them.
+    #[serde(default)]
+    pub append: bool,
+
+    /// When set to `true`, any extra arguments provided on the command-line are appended to the
+    /// command.
+    #[serde(default)]
+    pub allow_extra_args: bool,
+
+    /// An optional message to print before the command is executed.
+    pub banner: Option<String>,
+
+    /// An optional label to print before each line of the command's output, as `[label] `.
+    /// Output that's redirected to a file isn't prefixed.
+    pub label: Option<String>,
+
+    /// The maximum number of bytes that the command can write to stdout or stderr when its output
+    /// is captured, such as for execution variables, before it's stopped.
+    /// Overrides [`DingusOptions::max_output_bytes`].
+    pub max_output_bytes: Option<u64>,
+
+    /// Arguments passed to a script with a shebang as-is, after the script itself.
+    /// These are never read from the config, they're the extra or passthrough arguments given to
+    /// dingus.
+    #[serde(skip)]
+    pub extra_args: Vec<String>,
 }
 
 #[cfg(test)]
@@ -704,13 +2341,23 @@ mod tests {
     use crate::config::Platform::Linux;
     use crate::config::RawCommandConfigVariant::Shorthand;
     use std::io::Write;
-    use tempfile::NamedTempFile;
+    use tempfile::{NamedTempFile, TempDir};
 
     fn bash_exec(command: &str, workdir: Option<String>) -> ExecutionConfigVariant {
         return ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
             BashCommandConfig {
                 working_directory: workdir,
                 command: command.to_string(),
+                run_as: None,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                allow_extra_args: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
             },
         ));
     }
@@ -731,6 +2378,79 @@ mod tests {
         assert!(config.variables.is_empty());
     }
 
+    #[test]
+    fn platform_actions_parsed() {
+        let yaml = "commands:
+    build:
+        platform_actions:
+            Linux:
+                action: ./build.sh
+            Windows:
+                actions:
+                    - ./build.ps1";
+        let config = parse_config(&yaml.to_string(), Platform::Linux).unwrap();
+
+        let build_command = config.commands.get("build").unwrap();
+        assert_eq!(build_command.action, None);
+        assert_eq!(
+            build_command.platform_actions.get(&Platform::Linux.into()),
+            Some(&ActionConfig::SingleStep(SingleActionConfig {
+                action: ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(
+                    "./build.sh".to_string()
+                )),
+            }))
+        );
+        assert!(matches!(
+            build_command
+                .platform_actions
+                .get(&Platform::Windows.into()),
+            Some(ActionConfig::MultiStep(_))
+        ));
+    }
+
+    #[test]
+    fn platform_actions_parsed_for_platform_families() {
+        let yaml = "commands:
+    build:
+        platform_actions:
+            Unix:
+                action: ./build.sh";
+        let config = parse_config(&yaml.to_string(), Platform::Linux).unwrap();
+
+        let build_command = config.commands.get("build").unwrap();
+        assert!(build_command
+            .platform_actions
+            .contains_key(&PlatformFilter::Family(PlatformFamily::Unix)));
+    }
+
+    #[test]
+    fn variable_export_parsed() {
+        let yaml = "variables:
+    password:
+        value: hunter2
+        export: false
+    username:
+        value: dingus
+commands:
+    demo:
+        action: echo \"Hello, World!\"";
+        let config = parse_config(&yaml.to_string(), Platform::Linux).unwrap();
+
+        assert!(!config.variables.get("password").unwrap().is_exported());
+        assert!(config.variables.get("username").unwrap().is_exported());
+    }
+
+    #[test]
+    fn default_shell_parsed() {
+        let yaml = "default_shell: zsh
+commands:
+    demo:
+        action: echo \"Hello, World!\"";
+        let config = parse_config(&yaml.to_string(), Platform::Linux).unwrap();
+
+        assert_eq!(config.default_shell, Some(Shell::Zsh));
+    }
+
     #[test]
     fn shorthand_literal_variable_parsed() {
         let yaml = "variables:
@@ -782,6 +2502,10 @@ commands:
                 value: "My root value".to_string(),
                 argument: None,
                 environment_variable_name: None,
+                prompt_if_missing: None,
+                transform: vec![],
+                export: true,
+                sensitive: false,
             })
         );
 
@@ -793,6 +2517,10 @@ commands:
                 value: "My command value".to_string(),
                 argument: Some(ArgumentConfigVariant::Shorthand("command-arg".to_string())),
                 environment_variable_name: Some("MY_VAR".to_string()),
+                prompt_if_missing: None,
+                transform: vec![],
+                export: true,
+                sensitive: false,
             })
         )
     }
@@ -839,6 +2567,15 @@ commands:
                 execution: bash_exec("echo \"My root value\"", Some("../".to_string())),
                 argument: None,
                 environment_variable_name: None,
+                encoding: Encoding::Strict,
+                trim: Trim::End,
+                prompt_if_missing: None,
+                transform: vec![],
+                export: true,
+                r#match: None,
+                group: None,
+                lazy: false,
+                sensitive: false,
             })
         );
 
@@ -852,6 +2589,15 @@ commands:
                     "command-arg-1".to_string()
                 )),
                 environment_variable_name: Some("MY_VAR_1".to_string()),
+                encoding: Encoding::Strict,
+                trim: Trim::End,
+                prompt_if_missing: None,
+                transform: vec![],
+                export: true,
+                r#match: None,
+                group: None,
+                lazy: false,
+                sensitive: false,
             })
         );
 
@@ -864,8 +2610,20 @@ commands:
                     description: Some("Command level variable".to_string()),
                     long: "command-arg-2".to_string(),
                     short: Some('c'),
+                    aliases: vec![],
+                    conflicts_with: vec![],
+                    requires: vec![],
                 })),
                 environment_variable_name: Some("MY_VAR_2".to_string()),
+                encoding: Encoding::Strict,
+                trim: Trim::End,
+                prompt_if_missing: None,
+                transform: vec![],
+                export: true,
+                r#match: None,
+                group: None,
+                lazy: false,
+                sensitive: false,
             })
         );
 
@@ -878,9 +2636,20 @@ commands:
                     PositionalArgumentConfig {
                         description: Some("Command level variable".to_string()),
                         position: 1,
+                        conflicts_with: vec![],
+                        requires: vec![],
                     }
                 )),
                 environment_variable_name: Some("MY_VAR_3".to_string()),
+                encoding: Encoding::Strict,
+                trim: Trim::End,
+                prompt_if_missing: None,
+                transform: vec![],
+                export: true,
+                r#match: None,
+                group: None,
+                lazy: false,
+                sensitive: false,
             })
         )
     }
@@ -934,8 +2703,15 @@ commands:
                     options: PromptOptionsVariant::Text(TextPromptOptions {
                         multi_line: false,
                         sensitive: false,
-                    })
+                        min_length: None,
+                        max_length: None,
+                        default: None,
+                    }),
+                    help: None,
                 },
+                transform: vec![],
+                export: true,
+                sensitive: false,
             })
         );
 
@@ -953,8 +2729,12 @@ commands:
                             "Pizza".to_string(),
                             "Fries".to_string()
                         ])
-                    })
+                    }),
+                    help: None,
                 },
+                transform: vec![],
+                export: true,
+                sensitive: false,
             })
         );
 
@@ -969,9 +2749,16 @@ commands:
                     message: "What's your password?".to_string(),
                     options: PromptOptionsVariant::Text(TextPromptOptions {
                         multi_line: false,
-                        sensitive: true
-                    })
+                        sensitive: true,
+                        min_length: None,
+                        max_length: None,
+                        default: None,
+                    }),
+                    help: None,
                 },
+                transform: vec![],
+                export: true,
+                sensitive: false,
             })
         );
 
@@ -985,9 +2772,16 @@ commands:
                     message: "What's your life story?".to_string(),
                     options: PromptOptionsVariant::Text(TextPromptOptions {
                         multi_line: true,
-                        sensitive: false
-                    })
+                        sensitive: false,
+                        min_length: None,
+                        max_length: None,
+                        default: None,
+                    }),
+                    help: None,
                 },
+                transform: vec![],
+                export: true,
+                sensitive: false,
             })
         );
 
@@ -1001,10 +2795,89 @@ commands:
                     message: "What's your favourite line?".to_string(),
                     options: PromptOptionsVariant::Select(SelectPromptOptions {
                         options: SelectOptionsConfig::Execution(ExecutionSelectOptionsConfig {
-                            execution: raw_exec("cat example.txt")
+                            execution: raw_exec("cat example.txt"),
+                            encoding: Encoding::Strict,
+                            json: false,
                         }),
-                    })
-                }
+                    }),
+                    help: None,
+                },
+                transform: vec![],
+                export: true,
+                sensitive: false,
+            })
+        )
+    }
+
+    #[test]
+    fn autocomplete_prompt_variable_parsed() {
+        let yaml = "commands:
+    demo:
+        variables:
+            branch:
+                prompt:
+                    message: Which branch?
+                    suggestions:
+                        execute: git branch --format=%(refname:short)
+                    restrict_to_suggestions: true
+        action: git checkout $branch";
+
+        let config = parse_config(&yaml.to_string(), Linux).unwrap();
+        let demo_command = config.commands.get("demo").unwrap();
+
+        let branch_variable = demo_command.variables.get("branch").unwrap();
+        assert_eq!(
+            branch_variable,
+            &VariableConfig::Prompt(PromptVariableConfig {
+                argument: None,
+                environment_variable_name: None,
+                prompt: PromptConfig {
+                    message: "Which branch?".to_string(),
+                    options: PromptOptionsVariant::Autocomplete(AutocompletePromptOptions {
+                        suggestions: SelectOptionsConfig::Execution(ExecutionSelectOptionsConfig {
+                            execution: raw_exec("git branch --format=%(refname:short)"),
+                            encoding: Encoding::Strict,
+                            json: false,
+                        }),
+                        restrict_to_suggestions: true,
+                    }),
+                    help: None,
+                },
+                transform: vec![],
+                export: true,
+                sensitive: false,
+            })
+        )
+    }
+
+    #[test]
+    fn random_variable_parsed() {
+        let yaml = "commands:
+    demo:
+        variables:
+            container_name:
+                arg: name
+                random:
+                    kind: hex
+                    len: 8
+        action: docker run --name $container_name alpine";
+
+        let config = parse_config(&yaml.to_string(), Linux).unwrap();
+        let demo_command = config.commands.get("demo").unwrap();
+
+        let container_name_variable = demo_command.variables.get("container_name").unwrap();
+        assert_eq!(
+            container_name_variable,
+            &VariableConfig::Random(RandomVariableConfig {
+                argument: Some(ArgumentConfigVariant::Shorthand("name".to_string())),
+                environment_variable_name: None,
+                random: RandomConfig {
+                    kind: RandomKind::Hex,
+                    length: Some(8),
+                },
+                transform: vec![],
+                export: true,
+                sensitive: false,
             })
         )
     }
@@ -1038,8 +2911,16 @@ commands:
                     description: Some("Your name.".to_string()),
                     long: "name".to_string(),
                     short: Some('n'),
+                    aliases: vec![],
+                    conflicts_with: vec![],
+                    requires: vec![],
                 }),
                 environment_variable_name: None,
+                transform: vec![],
+                export: true,
+                default_from_command: None,
+                trim: Trim::End,
+                sensitive: false,
             })
         );
 
@@ -1049,6 +2930,11 @@ commands:
             &VariableConfig::Argument(ArgumentVariableConfig {
                 argument: ArgumentConfigVariant::Shorthand("age".to_string()),
                 environment_variable_name: None,
+                transform: vec![],
+                export: true,
+                default_from_command: None,
+                trim: Trim::End,
+                sensitive: false,
             })
         );
 
@@ -1058,9 +2944,16 @@ commands:
             &VariableConfig::Argument(ArgumentVariableConfig {
                 argument: ArgumentConfigVariant::Positional(PositionalArgumentConfig {
                     description: Some("Your favourite food.".to_string()),
-                    position: 1
+                    position: 1,
+                    conflicts_with: vec![],
+                    requires: vec![],
                 }),
                 environment_variable_name: None,
+                transform: vec![],
+                export: true,
+                default_from_command: None,
+                trim: Trim::End,
+                sensitive: false,
             })
         );
     }
@@ -1099,7 +2992,36 @@ commands:
         assert_eq!(command_variable_names[2], "command-var-3".to_string());
     }
 
-    // TODO: Command order is preserved
+    #[test]
+    fn command_order_is_preserved() {
+        let yaml = "commands:
+    command-3:
+        action: echo 3
+    command-1:
+        commands:
+            subcommand-2:
+                action: echo 2
+            subcommand-1:
+                action: echo 1
+    command-2:
+        action: echo 2";
+        let config = parse_config(&yaml.to_string(), Platform::Linux).unwrap();
+
+        let command_names: Vec<String> =
+            config.commands.iter().map(|kv| kv.0.to_string()).collect();
+        assert_eq!(command_names[0], "command-3".to_string());
+        assert_eq!(command_names[1], "command-1".to_string());
+        assert_eq!(command_names[2], "command-2".to_string());
+
+        let parent_command = config.commands.get("command-1").unwrap();
+        let subcommand_names: Vec<String> = parent_command
+            .commands
+            .iter()
+            .map(|kv| kv.0.to_string())
+            .collect();
+        assert_eq!(subcommand_names[0], "subcommand-2".to_string());
+        assert_eq!(subcommand_names[1], "subcommand-1".to_string());
+    }
 
     #[test]
     fn single_action_command_parses() {
@@ -1114,7 +3036,7 @@ commands:
             &CommandConfig {
                 name: None,
                 description: None,
-                hidden: false,
+                hidden: None,
                 platform: None,
                 variables: Default::default(),
                 commands: Default::default(),
@@ -1123,6 +3045,7 @@ commands:
                         "ls".to_string()
                     )),
                 })),
+                ..Default::default()
             }
         );
     }
@@ -1140,13 +3063,14 @@ commands:
             &CommandConfig {
                 name: None,
                 description: None,
-                hidden: false,
+                hidden: None,
                 platform: None,
                 variables: Default::default(),
                 commands: Default::default(),
                 action: Some(ActionConfig::Alias(AliasActionConfig {
                     alias: "docker compose -f docker-compose.deps.yml".to_string()
                 })),
+                ..Default::default()
             }
         );
     }
@@ -1166,7 +3090,7 @@ commands:
                 name: None,
                 platform: None,
                 description: Some("Says hello.".to_string()),
-                hidden: false,
+                hidden: None,
                 variables: Default::default(),
                 commands: Default::default(),
                 action: Some(ActionConfig::SingleStep(SingleActionConfig {
@@ -1174,6 +3098,7 @@ commands:
                         "ls".to_string()
                     )),
                 })),
+                ..Default::default()
             }
         );
     }
@@ -1196,7 +3121,7 @@ commands:
             &CommandConfig {
                 name: None,
                 description: None,
-                hidden: false,
+                hidden: None,
                 platform: None,
                 variables: Default::default(),
                 commands: Default::default(),
@@ -1205,6 +3130,7 @@ commands:
                         "ls".to_string()
                     )),
                 })),
+                ..Default::default()
             }
         );
 
@@ -1216,7 +3142,7 @@ commands:
             &CommandConfig {
                 name: None,
                 description: None,
-                hidden: false,
+                hidden: None,
                 platform: None,
                 variables: Default::default(),
                 commands: map,
@@ -1225,6 +3151,7 @@ commands:
                         "cat example.txt".to_string()
                     )),
                 })),
+                ..Default::default()
             }
         );
     }
@@ -1246,7 +3173,7 @@ commands:
             &CommandConfig {
                 name: None,
                 description: None,
-                hidden: false,
+                hidden: None,
                 platform: None,
                 variables: Default::default(),
                 commands: Default::default(),
@@ -1255,6 +3182,7 @@ commands:
                         "ls".to_string()
                     )),
                 })),
+                ..Default::default()
             }
         );
 
@@ -1266,11 +3194,12 @@ commands:
             &CommandConfig {
                 name: None,
                 description: None,
-                hidden: false,
+                hidden: None,
                 platform: None,
                 variables: Default::default(),
                 commands: map,
                 action: None,
+                ..Default::default()
             }
         );
     }
@@ -1292,7 +3221,7 @@ commands:
             &CommandConfig {
                 name: None,
                 description: None,
-                hidden: false,
+                hidden: None,
                 platform: None,
                 variables: Default::default(),
                 commands: Default::default(),
@@ -1305,7 +3234,9 @@ commands:
                             "ls".to_string()
                         )),
                     ],
+                    shared_shell: false,
                 })),
+                ..Default::default()
             }
         );
     }
@@ -1330,9 +3261,9 @@ commands:
             &CommandConfig {
                 name: None,
                 description: None,
-                hidden: false,
+                hidden: None,
                 platform: Some(Many(ManyPlatforms {
-                    platforms: vec![Platform::Linux, Platform::MacOS]
+                    platforms: vec![Platform::Linux.into(), Platform::MacOS.into()]
                 })),
                 variables: Default::default(),
                 commands: Default::default(),
@@ -1341,6 +3272,7 @@ commands:
                         "cat example.txt".to_string()
                     ))
                 })),
+                ..Default::default()
             }
         );
 
@@ -1349,9 +3281,9 @@ commands:
             &CommandConfig {
                 name: None,
                 description: None,
-                hidden: false,
+                hidden: None,
                 platform: Some(One(OnePlatform {
-                    platform: Platform::Windows
+                    platform: Platform::Windows.into()
                 })),
                 variables: Default::default(),
                 commands: Default::default(),
@@ -1360,6 +3292,7 @@ commands:
                         "Get-Content example.txt".to_string()
                     ))
                 })),
+                ..Default::default()
             }
         );
     }
@@ -1378,7 +3311,7 @@ commands:
             &CommandConfig {
                 name: Some("demonstration".to_string()),
                 description: None,
-                hidden: false,
+                hidden: None,
                 platform: None,
                 variables: Default::default(),
                 commands: Default::default(),
@@ -1387,6 +3320,7 @@ commands:
                         "cat example.txt".to_string()
                     ))
                 })),
+                ..Default::default()
             }
         );
     }
@@ -1407,7 +3341,7 @@ commands:
             &CommandConfig {
                 name: None,
                 description: None,
-                hidden: false,
+                hidden: None,
                 platform: None,
                 variables: Default::default(),
                 commands: Default::default(),
@@ -1417,20 +3351,237 @@ commands:
                             BashCommandConfig {
                                 working_directory: None,
                                 command: "echo \"Hello, World!\"".to_string(),
+                                run_as: None,
+                                stdout_file: None,
+                                stderr_file: None,
+                                append: false,
+                                allow_extra_args: false,
+                                banner: None,
+                                stdin: StdinConfig::Inherit,
+                                label: None,
+                                max_output_bytes: None,
+                                extra_args: vec![],
                             }
                         )),
                         ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
                             BashCommandConfig {
                                 working_directory: Some("/".to_string()),
                                 command: "pwd".to_string(),
+                                run_as: None,
+                                stdout_file: None,
+                                stderr_file: None,
+                                append: false,
+                                allow_extra_args: false,
+                                banner: None,
+                                stdin: StdinConfig::Inherit,
+                                label: None,
+                                max_output_bytes: None,
+                                extra_args: vec![],
                             }
                         )),
-                    ]
+                    ],
+                    shared_shell: false,
                 })),
+                ..Default::default()
             }
         );
     }
 
+    #[test]
+    fn stdin_parses() {
+        let yaml = "commands:
+    demo:
+        actions:
+            - bash: ./lint.sh
+              stdin: null
+            - command: ./seed.sh
+              stdin:
+                  file: ./seed.sql
+            - bash: cat
+              stdin:
+                  text: Hello, $name!
+            - bash: ./deploy.sh";
+        let config = parse_config(&yaml.to_string(), Platform::Linux).unwrap();
+
+        let Some(ActionConfig::MultiStep(multi_action_config)) =
+            &config.commands.get("demo").unwrap().action
+        else {
+            panic!("expected a multi-step action");
+        };
+        let stdin_configs: Vec<&StdinConfig> = multi_action_config
+            .actions
+            .iter()
+            .map(|execution_config| match execution_config {
+                ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+                    bash_config,
+                )) => &bash_config.stdin,
+                ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::RawCommandConfig(
+                    raw_config,
+                )) => &raw_config.stdin,
+                ExecutionConfigVariant::RawCommand(Shorthand(_)) => panic!("unexpected shorthand"),
+            })
+            .collect();
+        assert_eq!(
+            stdin_configs,
+            vec![
+                &StdinConfig::Null,
+                &StdinConfig::File("./seed.sql".to_string()),
+                &StdinConfig::Text("Hello, $name!".to_string()),
+                &StdinConfig::Inherit,
+            ]
+        );
+    }
+
+    #[test]
+    fn banner_and_label_parse() {
+        let yaml = "commands:
+    demo:
+        actions:
+            - bash: ./server
+              banner: Starting the server...
+              label: api";
+        let config = parse_config(&yaml.to_string(), Platform::Linux).unwrap();
+
+        let Some(ActionConfig::MultiStep(multi_action_config)) =
+            &config.commands.get("demo").unwrap().action
+        else {
+            panic!("expected a multi-step action");
+        };
+        let ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(bash_config)) =
+            &multi_action_config.actions[0]
+        else {
+            panic!("expected a bash action");
+        };
+        assert_eq!(
+            bash_config.banner,
+            Some("Starting the server...".to_string())
+        );
+        assert_eq!(bash_config.label, Some("api".to_string()));
+    }
+
+    #[test]
+    fn command_using_template_inherits_fields() {
+        let yaml = "templates:
+    compose:
+        description: Runs docker compose.
+        variables:
+            file: docker-compose.yaml
+        action: docker compose -f $file up
+commands:
+    up:
+        use: compose
+    deps:
+        use: compose
+        description: Starts the dependencies.
+        variables:
+            file: docker-compose.deps.yaml";
+        let config = parse_config(&yaml.to_string(), Platform::Linux).unwrap();
+
+        let up_command = config.commands.get("up").unwrap();
+        assert_eq!(
+            up_command.description,
+            Some("Runs docker compose.".to_string())
+        );
+        assert_eq!(
+            up_command.variables.get("file").unwrap(),
+            &VariableConfig::ShorthandLiteral("docker-compose.yaml".to_string())
+        );
+        assert_eq!(
+            up_command.action,
+            Some(ActionConfig::SingleStep(SingleActionConfig {
+                action: raw_exec("docker compose -f $file up"),
+            }))
+        );
+
+        // The command's own fields take precedence over the template
+        let deps_command = config.commands.get("deps").unwrap();
+        assert_eq!(
+            deps_command.description,
+            Some("Starts the dependencies.".to_string())
+        );
+        assert_eq!(
+            deps_command.variables.get("file").unwrap(),
+            &VariableConfig::ShorthandLiteral("docker-compose.deps.yaml".to_string())
+        );
+        assert_eq!(
+            deps_command.action,
+            Some(ActionConfig::SingleStep(SingleActionConfig {
+                action: raw_exec("docker compose -f $file up"),
+            }))
+        );
+    }
+
+    #[test]
+    fn command_using_template_can_turn_off_flags() {
+        let yaml = "templates:
+    base:
+        hidden: true
+        passthrough: true
+        action: cargo test
+commands:
+    inherited:
+        use: base
+    visible:
+        use: base
+        hidden: false
+        passthrough: false";
+        let config = parse_config(&yaml.to_string(), Platform::Linux).unwrap();
+
+        let inherited_command = config.commands.get("inherited").unwrap();
+        assert!(inherited_command.is_hidden());
+        assert!(inherited_command.is_passthrough());
+
+        let visible_command = config.commands.get("visible").unwrap();
+        assert!(!visible_command.is_hidden());
+        assert!(!visible_command.is_passthrough());
+    }
+
+    #[test]
+    fn command_using_unknown_template_fails() {
+        let yaml = "commands:
+    demo:
+        commands:
+            nested:
+                use: missing";
+        let result = parse_config(&yaml.to_string(), Platform::Linux);
+
+        assert!(matches!(
+            result,
+            Err(ConfigError::UnknownTemplate { command, template })
+                if command == "nested" && template == "missing"
+        ));
+    }
+
+    #[test]
+    fn command_metadata_is_parsed_and_checked() {
+        let yaml = "commands:
+    deploy:
+        meta:
+            owner: team-payments
+            ticket: PAY-123
+            tags: [billing, deploy]
+        action: ./deploy.sh";
+        let config = parse_config(&yaml.to_string(), Platform::Linux).unwrap();
+
+        assert_eq!(
+            config.commands.get("deploy").unwrap().meta,
+            CommandMetadata {
+                owner: Some("team-payments".to_string()),
+                ticket: Some("PAY-123".to_string()),
+                tags: vec!["billing".to_string(), "deploy".to_string()],
+            }
+        );
+
+        let yaml = "commands:
+    deploy:
+        meta:
+            onwer: team-payments
+        action: ./deploy.sh";
+        let result = parse_config(&yaml.to_string(), Platform::Linux);
+
+        assert!(result.is_err());
+    }
+
     #[test]
     fn import() {
         let yaml3 = "variables:
@@ -1494,7 +3645,9 @@ commands:
         );
         assert_eq!(
             second_level_command.platform,
-            Some(One(OnePlatform { platform: Linux }))
+            Some(One(OnePlatform {
+                platform: Linux.into()
+            }))
         );
         assert_eq!(
             second_level_command.variables.get("last_name").unwrap(),
@@ -1510,13 +3663,79 @@ commands:
                 ))
             }))
         );
-        assert_eq!(third_level_command.hidden, true);
+        assert!(third_level_command.is_hidden());
         assert_eq!(
             third_level_command.variables.get("age").unwrap(),
             &VariableConfig::ShorthandLiteral("Forty Two".to_string())
         );
     }
 
+    #[test]
+    fn import_sets_command_source() {
+        let yaml2 = "commands:
+    demo:
+        commands:
+            nested:
+                action: echo \"Hello, World!\""
+            .to_string();
+        let yaml2_file = create_temp_file(yaml2.as_str());
+        let yaml2_path = yaml2_file.path().to_str().unwrap();
+
+        let yaml1 = format!(
+            "imports:
+    - alias: other
+      source: {}
+commands:
+    demo:
+        action: echo \"Hello, World!\"",
+            yaml2_path
+        );
+
+        let config = parse_config(&yaml1.to_string(), Platform::Linux).unwrap();
+
+        // Sources for the root config are set by the caller
+        let root_demo_command = config.commands.get("demo").unwrap();
+        assert_eq!(root_demo_command.source, None);
+
+        let imported_demo_command = config
+            .commands
+            .get("other")
+            .unwrap()
+            .commands
+            .get("demo")
+            .unwrap();
+        assert_eq!(
+            imported_demo_command.source,
+            Some(PathBuf::from(yaml2_path))
+        );
+        assert_eq!(
+            imported_demo_command.commands.get("nested").unwrap().source,
+            Some(PathBuf::from(yaml2_path))
+        );
+    }
+
+    #[test]
+    fn import_with_invalid_git_source_fails() {
+        let yaml = "imports:
+    - alias: tasks
+      source: git+https://github.com/org/tasks.git@v1
+commands: {}"
+            .to_string();
+
+        let result = parse_config(&yaml, Platform::Linux);
+
+        match result {
+            Err(ConfigError::ImportFailed { alias, source }) => {
+                assert_eq!(alias, "tasks");
+                assert!(matches!(
+                    *source,
+                    ConfigError::GitSource(GitSourceError::Invalid { .. })
+                ));
+            }
+            _ => panic!("expected ImportFailed, got {result:?}"),
+        }
+    }
+
     #[test]
     fn import_for_other_platform_is_ignored() {
         let yaml2 = "commands:
@@ -1558,6 +3777,473 @@ commands:
         assert_eq!(second_level_command, None);
     }
 
+    #[test]
+    fn unix_import_applies_to_unix_like_platforms() {
+        let imported_file = create_temp_file(
+            "commands:
+    build:
+        action: make",
+        );
+        let yaml = format!(
+            "imports:
+    - alias: unix
+      source: {}
+      platform: Unix
+commands:
+    demo:
+        action: echo Hello",
+            imported_file.path().to_str().unwrap()
+        );
+
+        for (platform, expected) in [
+            (Platform::Linux, true),
+            (Platform::MacOS, true),
+            (Platform::Windows, false),
+        ] {
+            let config = parse_config(&yaml, platform.clone()).unwrap();
+            assert_eq!(
+                config.commands.contains_key("unix"),
+                expected,
+                "unexpected result on {platform:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn import_with_existing_name_becomes_candidate() {
+        let yaml2 = "commands:
+    build:
+        action: cargo build"
+            .to_string();
+        let yaml2_file = create_temp_file(yaml2.as_str());
+        let yaml2_path = yaml2_file.path().to_str().unwrap();
+
+        let yaml1 = format!(
+            "imports:
+    - alias: demo
+      source: {}
+commands:
+    demo:
+        action: echo \"Hello, World!\"",
+            yaml2_path
+        );
+
+        let config = parse_config(&yaml1.to_string(), Platform::Linux).unwrap();
+
+        let root_demo_command = config.commands.get("demo").unwrap();
+        assert_eq!(
+            root_demo_command.action,
+            Some(ActionConfig::SingleStep(SingleActionConfig {
+                action: ExecutionConfigVariant::RawCommand(Shorthand(
+                    "echo \"Hello, World!\"".to_string()
+                ))
+            }))
+        );
+
+        assert_eq!(root_demo_command.candidates.len(), 1);
+        let imported_demo_command = &root_demo_command.candidates[0];
+        assert_eq!(
+            imported_demo_command.source,
+            Some(PathBuf::from(yaml2_path))
+        );
+        assert!(imported_demo_command.commands.contains_key("build"));
+    }
+
+    #[test]
+    fn init_template_uses_provided_template() {
+        let template = "commands:
+    hello:
+        action: echo \"Hello from the team template!\"";
+        let template_file = create_temp_file(template);
+
+        let config_text =
+            read_init_template(Some(template_file.path().to_path_buf()), None).unwrap();
+
+        assert_eq!(config_text, template);
+    }
+
+    #[test]
+    fn init_template_uses_default_template_when_present() {
+        let template = "commands: {}";
+        let template_file = create_temp_file(template);
+
+        let config_text =
+            read_init_template(None, Some(template_file.path().to_path_buf())).unwrap();
+
+        assert_eq!(config_text, template);
+    }
+
+    #[test]
+    fn init_template_falls_back_to_built_in_default() {
+        let missing_path = PathBuf::from("/does/not/exist/template.yaml");
+
+        let config_text = read_init_template(None, Some(missing_path.clone())).unwrap();
+        assert_eq!(config_text, DEFAULT_CONFIG_FILE);
+
+        // Explicitly provided templates must exist
+        let result = read_init_template(Some(missing_path), None);
+        assert!(matches!(result, Err(ConfigError::ReadFailed(_))));
+    }
+
+    #[test]
+    fn init_with_writes_built_in_template() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let file_name = init_with(temp_dir.path(), "node").unwrap();
+
+        let config_text = fs::read_to_string(temp_dir.path().join(file_name)).unwrap();
+        let config = parse_config(&config_text, Platform::Linux).unwrap();
+        let command_names: Vec<&String> = config.commands.keys().collect();
+        assert_eq!(command_names, ["install", "build", "test"]);
+    }
+
+    #[test]
+    fn init_with_fails_for_existing_config_or_unknown_template() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("dingus.yml"), "commands: {}").unwrap();
+
+        let existing_result = init_with(temp_dir.path(), "node");
+        let unknown_result = init_with(temp_dir.path(), "cobol");
+
+        assert!(matches!(
+            existing_result,
+            Err(ConfigError::AlreadyExists { file_name }) if file_name == "dingus.yml"
+        ));
+        assert!(matches!(
+            unknown_result,
+            Err(ConfigError::UnknownInitTemplate { name, .. }) if name == "cobol"
+        ));
+    }
+
+    #[test]
+    fn find_config_file_walks_up_to_parent_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let nested_directory = temp_dir.path().join("src").join("components");
+        fs::create_dir_all(&nested_directory).unwrap();
+        fs::write(temp_dir.path().join("Dingus.yml"), "commands: {}").unwrap();
+
+        let config_file_path = find_config_file(&nested_directory).unwrap();
+
+        let source = Source::File(config_file_path);
+        assert_eq!(
+            source.display(),
+            temp_dir.path().join("Dingus.yml").display().to_string()
+        );
+        assert!(find_config_file(&TempDir::new().unwrap().path().join("src")).is_none());
+    }
+
+    #[test]
+    fn overlay_config_text_deep_merges_maps_and_replaces_everything_else() {
+        let config_text = "description: Base
+options:
+    print_commands: false
+    auto_args: true
+variables:
+    environment: development
+    region:
+        value: us-east-1
+        env: AWS_REGION
+commands:
+    deploy:
+        action: ./deploy.sh
+        depends_on:
+            - build
+";
+        let local_config_text = "options:
+    print_commands: true
+variables:
+    region:
+        value: ap-southeast-2
+commands:
+    deploy:
+        depends_on: []
+";
+
+        let overlaid_text = overlay_config_text(config_text, local_config_text).unwrap();
+        let config = parse_config(&overlaid_text, Platform::Linux).unwrap();
+
+        assert_eq!(config.description, Some("Base".to_string()));
+        assert!(config.options.print_commands);
+        assert!(config.options.auto_args);
+        assert_eq!(
+            config.variables.get("region"),
+            Some(&VariableConfig::Literal(LiteralVariableConfig {
+                argument: None,
+                environment_variable_name: Some("AWS_REGION".to_string()),
+                value: "ap-southeast-2".to_string(),
+                prompt_if_missing: None,
+                transform: vec![],
+                export: true,
+                sensitive: false,
+            }))
+        );
+        let deploy_command = config.commands.get("deploy").unwrap();
+        assert!(deploy_command.action.is_some());
+        assert!(deploy_command.depends_on.is_empty());
+        assert_eq!(
+            overlay_config_text(config_text, "").unwrap(),
+            overlay_config_text(config_text, "{}").unwrap()
+        );
+    }
+
+    #[test]
+    fn local_config_path_inserts_local_before_extension() {
+        assert_eq!(
+            local_config_path(Path::new("/project/dingus.yaml")),
+            PathBuf::from("/project/dingus.local.yaml")
+        );
+        assert_eq!(
+            local_config_path(Path::new("/project/Dingus.yml")),
+            PathBuf::from("/project/Dingus.local.yml")
+        );
+    }
+
+    #[test]
+    fn read_config_text_reads_input_unless_it_is_a_terminal() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("dingus.yaml"), "description: file").unwrap();
+
+        let (input_source, input_text) =
+            read_config_text(&mut "description: stdin".as_bytes(), false, temp_dir.path()).unwrap();
+        let (file_source, file_text) =
+            read_config_text(&mut "description: stdin".as_bytes(), true, temp_dir.path()).unwrap();
+
+        assert!(matches!(input_source, Source::Stdin));
+        assert_eq!(input_text, "description: stdin");
+        assert!(matches!(file_source, Source::File(_)));
+        assert_eq!(file_text, "description: file");
+    }
+
+    #[test]
+    fn built_in_templates_parse() {
+        for (name, config_text) in BUILT_IN_TEMPLATES {
+            let result = parse_config(&config_text.to_string(), Platform::Linux);
+            assert!(result.is_ok(), "{name} template failed to parse");
+        }
+    }
+
+    #[test]
+    fn validate_passes_for_valid_config() {
+        let yaml = "variables:
+    name:
+        value: Dingus
+        arg:
+            position: 1
+commands:
+    greet:
+        variables:
+            greeting:
+                value: Hello
+                arg:
+                    position: 2
+        action: echo \"$greeting, $name!\"
+    build:
+        commands:
+            docs:
+                action: echo docs";
+        let config = parse_config(&yaml.to_string(), Platform::Linux).unwrap();
+
+        assert!(validate(&config).is_ok());
+    }
+
+    #[test]
+    fn validate_fails_for_empty_command() {
+        let yaml = "commands:
+    build:
+        commands:
+            docs:
+                description: Does nothing";
+        let config = parse_config(&yaml.to_string(), Platform::Linux).unwrap();
+
+        let result = validate(&config);
+
+        assert!(matches!(
+            result,
+            Err(ConfigError::EmptyCommand { command }) if command == "build.docs"
+        ));
+    }
+
+    #[test]
+    fn validate_fails_for_duplicate_positions() {
+        let yaml = "variables:
+    name:
+        value: Dingus
+        arg:
+            position: 1
+commands:
+    greet:
+        variables:
+            greeting:
+                value: Hello
+                arg:
+                    position: 1
+        action: echo \"$greeting, $name!\"";
+        let config = parse_config(&yaml.to_string(), Platform::Linux).unwrap();
+
+        let result = validate(&config);
+
+        assert!(matches!(
+            result,
+            Err(ConfigError::DuplicatePosition { command, position, first, second })
+                if command == "greet" && position == 1 && first == "name" && second == "greeting"
+        ));
+    }
+
+    #[test]
+    fn validate_fails_for_unknown_conflicts() {
+        let yaml = "commands:
+    deploy:
+        variables:
+            version:
+                arg:
+                    long: version
+                    conflicts_with: [brnach]
+            branch:
+                arg: branch
+        action: ./deploy.sh";
+        let config = parse_config(&yaml.to_string(), Platform::Linux).unwrap();
+
+        let result = validate(&config);
+
+        assert!(matches!(
+            result,
+            Err(ConfigError::UnknownConflict { command, variable, conflict })
+                if command == "deploy" && variable == "version" && conflict == "brnach"
+        ));
+    }
+
+    #[test]
+    fn validate_fails_for_reserved_argument_names() {
+        let yaml = "variables:
+    from:
+        arg: from
+commands:
+    deploy:
+        variables:
+            revision:
+                arg:
+                    long: revision
+                    aliases: [since]
+        action: ./deploy.sh";
+        let config = parse_config(&yaml.to_string(), Platform::Linux).unwrap();
+
+        let result = validate(&config);
+
+        assert!(matches!(
+            result,
+            Err(ConfigError::ReservedArgument { variable, name })
+                if variable == "from" && name == "from"
+        ));
+
+        let yaml = "commands:
+    deploy:
+        variables:
+            from:
+                arg: from
+            revision:
+                arg:
+                    long: revision
+                    aliases: [since]
+        action: ./deploy.sh";
+        let config = parse_config(&yaml.to_string(), Platform::Linux).unwrap();
+
+        let result = validate(&config);
+
+        assert!(matches!(
+            result,
+            Err(ConfigError::ReservedArgument { variable, name })
+                if variable == "revision" && name == "since"
+        ));
+    }
+
+    #[test]
+    fn validate_fails_for_templates_in_shared_shell() {
+        let yaml = "commands:
+    publish:
+        shared_shell: true
+        actions:
+            - cd ./dist
+            - command: npm publish --tag {{ tag }}
+              template: tera";
+        let config = parse_config(&yaml.to_string(), Platform::Linux).unwrap();
+
+        let result = validate(&config);
+
+        assert!(matches!(
+            result,
+            Err(ConfigError::UnsupportedInSharedShell { command, field })
+                if command == "publish" && field == "template"
+        ));
+    }
+
+    #[test]
+    fn validate_fails_for_unknown_requirements() {
+        let yaml = "commands:
+    deploy:
+        variables:
+            version:
+                arg:
+                    long: version
+                    requires: [trget]
+            target:
+                arg: target
+        action: ./deploy.sh";
+        let config = parse_config(&yaml.to_string(), Platform::Linux).unwrap();
+
+        let result = validate(&config);
+
+        assert!(matches!(
+            result,
+            Err(ConfigError::UnknownRequirement { command, variable, requirement })
+                if command == "deploy" && variable == "version" && requirement == "trget"
+        ));
+    }
+
+    #[test]
+    fn validate_fails_for_position_gaps() {
+        let yaml = "variables:
+    name:
+        value: Dingus
+        arg:
+            position: 1
+commands:
+    greet:
+        variables:
+            greeting:
+                value: Hello
+                arg:
+                    position: 3
+        action: echo \"$greeting, $name!\"";
+        let config = parse_config(&yaml.to_string(), Platform::Linux).unwrap();
+
+        let result = validate(&config);
+
+        assert!(matches!(
+            result,
+            Err(ConfigError::PositionGap { command, variable, position, expected_position })
+                if command == "greet" && variable == "greeting" && position == 3 && expected_position == 2
+        ));
+    }
+
+    #[test]
+    fn describe_import_shows_platform_decision_and_depth() {
+        let import = Import {
+            alias: "mac".to_string(),
+            source: "./mac.yaml".to_string(),
+            hidden: false,
+            group: None,
+            platform: Some(One(OnePlatform {
+                platform: Platform::MacOS.into(),
+            })),
+        };
+
+        let skipped = describe_import(&import, &Linux, true, 2);
+        let imported = describe_import(&import, &Platform::MacOS, false, 1);
+
+        assert_eq!(skipped, "    mac (./mac.yaml): skipped, not for Linux");
+        assert_eq!(imported, "  mac (./mac.yaml): imported, matches MacOS");
+    }
+
     fn create_temp_file(content: &str) -> NamedTempFile {
         let mut temp_file = NamedTempFile::new().unwrap();
         temp_file.write_all(content.as_bytes()).unwrap();
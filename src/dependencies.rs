@@ -0,0 +1,231 @@
+use crate::cli::find_command_by_name;
+use crate::config::{ActionConfig, CommandConfig, CommandConfigMap};
+use thiserror::Error;
+
+/// The separator between each level of a command path.
+pub const PATH_SEPARATOR: char = '.';
+
+/// Resolves the commands that the provided [`CommandConfig`] depends on, in the order that they
+/// need to be executed.
+/// Each dependency is only included once, even if multiple commands depend on it.
+pub fn resolve_dependencies(
+    command_config: &CommandConfig,
+    commands: &CommandConfigMap,
+) -> Result<Vec<CommandConfig>, DependencyError> {
+    let mut visiting: Vec<String> = vec![];
+    let mut resolved_paths: Vec<String> = vec![];
+    let mut resolved_commands: Vec<CommandConfig> = vec![];
+
+    for path in &command_config.depends_on {
+        visit(
+            path,
+            commands,
+            &mut visiting,
+            &mut resolved_paths,
+            &mut resolved_commands,
+        )?;
+    }
+
+    Ok(resolved_commands)
+}
+
+fn visit(
+    path: &String,
+    commands: &CommandConfigMap,
+    visiting: &mut Vec<String>,
+    resolved_paths: &mut Vec<String>,
+    resolved_commands: &mut Vec<CommandConfig>,
+) -> Result<(), DependencyError> {
+    if resolved_paths.contains(path) {
+        return Ok(());
+    }
+
+    // If we're already visiting this path further up the chain, then we've gone in a circle.
+    if visiting.contains(path) {
+        let mut chain = visiting.clone();
+        chain.push(path.clone());
+        return Err(DependencyError::Cycle {
+            chain: chain.join(" -> "),
+        });
+    }
+
+    let command_config = find_command_by_path(path, commands)
+        .ok_or_else(|| DependencyError::NotFound { path: path.clone() })?;
+
+    // Depending on an alias for another command depends on that command instead
+    if let Some(ActionConfig::CommandAlias(command_alias)) = &command_config.action {
+        visiting.push(path.clone());
+        visit(
+            &command_alias.alias.command,
+            commands,
+            visiting,
+            resolved_paths,
+            resolved_commands,
+        )?;
+        visiting.pop();
+
+        resolved_paths.push(path.clone());
+        return Ok(());
+    }
+
+    visiting.push(path.clone());
+    for dependency_path in &command_config.depends_on {
+        visit(
+            dependency_path,
+            commands,
+            visiting,
+            resolved_paths,
+            resolved_commands,
+        )?;
+    }
+    visiting.pop();
+
+    resolved_paths.push(path.clone());
+    resolved_commands.push(command_config);
+
+    Ok(())
+}
+
+/// Finds the [`CommandConfig`] at the provided `.`-separated path, starting from `commands`.
+fn find_command_by_path(path: &str, commands: &CommandConfigMap) -> Option<CommandConfig> {
+    let mut segments = path.split(PATH_SEPARATOR);
+
+    let first_segment = segments.next()?;
+    let mut command_config = find_command_by_name(&first_segment.to_string(), commands)?;
+    for segment in segments {
+        command_config = find_command_by_name(&segment.to_string(), &command_config.commands)?;
+    }
+
+    Some(command_config)
+}
+
+#[derive(Error, Debug)]
+pub enum DependencyError {
+    #[error("could not find dependency \"{path}\"")]
+    NotFound { path: String },
+
+    #[error("circular dependency detected: {chain}")]
+    Cycle { chain: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RawCommandConfigVariant::Shorthand;
+    use crate::config::{
+        ActionConfig, CommandAliasActionConfig, CommandAliasConfig, ExecutionConfigVariant,
+        SingleActionConfig,
+    };
+
+    fn command(action: &str, depends_on: Vec<&str>) -> CommandConfig {
+        CommandConfig {
+            depends_on: depends_on.iter().map(|path| path.to_string()).collect(),
+            action: Some(ActionConfig::SingleStep(SingleActionConfig {
+                action: ExecutionConfigVariant::RawCommand(Shorthand(action.to_string())),
+            })),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn resolve_dependencies_orders_dependencies() {
+        // Arrange
+        let mut docker_commands = CommandConfigMap::new();
+        docker_commands.insert(
+            "build".to_string(),
+            command("docker build .", vec!["compile"]),
+        );
+
+        let mut commands = CommandConfigMap::new();
+        commands.insert("clean".to_string(), command("rm -rf build", vec![]));
+        commands.insert("compile".to_string(), command("cargo build", vec!["clean"]));
+        commands.insert(
+            "docker".to_string(),
+            CommandConfig {
+                commands: docker_commands,
+                ..Default::default()
+            },
+        );
+
+        let deploy_command = command("./deploy.sh", vec!["docker.build", "compile"]);
+
+        // Act
+        let dependencies = resolve_dependencies(&deploy_command, &commands).unwrap();
+
+        // Assert
+        let actions: Vec<ActionConfig> = dependencies
+            .iter()
+            .map(|dependency| dependency.action.clone().unwrap())
+            .collect();
+        assert_eq!(
+            actions,
+            vec![
+                command("rm -rf build", vec![]).action.unwrap(),
+                command("cargo build", vec![]).action.unwrap(),
+                command("docker build .", vec![]).action.unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn resolve_dependencies_detects_cycles() {
+        // Arrange
+        let mut commands = CommandConfigMap::new();
+        commands.insert("a".to_string(), command("echo a", vec!["b"]));
+        commands.insert("b".to_string(), command("echo b", vec!["c"]));
+        commands.insert("c".to_string(), command("echo c", vec!["a"]));
+
+        let target_command = command("echo target", vec!["a"]);
+
+        // Act
+        let result = resolve_dependencies(&target_command, &commands);
+
+        // Assert
+        assert!(matches!(
+            result,
+            Err(DependencyError::Cycle { chain }) if chain == "a -> b -> c -> a"
+        ));
+    }
+
+    #[test]
+    fn resolve_dependencies_follows_command_aliases() {
+        // Arrange
+        let mut commands = CommandConfigMap::new();
+        commands.insert("build".to_string(), command("cargo build", vec![]));
+        commands.insert(
+            "b".to_string(),
+            CommandConfig {
+                action: Some(ActionConfig::CommandAlias(CommandAliasActionConfig {
+                    alias: CommandAliasConfig {
+                        command: "build".to_string(),
+                    },
+                })),
+                ..Default::default()
+            },
+        );
+
+        let target_command = command("./deploy.sh", vec!["b", "build"]);
+
+        // Act
+        let dependencies = resolve_dependencies(&target_command, &commands).unwrap();
+
+        // Assert
+        assert_eq!(dependencies, vec![command("cargo build", vec![])]);
+    }
+
+    #[test]
+    fn resolve_dependencies_fails_for_unknown_command() {
+        // Arrange
+        let commands = CommandConfigMap::new();
+        let target_command = command("echo target", vec!["missing"]);
+
+        // Act
+        let result = resolve_dependencies(&target_command, &commands);
+
+        // Assert
+        assert!(matches!(
+            result,
+            Err(DependencyError::NotFound { path }) if path == "missing"
+        ));
+    }
+}
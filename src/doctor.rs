@@ -0,0 +1,130 @@
+use crate::config::{ConfigError, Source};
+use colored::control::SHOULD_COLORIZE;
+use std::env;
+use std::error::Error;
+use std::ffi::OsString;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+
+pub const DOCTOR_COMMAND_NAME: &str = "doctor";
+
+/// The shells that commands may be executed with, other than bash which comes from the options.
+const OTHER_SHELLS: [&str; 2] = ["zsh", "sh"];
+
+/// Whether `doctor` is in the raw command-line `args`, ignoring anything after `--`.
+/// This is only used when the config can't be loaded, since the arguments can't be parsed
+/// without it.
+pub fn is_doctor_requested(args: &[String]) -> bool {
+    args.iter()
+        .skip(1)
+        .take_while(|arg| arg.as_str() != "--")
+        .any(|arg| arg == DOCTOR_COMMAND_NAME)
+}
+
+/// Describes the terminal and environment dingus is running in, along with where the config was
+/// loaded from, or why it couldn't be loaded.
+/// `bash_path` is the bash binary commands would be executed with, and `search_path` is the
+/// `PATH` used to find it and the other shells.
+pub fn doctor_report(
+    config_source: Result<&Source, &ConfigError>,
+    bash_path: &str,
+    search_path: Option<OsString>,
+) -> Vec<String> {
+    let terminal = |is_terminal: bool| {
+        if is_terminal {
+            "terminal"
+        } else {
+            "not a terminal"
+        }
+    };
+
+    let config = match config_source {
+        Ok(source) => source.display(),
+        Err(err) => match err.source() {
+            Some(source) => format!("error: {err}: {source}"),
+            None => format!("error: {err}"),
+        },
+    };
+
+    let mut lines = vec![
+        format!("stdin: {}", terminal(io::stdin().is_terminal())),
+        format!("stdout: {}", terminal(io::stdout().is_terminal())),
+        format!("stderr: {}", terminal(io::stderr().is_terminal())),
+        format!(
+            "colors: {}",
+            if SHOULD_COLORIZE.should_colorize() {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        ),
+        format!(
+            "TERM: {}",
+            env::var("TERM").unwrap_or_else(|_| "not set".to_string())
+        ),
+        format!("config: {config}"),
+        format!("platform: {} ({})", env::consts::OS, env::consts::ARCH),
+        "shells:".to_string(),
+    ];
+
+    for shell in [bash_path].into_iter().chain(OTHER_SHELLS) {
+        let location = match find_program(shell, search_path.clone()) {
+            Some(path) => path.display().to_string(),
+            None => "not found".to_string(),
+        };
+        lines.push(format!("  {shell}: {location}"));
+    }
+
+    lines
+}
+
+/// Finds the `program` in the directories of the `search_path`, like a shell would.
+/// Programs given as a path are only checked for existence.
+fn find_program(program: &str, search_path: Option<OsString>) -> Option<PathBuf> {
+    if program.contains(std::path::MAIN_SEPARATOR) {
+        return Path::new(program).is_file().then(|| PathBuf::from(program));
+    }
+
+    env::split_paths(&search_path?)
+        .map(|directory| directory.join(program))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn is_doctor_requested_ignores_args_after_separator() {
+        // Arrange
+        let args =
+            |args: &[&str]| -> Vec<String> { args.iter().map(|arg| arg.to_string()).collect() };
+
+        // Act
+        let requested = is_doctor_requested(&args(&["dingus", "--color", "never", "doctor"]));
+        let after_separator = is_doctor_requested(&args(&["dingus", "run", "--", "doctor"]));
+
+        // Assert
+        assert!(requested);
+        assert!(!after_separator);
+    }
+
+    #[test]
+    fn doctor_report_lists_config_error_and_shells() {
+        // Arrange
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("zsh"), "").unwrap();
+        let search_path = env::join_paths([temp_dir.path()]).unwrap();
+
+        // Act
+        let lines = doctor_report(Err(&ConfigError::FileNotFound), "bash", Some(search_path));
+
+        // Assert
+        assert!(lines.contains(&format!("config: error: {}", ConfigError::FileNotFound)));
+        assert!(lines.contains(&"  bash: not found".to_string()));
+        assert!(lines.contains(&format!("  zsh: {}", temp_dir.path().join("zsh").display())));
+        assert!(lines.contains(&"  sh: not found".to_string()));
+    }
+}
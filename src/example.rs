@@ -0,0 +1,211 @@
+/// An annotated example config which demonstrates every kind of variable and action.
+pub const EXAMPLE_CONFIG: &str =
+    "# An example dingus.yaml demonstrating every kind of variable and action.
+description: An example Dingus file
+
+options:
+  # Prints each command before it's executed.
+  print_commands: false
+
+  # Creates a command-line argument for every variable.
+  auto_args: false
+
+# Variables defined here are available to every command.
+variables:
+  # Shorthand literal variables are hard-coded to a value.
+  name: Dingus
+
+  # Literal variables can also have a command-line argument and a custom environment variable name.
+  greeting:
+    value: Hello
+    argument:
+      long: greeting
+      short: g
+      description: The greeting to use
+    environment_variable: GREETING
+
+  # Execution variables use the output of a command.
+  branch:
+    execute: git branch --show-current
+    trim: end
+
+  # Prompt variables ask for a value when the command runs.
+  environment:
+    prompt:
+      message: Which environment?
+      options:
+        - Development
+        - Production
+
+  # Random variables generate a new UUID or hex string every time a command runs.
+  suffix:
+    random:
+      kind: hex
+      length: 8
+
+  # File variables use the content of a file.
+  release_notes:
+    file: ./CHANGELOG.md
+
+  # Argument variables can only be set from the command line.
+  replicas:
+    argument: replicas
+
+commands:
+  # A single action is executed without a shell unless a shell is used explicitly.
+  greet:
+    description: Greet someone
+    action: echo \"$greeting, $name!\"
+
+  # Multiple actions are executed in order.
+  deploy:
+    description: Deploy to an environment
+    variables:
+      version:
+        execute:
+          bash: git describe --tags
+    actions:
+      - echo \"Deploying $version to $environment\"
+      - bash: ./deploy.sh \"$environment\"
+
+  # Aliases pass any extra arguments through to another command.
+  compose:
+    description: Run docker compose
+    alias: docker compose
+
+  # Aliases can also point to another command, which receives any extra arguments.
+  migrate:
+    description: Run the database migrations
+    alias:
+      command: db.migrate
+
+  # Files can be written with variables substituted into the path and content.
+  configure:
+    description: Write the config file
+    write_file: ./config.json
+    content: |
+      { \"environment\": \"$environment\" }
+
+  # URLs and files can be opened with their default application.
+  dashboard:
+    description: Open the dashboard
+    open: https://example.com/dashboards/$environment
+
+  # Assertions fail with a message when a check doesn't pass.
+  check-token:
+    description: Check that a token is set
+    assert:
+      command: test -n \"$TOKEN\"
+      message: TOKEN must be set
+
+  # Each platform can have its own action, falling back to the regular one.
+  open:
+    description: Open the docs
+    platform_actions:
+      MacOS:
+        action: open ./docs/index.html
+      Windows:
+        action: start ./docs/index.html
+    action: xdg-open ./docs/index.html
+
+  # Commands can also be grouped under a parent command.
+  db:
+    description: Database commands
+    commands:
+      migrate:
+        action: ./migrate.sh
+      seed:
+        depends_on:
+          - db.migrate
+        action: ./seed.sh
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{parse_config, ActionConfig, CommandConfigMap, Platform, VariableConfig};
+
+    // These match every variant so that new kinds of variables and actions aren't forgotten
+    fn variable_kind(variable_config: &VariableConfig) -> &'static str {
+        match variable_config {
+            VariableConfig::ShorthandLiteral(_) => "shorthand literal",
+            VariableConfig::Literal(_) => "literal",
+            VariableConfig::Execution(_) => "execution",
+            VariableConfig::Prompt(_) => "prompt",
+            VariableConfig::Random(_) => "random",
+            VariableConfig::File(_) => "file",
+            VariableConfig::Argument(_) => "argument",
+        }
+    }
+
+    fn action_kind(action_config: &ActionConfig) -> &'static str {
+        match action_config {
+            ActionConfig::SingleStep(_) => "single step",
+            ActionConfig::MultiStep(_) => "multi step",
+            ActionConfig::Alias(_) => "alias",
+            ActionConfig::CommandAlias(_) => "command alias",
+            ActionConfig::WriteFile(_) => "write file",
+            ActionConfig::Open(_) => "open",
+            ActionConfig::Assert(_) => "assert",
+        }
+    }
+
+    fn action_kinds(commands: &CommandConfigMap) -> Vec<&'static str> {
+        commands
+            .values()
+            .flat_map(|command_config| {
+                let mut kinds: Vec<&'static str> = command_config
+                    .action
+                    .iter()
+                    .chain(command_config.platform_actions.values())
+                    .map(action_kind)
+                    .collect();
+                kinds.extend(action_kinds(&command_config.commands));
+                kinds
+            })
+            .collect()
+    }
+
+    #[test]
+    fn example_config_parses() {
+        // Act
+        let config = parse_config(&EXAMPLE_CONFIG.to_string(), Platform::Linux).unwrap();
+
+        // Assert
+        let variable_kinds: Vec<&'static str> = config
+            .variables
+            .values()
+            .chain(
+                config
+                    .commands
+                    .values()
+                    .flat_map(|command_config| command_config.variables.values()),
+            )
+            .map(variable_kind)
+            .collect();
+        for kind in [
+            "shorthand literal",
+            "literal",
+            "execution",
+            "prompt",
+            "random",
+            "file",
+            "argument",
+        ] {
+            assert!(variable_kinds.contains(&kind), "missing {kind} variable");
+        }
+
+        let action_kinds = action_kinds(&config.commands);
+        for kind in [
+            "single step",
+            "multi step",
+            "alias",
+            "command alias",
+            "write file",
+            "open",
+            "assert",
+        ] {
+            assert!(action_kinds.contains(&kind), "missing {kind} action");
+        }
+    }
+}
@@ -1,20 +1,36 @@
 use colored::Colorize;
 use mockall::automock;
+use std::env::JoinPathsError;
+use std::ffi::OsString;
 use std::fmt::Formatter;
-use std::process::Command;
-use std::{fmt, io};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::string::FromUtf8Error;
+use std::time::{Duration, Instant};
+use std::{env, fmt, fs, io, thread};
+use tempfile::{NamedTempFile, TempPath};
 use thiserror::Error;
 
 use crate::config::{
-    DingusOptions, ExecutionConfigVariant, RawCommandConfigVariant, ShellCommandConfigVariant,
+    DingusOptions, Encoding, ExecutionConfigVariant, RawCommandConfigVariant, Shell,
+    ShellCommandConfigVariant, StdinConfig, TemplateEngine,
 };
 use crate::exec::ExitStatus::Unknown;
 use crate::variables;
-use crate::variables::VariableMap;
+use crate::variables::{TemplateError, VariableMap, REDACTED_VALUE};
+use crate::workdir::expand_tilde;
 
 pub type ExecutionResult = Result<ExitStatus, ExecutionError>;
 pub type ExecutionOutputResult = Result<Output, ExecutionError>;
 
+/// The start of a bash command which should be executed with its own interpreter.
+const SHEBANG: &str = "#!";
+
+/// How often a command with a deadline is checked on.
+const DEADLINE_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
 #[derive(PartialEq, Debug, Clone)]
 pub enum ExitStatus {
     Success,
@@ -59,6 +75,14 @@ impl Output {
             stderr: output.stderr.clone(),
         }
     }
+
+    /// Decodes stdout into a [`String`] using the provided [`Encoding`].
+    pub fn stdout_string(&self, encoding: &Encoding) -> Result<String, FromUtf8Error> {
+        match encoding {
+            Encoding::Strict => String::from_utf8(self.stdout.clone()),
+            Encoding::Lossy => Ok(String::from_utf8_lossy(&self.stdout).into_owned()),
+        }
+    }
 }
 
 // TODO: Consider refactoring these to take stdio as args so we can test with stdin.
@@ -83,14 +107,80 @@ pub trait CommandExecutor {
     ) -> ExecutionOutputResult;
 }
 
-pub fn create_command_executor(options: &DingusOptions) -> Box<dyn CommandExecutor> {
+/// Creates a [`CommandExecutor`] like [`create_command_executor_with_search_path`], without adding
+/// anything to the `PATH`.
+#[cfg(test)]
+pub fn create_command_executor(
+    options: &DingusOptions,
+    default_shell: &Option<Shell>,
+    unexported_variable_names: &[String],
+) -> Box<dyn CommandExecutor> {
+    create_command_executor_with_search_path(
+        options,
+        default_shell,
+        unexported_variable_names,
+        &SearchPath::default(),
+    )
+}
+
+/// Creates a [`CommandExecutor`] which executes shorthand commands with the `default_shell`, if
+/// provided.
+/// The variables named in `unexported_variable_names` aren't passed to commands as environment
+/// variables, but they can still be substituted into raw commands, and shells can still use them
+/// as unexported shell variables.
+/// The directories in `search_path` are added to the `PATH` of every command.
+pub fn create_command_executor_with_search_path(
+    options: &DingusOptions,
+    default_shell: &Option<Shell>,
+    unexported_variable_names: &[String],
+    search_path: &SearchPath,
+) -> Box<dyn CommandExecutor> {
+    create_command_executor_with_deadline(
+        options,
+        default_shell,
+        unexported_variable_names,
+        search_path,
+        None,
+    )
+}
+
+/// Creates a [`CommandExecutor`] like [`create_command_executor_with_search_path`], which stops
+/// any command it executes once the `deadline` has passed.
+/// Commands are only stopped by [`CommandExecutor::execute`], collecting output isn't bounded.
+pub fn create_command_executor_with_deadline(
+    options: &DingusOptions,
+    default_shell: &Option<Shell>,
+    unexported_variable_names: &[String],
+    search_path: &SearchPath,
+    deadline: Option<Instant>,
+) -> Box<dyn CommandExecutor> {
     Box::new(CommandExecutorImpl {
         options: options.clone(),
+        default_shell: default_shell.clone(),
+        unexported_variable_names: unexported_variable_names.to_vec(),
+        search_path: search_path.clone(),
+        deadline,
     })
 }
 
+/// Directories to add to the `PATH` of executed commands.
+/// Variables are substituted into each directory, and relative directories are relative to the
+/// current directory.
+#[derive(Clone, Default)]
+pub struct SearchPath {
+    /// Directories searched before the inherited `PATH`, in order.
+    pub prepend: Vec<String>,
+
+    /// Directories searched after the inherited `PATH`, in order.
+    pub append: Vec<String>,
+}
+
 struct CommandExecutorImpl {
     options: DingusOptions,
+    default_shell: Option<Shell>,
+    unexported_variable_names: Vec<String>,
+    search_path: SearchPath,
+    deadline: Option<Instant>,
 }
 
 impl CommandExecutor for CommandExecutorImpl {
@@ -99,15 +189,59 @@ impl CommandExecutor for CommandExecutorImpl {
         execution_config: &ExecutionConfigVariant,
         variables: &VariableMap,
     ) -> ExecutionResult {
-        let mut command = get_command_for(execution_config, variables);
+        let (mut command, _script_path) = get_command_for(
+            execution_config,
+            variables,
+            &self.options,
+            &self.default_shell,
+            &self.unexported_variable_names,
+        )?;
+        if !runs_in_shell(execution_config, &self.default_shell) {
+            self.remove_unexported_variables(&mut command);
+        }
+        self.apply_search_path(&mut command, variables)?;
+
+        // Output is only redirected when it isn't being collected, otherwise the files would be
+        // overwritten without anything being written to them
+        redirect_output(&mut command, execution_config, variables).map_err(ExecutionError::IO)?;
 
         self.log(&command);
 
-        let exit_status = command
-            .spawn()
-            .map_err(|io_err| ExecutionError::IO(io_err))?
-            .wait()
-            .map_err(|io_err| ExecutionError::IO(io_err))?;
+        // Steps after the one that timed out shouldn't be started at all
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(ExecutionError::TimedOut);
+        }
+
+        let prefix = output_prefix(execution_config, variables);
+        if prefix.is_some() {
+            pipe_unredirected_output(&mut command, execution_config);
+        }
+
+        // The step is put in its own process group so that anything it starts can be stopped
+        // along with it once the deadline has passed
+        #[cfg(unix)]
+        if self.deadline.is_some() {
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
+
+        let mut child = command.spawn().map_err(ExecutionError::IO)?;
+        write_stdin(&mut child, execution_config, variables);
+        let output_printers = prefix
+            .map(|prefix| print_prefixed_output(&mut child, &prefix))
+            .unwrap_or_default();
+
+        let exit_status = match self.deadline {
+            Some(deadline) => wait_until(&mut child, deadline)?,
+            None => child.wait().map_err(ExecutionError::IO)?,
+        };
+
+        for output_printer in output_printers {
+            let _ = output_printer.join();
+        }
 
         Ok(ExitStatus::from_std_exitstatus(&exit_status))
     }
@@ -117,19 +251,83 @@ impl CommandExecutor for CommandExecutorImpl {
         execution_config: &ExecutionConfigVariant,
         variables: &VariableMap,
     ) -> ExecutionOutputResult {
-        let mut command = get_command_for(execution_config, variables);
+        let (mut command, _script_path) = get_command_for(
+            execution_config,
+            variables,
+            &self.options,
+            &self.default_shell,
+            &self.unexported_variable_names,
+        )?;
+        if !runs_in_shell(execution_config, &self.default_shell) {
+            self.remove_unexported_variables(&mut command);
+        }
+        self.apply_search_path(&mut command, variables)?;
+
+        // Output is only collected from commands which aren't interactive, so stdin is never
+        // inherited
+        if stdin_config(execution_config) == &StdinConfig::Inherit {
+            command.stdin(Stdio::null());
+        }
 
         self.log(&command);
 
-        let output = command
-            .output()
-            .map_err(|io_err| ExecutionError::IO(io_err))?;
+        let mut child = command
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(ExecutionError::IO)?;
+        write_stdin(&mut child, execution_config, variables);
+
+        let max_output_bytes = max_output_bytes(execution_config).or(self.options.max_output_bytes);
+        let output = match max_output_bytes {
+            Some(max_output_bytes) => output_with_limit(child, max_output_bytes)?,
+            None => child.wait_with_output().map_err(ExecutionError::IO)?,
+        };
 
         Ok(Output::from_std_output(&output))
     }
 }
 
 impl CommandExecutorImpl {
+    /// Stops the unexported variables from being passed to the command, while keeping any
+    /// environment variable of the same name that dingus inherited.
+    fn remove_unexported_variables(&self, command: &mut Command) {
+        for name in &self.unexported_variable_names {
+            command.env_remove(name);
+            if let Some(value) = env::var_os(name) {
+                command.env(name, value);
+            }
+        }
+    }
+
+    /// Sets the `PATH` of the command to include the directories in the [`SearchPath`].
+    fn apply_search_path(
+        &self,
+        command: &mut Command,
+        variables: &VariableMap,
+    ) -> Result<(), ExecutionError> {
+        if self.search_path.prepend.is_empty() && self.search_path.append.is_empty() {
+            return Ok(());
+        }
+
+        // A PATH variable takes the place of the inherited one, just like it would without this
+        let inherited_path = match variables.get("PATH") {
+            Some(path) => Some(OsString::from(path)),
+            None => env::var_os("PATH"),
+        };
+
+        let path = search_path_value(
+            &self.search_path,
+            variables,
+            inherited_path,
+            &env::current_dir().map_err(ExecutionError::IO)?,
+        )
+        .map_err(ExecutionError::Path)?;
+        command.env("PATH", path);
+
+        Ok(())
+    }
+
     fn log(&self, command: &Command) {
         if self.options.print_commands {
             let command_text = get_command_text(&command);
@@ -138,35 +336,292 @@ impl CommandExecutorImpl {
     }
 }
 
-fn get_command_for(execution_config: &ExecutionConfigVariant, variables: &VariableMap) -> Command {
+/// Waits for the [`Child`] to exit like [`Child::wait`], killing it and its process group if it's
+/// still running once the `deadline` has passed.
+fn wait_until(
+    child: &mut Child,
+    deadline: Instant,
+) -> Result<std::process::ExitStatus, ExecutionError> {
+    loop {
+        if let Some(exit_status) = child.try_wait().map_err(ExecutionError::IO)? {
+            return Ok(exit_status);
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            kill_process_group(child).map_err(ExecutionError::IO)?;
+            child.wait().map_err(ExecutionError::IO)?;
+            return Err(ExecutionError::TimedOut);
+        }
+
+        thread::sleep((deadline - now).min(DEADLINE_POLL_INTERVAL));
+    }
+}
+
+/// Kills the [`Child`] along with any processes it started.
+/// Falls back to killing only the child where it doesn't lead its own process group.
+fn kill_process_group(child: &mut Child) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        // SAFETY: kill only sends a signal, a negative pid targets the child's process group
+        let result = unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) };
+        if result == 0 {
+            return Ok(());
+        }
+    }
+
+    child.kill()
+}
+
+/// Joins the directories in `search_path` with `inherited_path` into a new `PATH` value.
+fn search_path_value(
+    search_path: &SearchPath,
+    variables: &VariableMap,
+    inherited_path: Option<OsString>,
+    current_dir: &Path,
+) -> Result<OsString, JoinPathsError> {
+    let to_path = |directory: &String| -> PathBuf {
+        current_dir.join(expand_tilde(&variables::substitute_variables(
+            directory, variables,
+        )))
+    };
+
+    let inherited_paths: Vec<PathBuf> = inherited_path
+        .map(|inherited_path| env::split_paths(&inherited_path).collect())
+        .unwrap_or_default();
+
+    env::join_paths(
+        search_path
+            .prepend
+            .iter()
+            .map(to_path)
+            .chain(inherited_paths)
+            .chain(search_path.append.iter().map(to_path)),
+    )
+}
+
+/// Collects the output of the [`Child`] like [`Child::wait_with_output`], but stops the command as
+/// soon as stdout or stderr exceeds `max_output_bytes` rather than buffering all of it.
+/// Both stdout and stderr must be piped.
+fn output_with_limit(
+    mut child: Child,
+    max_output_bytes: u64,
+) -> Result<std::process::Output, ExecutionError> {
+    // Safe to unwrap: both streams are piped by the caller
+    let stdout = child.stdout.take().unwrap();
+    let stderr = child.stderr.take().unwrap();
+
+    // stderr is read on another thread so the command can't get stuck writing to it while stdout
+    // is being read
+    let stderr_reader = thread::spawn(move || read_with_limit(stderr, max_output_bytes));
+    let stdout = read_with_limit(stdout, max_output_bytes);
+    if !matches!(stdout, Ok(Some(_))) {
+        let _ = child.kill();
+    }
+
+    let stderr = stderr_reader
+        .join()
+        .unwrap_or_else(|_| Err(io::Error::other("failed to read stderr")));
+    if !matches!(stderr, Ok(Some(_))) {
+        let _ = child.kill();
+    }
+
+    let status = child.wait().map_err(ExecutionError::IO)?;
+    match (
+        stdout.map_err(ExecutionError::IO)?,
+        stderr.map_err(ExecutionError::IO)?,
+    ) {
+        (Some(stdout), Some(stderr)) => Ok(std::process::Output {
+            status,
+            stdout,
+            stderr,
+        }),
+        _ => Err(ExecutionError::OutputTooLarge { max_output_bytes }),
+    }
+}
+
+/// Reads everything from `reader`, returning `None` if there's more than `max_bytes` to read.
+fn read_with_limit(reader: impl Read, max_bytes: u64) -> io::Result<Option<Vec<u8>>> {
+    let mut bytes = Vec::new();
+    reader.take(max_bytes + 1).read_to_end(&mut bytes)?;
+
+    if bytes.len() as u64 > max_bytes {
+        return Ok(None);
+    }
+
+    Ok(Some(bytes))
+}
+
+/// Creates the [`Command`] for the provided [`ExecutionConfigVariant`].
+/// Bash commands starting with a `#!` shebang are written to a script file which is passed to the
+/// shebang's interpreter. The path to this file is returned along
+/// with the command, and the file is deleted once the path is dropped, so it must be kept until
+/// the command has finished.
+fn get_command_for(
+    execution_config: &ExecutionConfigVariant,
+    variables: &VariableMap,
+    options: &DingusOptions,
+    default_shell: &Option<Shell>,
+    unexported_variable_names: &[String],
+) -> Result<(Command, Option<TempPath>), ExecutionError> {
+    let (mut command, script_path) = match (execution_config, default_shell) {
+        (
+            ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(command_text)),
+            Some(shell),
+        ) => {
+            let mut command = Command::new(shell_program(shell, options));
+            command.arg("-c").envs(variables).arg(format!(
+                "{}{command_text}",
+                unexport_script(unexported_variable_names, variables)
+            ));
+            (command, None)
+        }
+        _ => create_command(
+            execution_config,
+            variables,
+            options,
+            unexported_variable_names,
+        )?,
+    };
+    redirect_input(&mut command, execution_config, variables).map_err(ExecutionError::IO)?;
+    Ok((command, script_path))
+}
+
+/// Whether the provided [`ExecutionConfigVariant`] is executed by a shell, which can see variables
+/// that aren't exported to the programs it runs.
+fn runs_in_shell(execution_config: &ExecutionConfigVariant, default_shell: &Option<Shell>) -> bool {
+    match execution_config {
+        ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(bash_config)) => {
+            !is_shebang_script(&bash_config.command)
+        }
+        ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(_)) => {
+            default_shell.is_some()
+        }
+        ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::RawCommandConfig(_)) => false,
+    }
+}
+
+/// A shell script which turns the variables named in `unexported_variable_names` into shell
+/// variables, so that the script can use them without them being exported to the programs it
+/// runs.
+fn unexport_script(unexported_variable_names: &[String], variables: &VariableMap) -> String {
+    let mut script = String::new();
+    for name in unexported_variable_names {
+        if variables.contains_key(name) {
+            script.push_str(&format!(
+                "__dingus_value=${name}; unset {name}; {name}=$__dingus_value; unset __dingus_value\n"
+            ));
+        }
+    }
+
+    script
+}
+
+/// Whether the provided bash command is a script starting with a shebang, which is run by the
+/// shebang's interpreter instead of bash.
+pub fn is_shebang_script(command: &str) -> bool {
+    command.starts_with(SHEBANG)
+}
+
+/// Writes the provided `script` to a temporary file, returning its path.
+/// The file is only readable by the current user unless `readable_by_others` is set, which is
+/// needed when the script's interpreter is run as another user.
+fn write_script_file(script: &str, readable_by_others: bool) -> io::Result<TempPath> {
+    let mut script_file = NamedTempFile::new()?;
+    script_file.write_all(script.as_bytes())?;
+
+    #[cfg(unix)]
+    if readable_by_others {
+        script_file
+            .as_file()
+            .set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o644))?;
+    }
+
+    // The file has to be closed before it's read, otherwise it might not have been written yet
+    Ok(script_file.into_temp_path())
+}
+
+/// A [`Command`] for the interpreter in the shebang of the provided `script`, along with any
+/// arguments the shebang passes to it.
+/// The script file is passed to the interpreter rather than executed itself, so that it still runs
+/// where the temporary directory doesn't allow executing files.
+fn interpreter_command(script: &str) -> Command {
+    let shebang = script.lines().next().unwrap_or_default();
+    let mut parts = shebang.trim_start_matches(SHEBANG).split_whitespace();
+
+    let mut command = Command::new(parts.next().unwrap_or_default());
+    command.args(parts);
+    command
+}
+
+fn create_command(
+    execution_config: &ExecutionConfigVariant,
+    variables: &VariableMap,
+    options: &DingusOptions,
+    unexported_variable_names: &[String],
+) -> Result<(Command, Option<TempPath>), ExecutionError> {
     match execution_config {
         ExecutionConfigVariant::ShellCommand(shell_command_config) => match shell_command_config {
             ShellCommandConfigVariant::Bash(bash_command_config) => {
-                let mut binding = Command::new("bash");
-                binding
-                    .arg("-c")
-                    .envs(variables)
-                    .arg(bash_command_config.clone().command);
+                let (mut binding, script_path) = if is_shebang_script(&bash_command_config.command)
+                {
+                    let script_path = write_script_file(
+                        &bash_command_config.command,
+                        bash_command_config.run_as.is_some(),
+                    )
+                    .map_err(ExecutionError::IO)?;
+                    let mut binding = interpreter_command(&bash_command_config.command);
+                    binding
+                        .arg(&script_path)
+                        .args(&bash_command_config.extra_args);
+                    (binding, Some(script_path))
+                } else {
+                    let mut binding = Command::new(&options.bash_path);
+                    binding.arg("-c").arg(format!(
+                        "{}{}",
+                        unexport_script(unexported_variable_names, variables),
+                        bash_command_config.command
+                    ));
+                    (binding, None)
+                };
+                binding.envs(variables);
 
                 if let Some(wd) = bash_command_config.clone().working_directory {
-                    binding.current_dir(wd);
+                    binding.current_dir(expand_tilde(&variables::substitute_variables(
+                        &wd, variables,
+                    )));
                 }
 
-                binding
+                match &bash_command_config.run_as {
+                    Some(user) => Ok((run_as(&binding, user, variables), script_path)),
+                    None => Ok((binding, script_path)),
+                }
             }
         },
 
         ExecutionConfigVariant::RawCommand(raw_command_config) => {
-            let (command_template, working_directory) = match raw_command_config {
-                RawCommandConfigVariant::Shorthand(command) => (command.clone(), None),
-                RawCommandConfigVariant::RawCommandConfig(raw_command_config) => (
-                    raw_command_config.clone().command,
-                    raw_command_config.clone().working_directory,
-                ),
-            };
+            let (command_template, template_engine, working_directory, user, extra_args) =
+                match raw_command_config {
+                    RawCommandConfigVariant::Shorthand(command) => (
+                        command.clone(),
+                        TemplateEngine::default(),
+                        None,
+                        None,
+                        vec![],
+                    ),
+                    RawCommandConfigVariant::RawCommandConfig(raw_command_config) => (
+                        raw_command_config.clone().command,
+                        raw_command_config.clone().template,
+                        raw_command_config.clone().working_directory,
+                        raw_command_config.clone().run_as,
+                        raw_command_config.clone().extra_args,
+                    ),
+                };
 
             // Substitute any variables in the command invocation
-            let command = variables::substitute_variables(&command_template, variables);
+            let command =
+                variables::render_template(&command_template, &template_engine, variables)
+                    .map_err(ExecutionError::Template)?;
 
             const DELIMITER: &str = " ";
             let mut cmd = match command.split_once(DELIMITER) {
@@ -178,14 +633,289 @@ fn get_command_for(execution_config: &ExecutionConfigVariant, variables: &Variab
                 }
                 None => Command::new(command),
             };
+            cmd.args(extra_args);
 
             if let Some(wd) = working_directory {
-                cmd.current_dir(wd);
+                cmd.current_dir(expand_tilde(&variables::substitute_variables(
+                    &wd, variables,
+                )));
+            }
+
+            if let Some(user) = user {
+                return Ok((run_as(&cmd, &user, variables), None));
             }
 
-            return cmd;
+            return Ok((cmd, None));
+        }
+    }
+}
+
+/// The program to execute for the provided [`Shell`].
+fn shell_program(shell: &Shell, options: &DingusOptions) -> String {
+    match shell {
+        Shell::Bash => options.bash_path.clone(),
+        Shell::Zsh => "zsh".to_string(),
+        Shell::Sh => "sh".to_string(),
+    }
+}
+
+/// Formats the variables that are passed to commands as environment variables as `KEY=value`
+/// lines, sorted by key.
+/// The values of any variables in `sensitive_variable_names` are redacted.
+pub fn format_env(variables: &VariableMap, sensitive_variable_names: &[String]) -> Vec<String> {
+    let mut names: Vec<&String> = variables.keys().collect();
+    names.sort();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let value = if sensitive_variable_names.contains(name) {
+                REDACTED_VALUE
+            } else {
+                &variables[name]
+            };
+
+            format!("{name}={value}")
+        })
+        .collect()
+}
+
+/// The [`StdinConfig`] for the provided [`ExecutionConfigVariant`].
+/// Shorthand commands always inherit stdin.
+fn stdin_config(execution_config: &ExecutionConfigVariant) -> &StdinConfig {
+    match execution_config {
+        ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(bash_config)) => {
+            &bash_config.stdin
+        }
+        ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::RawCommandConfig(
+            raw_config,
+        )) => &raw_config.stdin,
+        ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(_)) => {
+            &StdinConfig::Inherit
+        }
+    }
+}
+
+/// Redirects the stdin of the provided [`Command`] as configured on the provided
+/// [`ExecutionConfigVariant`].
+/// Text is written once the command has been spawned, see [`write_stdin`].
+fn redirect_input(
+    command: &mut Command,
+    execution_config: &ExecutionConfigVariant,
+    variables: &VariableMap,
+) -> io::Result<()> {
+    match stdin_config(execution_config) {
+        StdinConfig::Inherit => {}
+        StdinConfig::Null => {
+            command.stdin(Stdio::null());
+        }
+        StdinConfig::Text(_) => {
+            command.stdin(Stdio::piped());
+        }
+        StdinConfig::File(path) => {
+            let path = variables::substitute_variables(path, variables);
+            command.stdin(File::open(expand_tilde(&path))?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes any text configured as stdin on the provided [`ExecutionConfigVariant`] to the spawned
+/// [`Child`], then closes its stdin.
+/// The text is written from another thread so that the command can't get stuck writing output
+/// that's only read once all of the text has been written.
+fn write_stdin(
+    child: &mut Child,
+    execution_config: &ExecutionConfigVariant,
+    variables: &VariableMap,
+) {
+    let StdinConfig::Text(text) = stdin_config(execution_config) else {
+        return;
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let text = variables::substitute_variables(text, variables);
+
+        // Commands are free to exit without reading all of their input
+        thread::spawn(move || stdin.write_all(text.as_bytes()));
+    }
+}
+
+/// The maximum number of bytes of output to capture from the provided [`ExecutionConfigVariant`],
+/// if it overrides the limit from the [`DingusOptions`].
+fn max_output_bytes(execution_config: &ExecutionConfigVariant) -> Option<u64> {
+    match execution_config {
+        ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(bash_config)) => {
+            bash_config.max_output_bytes
+        }
+        ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::RawCommandConfig(
+            raw_config,
+        )) => raw_config.max_output_bytes,
+        ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(_)) => None,
+    }
+}
+
+/// The `[label] ` to print before each line of output from the provided
+/// [`ExecutionConfigVariant`], if it has one.
+fn output_prefix(
+    execution_config: &ExecutionConfigVariant,
+    variables: &VariableMap,
+) -> Option<String> {
+    let prefix = match execution_config {
+        ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(bash_config)) => {
+            &bash_config.label
+        }
+        ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::RawCommandConfig(
+            raw_config,
+        )) => &raw_config.label,
+        ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(_)) => &None,
+    };
+
+    prefix
+        .as_ref()
+        .map(|prefix| format!("[{}] ", variables::substitute_variables(prefix, variables)))
+}
+
+/// Pipes any output of the provided [`Command`] that isn't redirected to a file, so that it can be
+/// prefixed.
+fn pipe_unredirected_output(command: &mut Command, execution_config: &ExecutionConfigVariant) {
+    let (stdout_file, stderr_file) = match execution_config {
+        ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(bash_config)) => {
+            (&bash_config.stdout_file, &bash_config.stderr_file)
         }
+        ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::RawCommandConfig(
+            raw_config,
+        )) => (&raw_config.stdout_file, &raw_config.stderr_file),
+        ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(_)) => (&None, &None),
+    };
+
+    if stdout_file.is_none() {
+        command.stdout(Stdio::piped());
     }
+
+    if stderr_file.is_none() {
+        command.stderr(Stdio::piped());
+    }
+}
+
+/// Prints each line from the piped stdout and stderr of the [`Child`] to the same stream of the
+/// current process with the `prefix`, returning the threads doing the printing.
+fn print_prefixed_output(
+    child: &mut Child,
+    prefix: &str,
+) -> Vec<thread::JoinHandle<io::Result<()>>> {
+    let mut output_printers = vec![];
+
+    if let Some(stdout) = child.stdout.take() {
+        let prefix = prefix.to_string();
+        output_printers.push(thread::spawn(move || {
+            copy_prefixed(stdout, io::stdout(), &prefix)
+        }));
+    }
+
+    if let Some(stderr) = child.stderr.take() {
+        let prefix = prefix.to_string();
+        output_printers.push(thread::spawn(move || {
+            copy_prefixed(stderr, io::stderr(), &prefix)
+        }));
+    }
+
+    output_printers
+}
+
+/// Copies each line from `reader` to `writer`, with the `prefix` before it.
+fn copy_prefixed(reader: impl Read, mut writer: impl Write, prefix: &str) -> io::Result<()> {
+    let mut reader = BufReader::new(reader);
+    let mut line = Vec::new();
+
+    while reader.read_until(b'\n', &mut line)? > 0 {
+        writer.write_all(prefix.as_bytes())?;
+        writer.write_all(&line)?;
+        writer.flush()?;
+        line.clear();
+    }
+
+    Ok(())
+}
+
+/// Redirects the stdout and stderr of the provided [`Command`] to the files configured on the
+/// provided [`ExecutionConfigVariant`], if any.
+fn redirect_output(
+    command: &mut Command,
+    execution_config: &ExecutionConfigVariant,
+    variables: &VariableMap,
+) -> io::Result<()> {
+    let (stdout_file, stderr_file, append) = match execution_config {
+        ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(bash_config)) => (
+            &bash_config.stdout_file,
+            &bash_config.stderr_file,
+            bash_config.append,
+        ),
+        ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::RawCommandConfig(
+            raw_config,
+        )) => (
+            &raw_config.stdout_file,
+            &raw_config.stderr_file,
+            raw_config.append,
+        ),
+        ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(_)) => return Ok(()),
+    };
+
+    if let Some(stdout_file) = stdout_file {
+        command.stdout(open_output_file(stdout_file, append, variables)?);
+    }
+
+    if let Some(stderr_file) = stderr_file {
+        command.stderr(open_output_file(stderr_file, append, variables)?);
+    }
+
+    Ok(())
+}
+
+/// Opens the file at `path` for writing, creating any missing parent directories.
+fn open_output_file(path: &str, append: bool, variables: &VariableMap) -> io::Result<File> {
+    let path = variables::substitute_variables(path, variables);
+
+    if let Some(parent) = Path::new(&path).parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)
+}
+
+/// Wraps the provided [`Command`] so that it's executed as `user` via `sudo`.
+/// `sudo` resets the environment by default, so the variables need to be explicitly preserved.
+/// Stdin is inherited so that `sudo` can prompt for a password, unless the step redirects it.
+fn run_as(command: &Command, user: &str, variables: &VariableMap) -> Command {
+    let mut variable_names: Vec<&String> = variables.keys().collect();
+    variable_names.sort();
+
+    let mut sudo_command = Command::new("sudo");
+    sudo_command.arg("-u").arg(user);
+
+    if !variable_names.is_empty() {
+        let variable_names: Vec<&str> = variable_names.iter().map(|name| name.as_str()).collect();
+        sudo_command.arg(format!("--preserve-env={}", variable_names.join(",")));
+    }
+
+    sudo_command
+        .arg("--")
+        .arg(command.get_program())
+        .args(command.get_args())
+        .envs(variables)
+        .stdin(Stdio::inherit());
+
+    if let Some(current_dir) = command.get_current_dir() {
+        sudo_command.current_dir(current_dir);
+    }
+
+    sudo_command
 }
 
 fn get_command_text(command: &Command) -> String {
@@ -204,6 +934,18 @@ fn get_command_text(command: &Command) -> String {
 pub enum ExecutionError {
     #[error(transparent)]
     IO(io::Error),
+
+    #[error(transparent)]
+    Template(TemplateError),
+
+    #[error("output exceeded the {max_output_bytes} byte limit")]
+    OutputTooLarge { max_output_bytes: u64 },
+
+    #[error("failed to add directories to PATH")]
+    Path(#[source] JoinPathsError),
+
+    #[error("timed out")]
+    TimedOut,
 }
 
 #[cfg(test)]
@@ -211,6 +953,7 @@ mod tests {
     use super::*;
     use crate::config::{BashCommandConfig, RawCommandConfig};
     use std::collections::HashMap;
+    use std::ffi::OsStr;
     use std::fs;
     use std::io::Write;
     use std::path::Path;
@@ -229,9 +972,19 @@ mod tests {
             ShellCommandConfigVariant::Bash(BashCommandConfig {
                 working_directory: None,
                 command: format!("echo \"Hello, World!\" > {temp_file_path}"),
+                run_as: None,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                allow_extra_args: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
             }),
         );
-        let command_executor = create_command_executor(&DingusOptions::default());
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
 
         // Act
         let result = command_executor.execute(&bash_exec_config, &Default::default());
@@ -242,6 +995,67 @@ mod tests {
         assert_eq!(file_content, format!("Hello, World!\n"));
     }
 
+    #[test]
+    #[cfg(not(windows))]
+    fn search_path_value_prepends_and_appends_directories() {
+        // Arrange
+        let search_path = SearchPath {
+            prepend: vec!["./bin".to_string(), "/opt/$tool/bin".to_string()],
+            append: vec!["/usr/local/sbin".to_string()],
+        };
+        let variables = HashMap::from([("tool".to_string(), "dingus".to_string())]);
+
+        // Act
+        let path = search_path_value(
+            &search_path,
+            &variables,
+            Some(OsString::from("/usr/bin:/bin")),
+            Path::new("/project"),
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!(
+            path,
+            "/project/./bin:/opt/dingus/bin:/usr/bin:/bin:/usr/local/sbin"
+        );
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn raw_command_get_output_uses_search_path() {
+        // Arrange
+        let temp_dir = TempDir::new().unwrap();
+        let script_path = temp_dir.path().join("dingus-test-greet");
+        fs::write(&script_path, "#!/bin/sh\necho \"Hello, World!\"\n").unwrap();
+        fs::set_permissions(
+            &script_path,
+            std::os::unix::fs::PermissionsExt::from_mode(0o755),
+        )
+        .unwrap();
+
+        let exec_config = ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(
+            "dingus-test-greet".to_string(),
+        ));
+        let command_executor = create_command_executor_with_search_path(
+            &DingusOptions::default(),
+            &None,
+            &[],
+            &SearchPath {
+                prepend: vec![get_path(temp_dir.path())],
+                append: vec![],
+            },
+        );
+
+        // Act
+        let output = command_executor
+            .get_output(&exec_config, &Default::default())
+            .unwrap();
+
+        // Assert
+        assert_eq!(output.stdout, "Hello, World!\n".as_bytes());
+    }
+
     #[test]
     #[cfg(not(windows))]
     fn bash_command_execute_evaluates_variables() {
@@ -258,9 +1072,19 @@ mod tests {
             ShellCommandConfigVariant::Bash(BashCommandConfig {
                 working_directory: None,
                 command: format!("echo \"Hello, ${variable_name}!\" > {temp_file_path}"),
+                run_as: None,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                allow_extra_args: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
             }),
         );
-        let command_executor = create_command_executor(&DingusOptions::default());
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
 
         // Act
         let result = command_executor.execute(&bash_exec_config, &variables);
@@ -271,6 +1095,51 @@ mod tests {
         assert_eq!(file_content, format!("Hello, {variable_value}!\n"));
     }
 
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn bash_command_execute_stops_child_processes_after_deadline() {
+        // Arrange
+        let temp_file = create_empty_temp_file();
+        let temp_file_path = get_path(temp_file.path());
+
+        let bash_exec_config = ExecutionConfigVariant::ShellCommand(
+            ShellCommandConfigVariant::Bash(BashCommandConfig {
+                working_directory: None,
+                command: format!("sleep 30 & echo $! > {temp_file_path}; wait"),
+                run_as: None,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                allow_extra_args: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
+            }),
+        );
+        let command_executor = create_command_executor_with_deadline(
+            &DingusOptions::default(),
+            &None,
+            &[],
+            &SearchPath::default(),
+            Some(Instant::now() + Duration::from_millis(500)),
+        );
+
+        // Act
+        let result = command_executor.execute(&bash_exec_config, &Default::default());
+
+        // Assert
+        assert!(matches!(result, Err(ExecutionError::TimedOut)));
+        let pid = fs::read_to_string(temp_file_path).unwrap();
+        let stat = fs::read_to_string(format!("/proc/{}/stat", pid.trim())).unwrap_or_default();
+        // Killed processes which haven't been reaped yet are zombies
+        let is_running = stat
+            .rsplit_once(')')
+            .is_some_and(|(_, rest)| !rest.trim_start().starts_with('Z'));
+        assert!(!is_running, "sleep is still running");
+    }
+
     #[test]
     #[cfg(not(windows))]
     fn bash_command_execute_returns_exit_code() {
@@ -279,9 +1148,19 @@ mod tests {
             ShellCommandConfigVariant::Bash(BashCommandConfig {
                 working_directory: None,
                 command: "exit 42".to_string(),
+                run_as: None,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                allow_extra_args: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
             }),
         );
-        let command_executor = create_command_executor(&DingusOptions::default());
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
 
         // Act
         let result = command_executor.execute(&bash_exec_config, &Default::default());
@@ -305,9 +1184,19 @@ mod tests {
             ShellCommandConfigVariant::Bash(BashCommandConfig {
                 working_directory: None,
                 command: format!("echo \"Hello, ${variable_name}!\""),
+                run_as: None,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                allow_extra_args: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
             }),
         );
-        let command_executor = create_command_executor(&DingusOptions::default());
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
 
         // Act
         let result = command_executor.get_output(&bash_exec_config, &variables);
@@ -324,116 +1213,443 @@ mod tests {
 
     #[test]
     #[cfg(not(windows))]
-    fn bash_command_get_output_returns_stdout() {
+    fn get_output_fails_when_output_exceeds_limit() {
         // Arrange
         let bash_exec_config = ExecutionConfigVariant::ShellCommand(
             ShellCommandConfigVariant::Bash(BashCommandConfig {
                 working_directory: None,
-                command: "echo \"Hello, World!\"".to_string(),
+                command: "yes".to_string(),
+                run_as: None,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                allow_extra_args: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
             }),
         );
-        let command_executor = create_command_executor(&DingusOptions::default());
+        let options = DingusOptions {
+            max_output_bytes: Some(1024),
+            ..Default::default()
+        };
+        let command_executor = create_command_executor(&options, &None, &[]);
 
         // Act
         let result = command_executor.get_output(&bash_exec_config, &HashMap::new());
-        assert!(!result.is_err());
 
         // Assert
-        let output = result.unwrap();
-        assert_eq!(output.status, ExitStatus::Success);
-        assert!(output.stderr.is_empty());
-
-        let output_value = String::from_utf8(output.stdout).unwrap();
-        assert_eq!(output_value, "Hello, World!\n");
+        assert!(matches!(
+            result,
+            Err(ExecutionError::OutputTooLarge {
+                max_output_bytes: 1024
+            })
+        ));
     }
 
     #[test]
     #[cfg(not(windows))]
-    fn bash_command_get_output_returns_stderr() {
+    fn get_output_succeeds_within_limit() {
         // Arrange
         let bash_exec_config = ExecutionConfigVariant::ShellCommand(
             ShellCommandConfigVariant::Bash(BashCommandConfig {
                 working_directory: None,
-                command: ">&2 echo \"Error message\"".to_string(),
+                command: "echo \"Hello, World!\"; echo oops >&2".to_string(),
+                run_as: None,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                allow_extra_args: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
             }),
         );
-        let command_executor = create_command_executor(&DingusOptions::default());
+        let options = DingusOptions {
+            max_output_bytes: Some(14),
+            ..Default::default()
+        };
+        let command_executor = create_command_executor(&options, &None, &[]);
 
         // Act
-        let result = command_executor.get_output(&bash_exec_config, &HashMap::new());
-        assert!(!result.is_err());
+        let output = command_executor
+            .get_output(&bash_exec_config, &HashMap::new())
+            .unwrap();
 
         // Assert
-        let output = result.unwrap();
-        assert_eq!(output.status, ExitStatus::Success);
-        assert!(output.stdout.is_empty());
-
-        let output_value = String::from_utf8(output.stderr).unwrap();
-        assert_eq!(output_value, "Error message\n");
+        assert_eq!(output.stdout, b"Hello, World!\n");
+        assert_eq!(output.stderr, b"oops\n");
     }
 
     #[test]
     #[cfg(not(windows))]
-    fn bash_command_get_output_returns_exit_code() {
+    fn bash_command_with_shebang_uses_its_interpreter() {
         // Arrange
+        let script = "#!/bin/sh\necho \"$0\"\n";
         let bash_exec_config = ExecutionConfigVariant::ShellCommand(
             ShellCommandConfigVariant::Bash(BashCommandConfig {
                 working_directory: None,
-                command: "exit 42".to_string(),
+                command: script.to_string(),
+                run_as: None,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                allow_extra_args: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
             }),
         );
-        let command_executor = create_command_executor(&DingusOptions::default());
+        let options = DingusOptions {
+            bash_path: "/bin/false".to_string(),
+            ..Default::default()
+        };
+        let command_executor = create_command_executor(&options, &None, &[]);
 
         // Act
-        let result = command_executor.get_output(&bash_exec_config, &HashMap::new());
-        assert!(!result.is_err());
+        let output = command_executor
+            .get_output(&bash_exec_config, &HashMap::new())
+            .unwrap();
 
         // Assert
-        let output = result.unwrap();
-        assert_eq!(output.status, ExitStatus::Fail(42));
-        assert!(output.stdout.is_empty());
-        assert!(output.stderr.is_empty());
+        assert_eq!(output.status, ExitStatus::Success);
+        // The script is executed from a temporary file, which is gone once the command finishes
+        let script_path = String::from_utf8(output.stdout).unwrap();
+        assert!(Path::new(script_path.trim_end()).is_absolute());
+        assert!(!Path::new(script_path.trim_end()).exists());
     }
 
     #[test]
     #[cfg(not(windows))]
-    fn bash_command_honours_workdir() {
+    fn bash_command_with_shebang_receives_extra_args() {
         // Arrange
+        let script = "#!/bin/sh\nprintf '%s|' \"$@\"\n";
         let bash_exec_config = ExecutionConfigVariant::ShellCommand(
             ShellCommandConfigVariant::Bash(BashCommandConfig {
-                working_directory: Some("./src".to_string()),
-                command: "pwd".to_string(),
+                working_directory: None,
+                command: script.to_string(),
+                run_as: None,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                allow_extra_args: true,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec!["a b".to_string(), "$HOME".to_string()],
             }),
         );
-        let command_executor = create_command_executor(&DingusOptions::default());
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
 
         // Act
-        let result = command_executor.get_output(&bash_exec_config, &HashMap::new());
-        assert!(!result.is_err());
+        let output = command_executor
+            .get_output(&bash_exec_config, &HashMap::new())
+            .unwrap();
 
         // Assert
-        let output = result.unwrap();
         assert_eq!(output.status, ExitStatus::Success);
-        assert!(output.stderr.is_empty());
-
-        let output_value = String::from_utf8(output.stdout).unwrap();
-        assert!(output_value.ends_with("/src\n"));
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "a b|$HOME|");
     }
 
     #[test]
-    fn raw_command_execute_executes_command() {
+    #[cfg(not(windows))]
+    fn get_output_uses_step_limit_over_options() {
         // Arrange
-        let temp_dir = create_temp_dir();
-        let file_name = "dingus.txt";
-        let test_file_path = temp_dir.path().join(file_name);
+        let echo_with_limit = |max_output_bytes: Option<u64>| {
+            ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+                BashCommandConfig {
+                    working_directory: None,
+                    command: "echo \"Hello, World!\"".to_string(),
+                    run_as: None,
+                    stdout_file: None,
+                    stderr_file: None,
+                    append: false,
+                    allow_extra_args: false,
+                    banner: None,
+                    stdin: StdinConfig::Inherit,
+                    label: None,
+                    max_output_bytes,
+                    extra_args: vec![],
+                },
+            ))
+        };
+        let options = DingusOptions {
+            max_output_bytes: Some(4),
+            ..Default::default()
+        };
+        let command_executor = create_command_executor(&options, &None, &[]);
 
-        // Sanity check
-        assert_eq!(test_file_path.exists(), false);
+        // Act
+        let raised_result =
+            command_executor.get_output(&echo_with_limit(Some(100)), &HashMap::new());
+        let default_result = command_executor.get_output(&echo_with_limit(None), &HashMap::new());
 
-        let bash_exec_config = ExecutionConfigVariant::RawCommand(
+        // Assert
+        assert_eq!(raised_result.unwrap().stdout, b"Hello, World!\n");
+        assert!(matches!(
+            default_result,
+            Err(ExecutionError::OutputTooLarge {
+                max_output_bytes: 4
+            })
+        ));
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn get_output_writes_stdin_text_and_files() {
+        // Arrange
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("input.txt"), "from a file").unwrap();
+        let cat_with_stdin = |stdin: StdinConfig| {
+            ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+                BashCommandConfig {
+                    working_directory: None,
+                    command: "cat".to_string(),
+                    run_as: None,
+                    stdout_file: None,
+                    stderr_file: None,
+                    append: false,
+                    allow_extra_args: false,
+                    banner: None,
+                    stdin,
+                    label: None,
+                    max_output_bytes: None,
+                    extra_args: vec![],
+                },
+            ))
+        };
+        let variables = HashMap::from([
+            ("name".to_string(), "Dingus".to_string()),
+            ("dir".to_string(), get_path(temp_dir.path())),
+        ]);
+        let limited_options = DingusOptions {
+            max_output_bytes: Some(100),
+            ..Default::default()
+        };
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
+        let limited_command_executor = create_command_executor(&limited_options, &None, &[]);
+
+        // Act
+        let text_output = command_executor
+            .get_output(
+                &cat_with_stdin(StdinConfig::Text("Hello, $name!".to_string())),
+                &variables,
+            )
+            .unwrap();
+        let file_output = limited_command_executor
+            .get_output(
+                &cat_with_stdin(StdinConfig::File("$dir/input.txt".to_string())),
+                &variables,
+            )
+            .unwrap();
+        let null_output = command_executor
+            .get_output(&cat_with_stdin(StdinConfig::Null), &variables)
+            .unwrap();
+
+        // Assert
+        assert_eq!(text_output.stdout, b"Hello, Dingus!");
+        assert_eq!(file_output.stdout, b"from a file");
+        assert!(null_output.stdout.is_empty());
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn bash_command_get_output_returns_stdout() {
+        // Arrange
+        let bash_exec_config = ExecutionConfigVariant::ShellCommand(
+            ShellCommandConfigVariant::Bash(BashCommandConfig {
+                working_directory: None,
+                command: "echo \"Hello, World!\"".to_string(),
+                run_as: None,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                allow_extra_args: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
+            }),
+        );
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
+
+        // Act
+        let result = command_executor.get_output(&bash_exec_config, &HashMap::new());
+        assert!(!result.is_err());
+
+        // Assert
+        let output = result.unwrap();
+        assert_eq!(output.status, ExitStatus::Success);
+        assert!(output.stderr.is_empty());
+
+        let output_value = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(output_value, "Hello, World!\n");
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn bash_command_get_output_returns_stderr() {
+        // Arrange
+        let bash_exec_config = ExecutionConfigVariant::ShellCommand(
+            ShellCommandConfigVariant::Bash(BashCommandConfig {
+                working_directory: None,
+                command: ">&2 echo \"Error message\"".to_string(),
+                run_as: None,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                allow_extra_args: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
+            }),
+        );
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
+
+        // Act
+        let result = command_executor.get_output(&bash_exec_config, &HashMap::new());
+        assert!(!result.is_err());
+
+        // Assert
+        let output = result.unwrap();
+        assert_eq!(output.status, ExitStatus::Success);
+        assert!(output.stdout.is_empty());
+
+        let output_value = String::from_utf8(output.stderr).unwrap();
+        assert_eq!(output_value, "Error message\n");
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn bash_command_get_output_returns_exit_code() {
+        // Arrange
+        let bash_exec_config = ExecutionConfigVariant::ShellCommand(
+            ShellCommandConfigVariant::Bash(BashCommandConfig {
+                working_directory: None,
+                command: "exit 42".to_string(),
+                run_as: None,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                allow_extra_args: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
+            }),
+        );
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
+
+        // Act
+        let result = command_executor.get_output(&bash_exec_config, &HashMap::new());
+        assert!(!result.is_err());
+
+        // Assert
+        let output = result.unwrap();
+        assert_eq!(output.status, ExitStatus::Fail(42));
+        assert!(output.stdout.is_empty());
+        assert!(output.stderr.is_empty());
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn bash_command_honours_workdir() {
+        // Arrange
+        let bash_exec_config = ExecutionConfigVariant::ShellCommand(
+            ShellCommandConfigVariant::Bash(BashCommandConfig {
+                working_directory: Some("./src".to_string()),
+                command: "pwd".to_string(),
+                run_as: None,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                allow_extra_args: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
+            }),
+        );
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
+
+        // Act
+        let result = command_executor.get_output(&bash_exec_config, &HashMap::new());
+        assert!(!result.is_err());
+
+        // Assert
+        let output = result.unwrap();
+        assert_eq!(output.status, ExitStatus::Success);
+        assert!(output.stderr.is_empty());
+
+        let output_value = String::from_utf8(output.stdout).unwrap();
+        assert!(output_value.ends_with("/src\n"));
+    }
+
+    #[test]
+    fn bash_command_substitutes_variables_in_workdir() {
+        // Arrange
+        let temp_dir = create_temp_dir();
+        let temp_dir_path = get_path(&temp_dir.path().canonicalize().unwrap());
+
+        let mut variables = HashMap::new();
+        variables.insert("project_dir".to_string(), temp_dir_path.clone());
+
+        let exec_config = ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+            BashCommandConfig {
+                working_directory: Some("$project_dir".to_string()),
+                command: "pwd".to_string(),
+                run_as: None,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                allow_extra_args: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
+            },
+        ));
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
+
+        // Act
+        let result = command_executor.get_output(&exec_config, &variables);
+        assert!(result.is_ok());
+
+        // Assert
+        let output = result.unwrap();
+        assert_eq!(output.status, ExitStatus::Success);
+
+        let output_value = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(output_value, format!("{temp_dir_path}\n"));
+    }
+
+    #[test]
+    fn raw_command_execute_executes_command() {
+        // Arrange
+        let temp_dir = create_temp_dir();
+        let file_name = "dingus.txt";
+        let test_file_path = temp_dir.path().join(file_name);
+
+        // Sanity check
+        assert_eq!(test_file_path.exists(), false);
+
+        let bash_exec_config = ExecutionConfigVariant::RawCommand(
             RawCommandConfigVariant::Shorthand(format!("touch {}", get_path(&test_file_path))),
         );
-        let command_executor = create_command_executor(&DingusOptions::default());
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
 
         // Act
         let result = command_executor.execute(&bash_exec_config, &Default::default());
@@ -462,7 +1678,7 @@ mod tests {
         let exec_config = ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(
             "touch $file_name".to_string(),
         ));
-        let command_executor = create_command_executor(&DingusOptions::default());
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
 
         // Act
         let result = command_executor.execute(&exec_config, &variables);
@@ -480,7 +1696,7 @@ mod tests {
         let exec_config = ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(
             "cargo silly".to_string(),
         ));
-        let command_executor = create_command_executor(&DingusOptions::default());
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
 
         // Act
         let result = command_executor.execute(&exec_config, &Default::default());
@@ -506,7 +1722,7 @@ mod tests {
         let exec_config = ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(
             "cat $file_name".to_string(),
         ));
-        let command_executor = create_command_executor(&DingusOptions::default());
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
 
         // Act
         let result = command_executor.get_output(&exec_config, &variables);
@@ -523,6 +1739,34 @@ mod tests {
         assert_eq!(output_value, content);
     }
 
+    #[test]
+    #[cfg(not(windows))]
+    fn raw_command_get_output_passes_extra_args_as_is() {
+        // Arrange
+        let mut variables = HashMap::new();
+        variables.insert("name".to_string(), "Dingus".to_string());
+
+        let exec_config = ExecutionConfigVariant::RawCommand(
+            RawCommandConfigVariant::RawCommandConfig(RawCommandConfig {
+                command: "echo $name".to_string(),
+                extra_args: vec!["a  b".to_string(), "$name".to_string()],
+                ..Default::default()
+            }),
+        );
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
+
+        // Act
+        let result = command_executor.get_output(&exec_config, &variables);
+
+        // Assert
+        let output = result.unwrap();
+        assert_eq!(output.status, ExitStatus::Success);
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            "Dingus a  b $name\n"
+        );
+    }
+
     // TODO: Re-implement. This is flaky.
     #[test]
     #[ignore]
@@ -536,7 +1780,7 @@ mod tests {
         let exec_config = ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(
             "cargo v".to_string(),
         ));
-        let command_executor = create_command_executor(&DingusOptions::default());
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
 
         // Act
         let result = command_executor.get_output(&exec_config, &variables);
@@ -551,6 +1795,85 @@ mod tests {
         assert!(output_value.contains("cargo 1.78.0"));
     }
 
+    #[test]
+    #[cfg(not(windows))]
+    fn unexported_variables_are_substituted_but_not_exported() {
+        // Arrange
+        let variable_name = "DINGUS_UNEXPORTED_PASSWORD";
+        let mut variables = HashMap::new();
+        variables.insert(variable_name.to_string(), "hunter2".to_string());
+
+        let echo_config = ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(
+            format!("echo ${variable_name}"),
+        ));
+        let printenv_config = ExecutionConfigVariant::RawCommand(
+            RawCommandConfigVariant::Shorthand(format!("printenv {variable_name}")),
+        );
+        let command_executor = create_command_executor(
+            &DingusOptions::default(),
+            &None,
+            &[variable_name.to_string()],
+        );
+
+        // Act
+        let echo_output = command_executor
+            .get_output(&echo_config, &variables)
+            .unwrap();
+        let printenv_output = command_executor
+            .get_output(&printenv_config, &variables)
+            .unwrap();
+
+        // Assert
+        assert_eq!(echo_output.stdout, b"hunter2\n");
+        assert_eq!(printenv_output.status, ExitStatus::Fail(1));
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn unexported_variables_are_visible_to_shells_but_not_exported() {
+        // Arrange
+        let variable_name = "DINGUS_UNEXPORTED_PASSWORD";
+        let mut variables = HashMap::new();
+        variables.insert(variable_name.to_string(), "hunter2".to_string());
+
+        let script = format!("echo \"${variable_name}\"; printenv {variable_name} || echo unset");
+        let bash_config = ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+            BashCommandConfig {
+                working_directory: None,
+                command: script.clone(),
+                run_as: None,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                allow_extra_args: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
+            },
+        ));
+        let shorthand_config =
+            ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(script));
+        let command_executor = create_command_executor(
+            &DingusOptions::default(),
+            &Some(Shell::Sh),
+            &[variable_name.to_string()],
+        );
+
+        // Act
+        let bash_output = command_executor
+            .get_output(&bash_config, &variables)
+            .unwrap();
+        let shorthand_output = command_executor
+            .get_output(&shorthand_config, &variables)
+            .unwrap();
+
+        // Assert
+        assert_eq!(bash_output.stdout, b"hunter2\nunset\n");
+        assert_eq!(shorthand_output.stdout, b"hunter2\nunset\n");
+    }
+
     #[test]
     fn raw_command_get_output_returns_stdout() {
         // Arrange
@@ -561,7 +1884,7 @@ mod tests {
         let exec_config = ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(
             format!("cat {temp_file_path}").to_string(),
         ));
-        let command_executor = create_command_executor(&DingusOptions::default());
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
 
         // Act
         let result = command_executor.get_output(&exec_config, &HashMap::new());
@@ -582,7 +1905,7 @@ mod tests {
         let exec_config = ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(
             "cat does_not_exist.txt".to_string(),
         ));
-        let command_executor = create_command_executor(&DingusOptions::default());
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
 
         // Act
         let result = command_executor.get_output(&exec_config, &HashMap::new());
@@ -604,9 +1927,19 @@ mod tests {
             RawCommandConfigVariant::RawCommandConfig(RawCommandConfig {
                 working_directory: Some("./src".to_string()),
                 command: "pwd".to_string(),
+                run_as: None,
+                template: TemplateEngine::Simple,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
             }),
         );
-        let command_executor = create_command_executor(&DingusOptions::default());
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
 
         // Act
         let result = command_executor.get_output(&exec_config, &HashMap::new());
@@ -621,6 +1954,217 @@ mod tests {
         assert!(output_value.ends_with("/src\n"));
     }
 
+    #[test]
+    fn raw_command_substitutes_variables_in_workdir() {
+        // Arrange
+        let temp_dir = create_temp_dir();
+        let temp_dir_path = get_path(&temp_dir.path().canonicalize().unwrap());
+
+        let mut variables = HashMap::new();
+        variables.insert("project_dir".to_string(), temp_dir_path.clone());
+
+        let exec_config = ExecutionConfigVariant::RawCommand(
+            RawCommandConfigVariant::RawCommandConfig(RawCommandConfig {
+                working_directory: Some("$project_dir".to_string()),
+                command: "pwd".to_string(),
+                run_as: None,
+                template: TemplateEngine::Simple,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
+            }),
+        );
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
+
+        // Act
+        let result = command_executor.get_output(&exec_config, &variables);
+        assert!(result.is_ok());
+
+        // Assert
+        let output = result.unwrap();
+        assert_eq!(output.status, ExitStatus::Success);
+
+        let output_value = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(output_value, format!("{temp_dir_path}\n"));
+    }
+
+    #[test]
+    fn bash_command_uses_custom_bash_path() {
+        // Arrange
+        let exec_config = ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+            BashCommandConfig {
+                working_directory: None,
+                command: "echo $BASH_VERSION".to_string(),
+                run_as: None,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                allow_extra_args: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
+            },
+        ));
+
+        let options = DingusOptions {
+            bash_path: "/opt/homebrew/bin/bash".to_string(),
+            ..Default::default()
+        };
+
+        // Act
+        let (command, _) =
+            get_command_for(&exec_config, &HashMap::new(), &options, &None, &[]).unwrap();
+
+        // Assert
+        assert_eq!(command.get_program(), "/opt/homebrew/bin/bash");
+    }
+
+    #[test]
+    fn bash_command_runs_as_user() {
+        // Arrange
+        let exec_config = ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+            BashCommandConfig {
+                working_directory: None,
+                command: "whoami".to_string(),
+                run_as: Some("root".to_string()),
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                allow_extra_args: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
+            },
+        ));
+
+        let mut variables = HashMap::new();
+        variables.insert("name".to_string(), "Dingus".to_string());
+
+        // Act
+        let (command, _) = get_command_for(
+            &exec_config,
+            &variables,
+            &DingusOptions::default(),
+            &None,
+            &[],
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!(command.get_program(), "sudo");
+        let args: Vec<&OsStr> = command.get_args().collect();
+        assert_eq!(
+            args,
+            vec![
+                "-u",
+                "root",
+                "--preserve-env=name",
+                "--",
+                "bash",
+                "-c",
+                "whoami"
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn bash_command_with_shebang_runs_as_user() {
+        // Arrange
+        let exec_config = ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+            BashCommandConfig {
+                working_directory: None,
+                command: "#!/bin/sh\nwhoami\n".to_string(),
+                run_as: Some("admin".to_string()),
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                allow_extra_args: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
+            },
+        ));
+
+        // Act
+        let (command, script_path) = get_command_for(
+            &exec_config,
+            &HashMap::new(),
+            &DingusOptions::default(),
+            &None,
+            &[],
+        )
+        .unwrap();
+
+        // Assert
+        let script_path = script_path.unwrap();
+        assert_eq!(command.get_program(), "sudo");
+        let args: Vec<&OsStr> = command.get_args().collect();
+        assert_eq!(
+            args,
+            vec![
+                OsStr::new("-u"),
+                OsStr::new("admin"),
+                OsStr::new("--"),
+                OsStr::new("/bin/sh"),
+                script_path.as_os_str()
+            ]
+        );
+        // The interpreter runs as the other user, so it has to be able to read the script
+        let mode = std::os::unix::fs::PermissionsExt::mode(
+            &fs::metadata(&script_path).unwrap().permissions(),
+        );
+        assert_eq!(mode & 0o777, 0o644);
+    }
+
+    #[test]
+    fn raw_command_runs_as_user() {
+        // Arrange
+        let exec_config = ExecutionConfigVariant::RawCommand(
+            RawCommandConfigVariant::RawCommandConfig(RawCommandConfig {
+                working_directory: Some("./src".to_string()),
+                command: "ls -la".to_string(),
+                run_as: Some("admin".to_string()),
+                template: TemplateEngine::Simple,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
+            }),
+        );
+
+        // Act
+        let (command, _) = get_command_for(
+            &exec_config,
+            &HashMap::new(),
+            &DingusOptions::default(),
+            &None,
+            &[],
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!(command.get_program(), "sudo");
+        let args: Vec<&OsStr> = command.get_args().collect();
+        assert_eq!(args, vec!["-u", "admin", "--", "ls", "-la"]);
+        assert_eq!(command.get_current_dir(), Some(Path::new("./src")));
+    }
+
     #[test]
     fn raw_command_does_not_use_shell() {
         // Arrange
@@ -628,17 +2172,258 @@ mod tests {
             RawCommandConfigVariant::RawCommandConfig(RawCommandConfig {
                 working_directory: None,
                 command: "shopt -s expand_aliases".to_string(),
+                run_as: None,
+                template: TemplateEngine::Simple,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
             }),
         );
-        let command_executor = create_command_executor(&DingusOptions::default());
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
+
+        // Act
+        let result = command_executor.get_output(&exec_config, &HashMap::new());
+
+        // Assert
+        assert!(result.is_err());
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn shorthand_command_uses_default_shell() {
+        // Arrange
+        let exec_config = ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(
+            "echo $((1 + 2))".to_string(),
+        ));
+        let command_executor =
+            create_command_executor(&DingusOptions::default(), &Some(Shell::Sh), &[]);
 
         // Act
         let result = command_executor.get_output(&exec_config, &HashMap::new());
+        assert!(result.is_ok());
+
+        // Assert
+        let output = result.unwrap();
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "3\n");
+    }
+
+    #[test]
+    fn raw_command_config_ignores_default_shell() {
+        // Arrange
+        let exec_config = ExecutionConfigVariant::RawCommand(
+            RawCommandConfigVariant::RawCommandConfig(RawCommandConfig {
+                working_directory: None,
+                command: "echo Hello".to_string(),
+                run_as: None,
+                template: TemplateEngine::Simple,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
+            }),
+        );
+
+        // Act
+        let (command, _) = get_command_for(
+            &exec_config,
+            &HashMap::new(),
+            &DingusOptions::default(),
+            &Some(Shell::Zsh),
+            &[],
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!(command.get_program(), "echo");
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn raw_command_redirects_stdout_to_file() {
+        // Arrange
+        let temp_dir = create_temp_dir();
+        let output_path = temp_dir.path().join("logs/output.txt");
+
+        let mut variables = HashMap::new();
+        variables.insert("LOG_DIR".to_string(), get_path(temp_dir.path()));
+
+        let exec_config = ExecutionConfigVariant::RawCommand(
+            RawCommandConfigVariant::RawCommandConfig(RawCommandConfig {
+                working_directory: None,
+                command: "echo Hello".to_string(),
+                run_as: None,
+                template: TemplateEngine::Simple,
+                stdout_file: Some("$LOG_DIR/logs/output.txt".to_string()),
+                stderr_file: None,
+                append: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
+            }),
+        );
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
+
+        // Act
+        let result = command_executor.execute(&exec_config, &variables);
+        assert!(result.is_ok());
+
+        // Assert
+        let file_content = fs::read_to_string(output_path).unwrap();
+        assert_eq!(file_content, "Hello\n");
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn get_output_leaves_output_files_untouched() {
+        // Arrange
+        let temp_file = create_temp_file("First\n");
+        let temp_file_path = get_path(temp_file.path());
+
+        let exec_config = ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+            BashCommandConfig {
+                working_directory: None,
+                command: "echo Second".to_string(),
+                run_as: None,
+                stdout_file: Some(temp_file_path.clone()),
+                stderr_file: None,
+                append: false,
+                allow_extra_args: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
+            },
+        ));
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
+
+        // Act
+        let output = command_executor
+            .get_output(&exec_config, &Default::default())
+            .unwrap();
+
+        // Assert
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "Second\n");
+        let file_content = fs::read_to_string(temp_file_path).unwrap();
+        assert_eq!(file_content, "First\n");
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn bash_command_appends_to_output_files() {
+        // Arrange
+        let temp_file = create_temp_file("First\n");
+        let temp_file_path = get_path(temp_file.path());
+
+        let exec_config = ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+            BashCommandConfig {
+                working_directory: None,
+                command: "echo Second >&2".to_string(),
+                run_as: None,
+                stdout_file: None,
+                stderr_file: Some(temp_file_path.clone()),
+                append: true,
+                allow_extra_args: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
+            },
+        ));
+        let command_executor = create_command_executor(&DingusOptions::default(), &None, &[]);
+
+        // Act
+        let result = command_executor.execute(&exec_config, &Default::default());
+        assert!(result.is_ok());
+
+        // Assert
+        let file_content = fs::read_to_string(temp_file_path).unwrap();
+        assert_eq!(file_content, "First\nSecond\n");
+    }
+
+    #[test]
+    fn format_env_includes_resolved_variables() {
+        // Arrange
+        let mut variables = HashMap::new();
+        variables.insert("NAME".to_string(), "Dingus".to_string());
+        variables.insert("PASSWORD".to_string(), "hunter2".to_string());
+        variables.insert("AGE".to_string(), "3".to_string());
+
+        // Act
+        let lines = format_env(&variables, &["PASSWORD".to_string()]);
+
+        // Assert
+        assert_eq!(
+            lines,
+            vec![
+                "AGE=3".to_string(),
+                "NAME=Dingus".to_string(),
+                "PASSWORD=********".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn output_stdout_string_strict_rejects_invalid_utf8() {
+        // Arrange
+        let output = Output {
+            status: ExitStatus::Success,
+            stdout: vec![b'h', b'i', 0xFF],
+            stderr: vec![],
+        };
+
+        // Act
+        let result = output.stdout_string(&Encoding::Strict);
 
         // Assert
         assert!(result.is_err());
     }
 
+    #[test]
+    fn output_stdout_string_lossy_replaces_invalid_utf8() {
+        // Arrange
+        let output = Output {
+            status: ExitStatus::Success,
+            stdout: vec![b'h', b'i', 0xFF],
+            stderr: vec![],
+        };
+
+        // Act
+        let result = output.stdout_string(&Encoding::Lossy);
+
+        // Assert
+        assert_eq!(result.unwrap(), "hi\u{FFFD}");
+    }
+
+    #[test]
+    fn copy_prefixed_prefixes_each_line() {
+        // Arrange
+        let output = "Building...\nDone\nno trailing newline".as_bytes();
+        let mut prefixed_output = Vec::new();
+
+        // Act
+        let result = copy_prefixed(output, &mut prefixed_output, "[api] ");
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(
+            String::from_utf8(prefixed_output).unwrap(),
+            "[api] Building...\n[api] Done\n[api] no trailing newline"
+        );
+    }
+
     fn create_temp_dir() -> TempDir {
         let temp_dir = TempDir::new().unwrap();
         return temp_dir;
@@ -0,0 +1,72 @@
+use crate::config::ConfigError;
+use crate::variables::{VariableCheckError, VariableResolutionError};
+
+/// The exit code used for failures that don't have a more specific exit code.
+pub const FAILURE_EXIT_CODE: i32 = 1;
+
+/// The exit code used when a variable couldn't be resolved, or any variables failed the
+/// `--check-vars` check.
+pub const VARIABLE_RESOLUTION_EXIT_CODE: i32 = 3;
+
+/// The exit code used when the config file couldn't be loaded or is invalid.
+pub const CONFIG_EXIT_CODE: i32 = 4;
+
+/// Maps the provided error to an exit code so that scripts can tell different kinds of failures
+/// apart.
+/// The first error in the chain with a specific exit code is used.
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    err.chain()
+        .find_map(|err| {
+            if err.is::<VariableResolutionError>() || err.is::<VariableCheckError>() {
+                Some(VARIABLE_RESOLUTION_EXIT_CODE)
+            } else if err.is::<ConfigError>() {
+                Some(CONFIG_EXIT_CODE)
+            } else {
+                None
+            }
+        })
+        .unwrap_or(FAILURE_EXIT_CODE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn exit_code_maps_known_errors() {
+        // Arrange
+        let variable_err = anyhow::Error::new(VariableResolutionError::NoMatch {
+            key: "version".to_string(),
+            pattern: "v(.+)".to_string(),
+        });
+        let check_err = anyhow::Error::new(VariableCheckError { errors: vec![] });
+        let config_err = anyhow::Error::new(ConfigError::FileNotFound);
+        let other_err = anyhow::anyhow!("something went wrong");
+
+        // Act
+        let variable_exit_code = exit_code(&variable_err);
+        let check_exit_code = exit_code(&check_err);
+        let config_exit_code = exit_code(&config_err);
+        let other_exit_code = exit_code(&other_err);
+
+        // Assert
+        assert_eq!(variable_exit_code, VARIABLE_RESOLUTION_EXIT_CODE);
+        assert_eq!(check_exit_code, VARIABLE_RESOLUTION_EXIT_CODE);
+        assert_eq!(config_exit_code, CONFIG_EXIT_CODE);
+        assert_eq!(other_exit_code, FAILURE_EXIT_CODE);
+    }
+
+    #[test]
+    fn exit_code_checks_error_sources() {
+        // Arrange
+        let result: Result<(), ConfigError> = Err(ConfigError::FileNotFound);
+        let err = result.context("failed to start").unwrap_err();
+
+        // Act
+        let exit_code = exit_code(&err);
+
+        // Assert
+        assert_eq!(exit_code, CONFIG_EXIT_CODE);
+    }
+}
@@ -0,0 +1,294 @@
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::{env, fs, io};
+use tempfile::TempDir;
+use thiserror::Error;
+
+const GIT_SOURCE_PREFIX: &str = "git+";
+
+/// A config file in a remote git repository, written as `git+<repository>//<path>@<ref>`.
+#[derive(PartialEq, Debug)]
+pub struct GitSource {
+    pub repository: String,
+    pub path: String,
+    pub reference: Option<String>,
+}
+
+/// Parses the provided import source as a [`GitSource`].
+/// Returns `None` if the source isn't a git source, or an error if it is one but it's malformed.
+pub fn parse_git_source(source: &str) -> Option<Result<GitSource, GitSourceError>> {
+    let source = source.strip_prefix(GIT_SOURCE_PREFIX)?;
+
+    // The first `//` after the scheme separates the repository from the path
+    let path_start = source.find("://").map_or(0, |index| index + 3);
+    let Some(separator) = source[path_start..]
+        .find("//")
+        .map(|index| index + path_start)
+    else {
+        return Some(Err(GitSourceError::Invalid {
+            git_source: format!("{GIT_SOURCE_PREFIX}{source}"),
+        }));
+    };
+
+    let repository = &source[..separator];
+    let (path, reference) = match source[separator + 2..].rsplit_once('@') {
+        Some((path, reference)) => (path, Some(reference.to_string())),
+        None => (&source[separator + 2..], None),
+    };
+
+    if repository.is_empty() || path.is_empty() || reference.as_deref() == Some("") {
+        return Some(Err(GitSourceError::Invalid {
+            git_source: format!("{GIT_SOURCE_PREFIX}{source}"),
+        }));
+    }
+
+    Some(Ok(GitSource {
+        repository: repository.to_string(),
+        path: path.to_string(),
+        reference,
+    }))
+}
+
+/// The directory that git sources are cached in, `$XDG_CACHE_HOME/dingus/git`, falling back to
+/// `~/.cache/dingus/git`.
+pub fn cache_directory() -> PathBuf {
+    let cache_home = match env::var_os("XDG_CACHE_HOME") {
+        Some(cache_home) => PathBuf::from(cache_home),
+        None => match env::var_os("HOME") {
+            Some(home) => PathBuf::from(home).join(".cache"),
+            None => env::temp_dir(),
+        },
+    };
+
+    cache_home.join("dingus").join("git")
+}
+
+/// Returns the path to the file described by `git_source`, cloning the repository into
+/// `cache_directory` first unless it has already been cloned.
+/// Each repository and ref is cloned once, so sources pinned to a tag or commit stay the same
+/// until the cache is cleared.
+pub fn fetch_git_source(
+    git_source: &GitSource,
+    cache_directory: &Path,
+) -> Result<PathBuf, GitSourceError> {
+    // The hash has to stay the same across Rust releases, which DefaultHasher doesn't promise
+    let mut hasher = Sha256::new();
+    hasher.update(&git_source.repository);
+    if let Some(reference) = &git_source.reference {
+        hasher.update(b"\0");
+        hasher.update(reference);
+    }
+    let hash: String = hasher.finalize()[..8]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    let checkout_directory = cache_directory.join(hash);
+
+    if !checkout_directory.exists() {
+        fs::create_dir_all(cache_directory)?;
+
+        // Clone into a temporary directory first so that a failed clone isn't mistaken for a
+        // cached one next time
+        let temp_dir = TempDir::new_in(cache_directory)?;
+        let reference = git_source.reference.as_deref().unwrap_or("HEAD");
+        git(temp_dir.path(), &["init", "--quiet"])?;
+        git(
+            temp_dir.path(),
+            &[
+                "fetch",
+                "--quiet",
+                "--depth",
+                "1",
+                &git_source.repository,
+                reference,
+            ],
+        )?;
+        git(
+            temp_dir.path(),
+            &["checkout", "--quiet", "--detach", "FETCH_HEAD"],
+        )?;
+
+        // Another run may have finished cloning the same source in the meantime
+        if let Err(err) = fs::rename(temp_dir.path(), &checkout_directory) {
+            if !checkout_directory.exists() {
+                return Err(err.into());
+            }
+        }
+    }
+
+    let file_path = checkout_directory.join(&git_source.path);
+    if !file_path.is_file() {
+        return Err(GitSourceError::FileNotFound {
+            path: git_source.path.clone(),
+            repository: git_source.repository.clone(),
+        });
+    }
+
+    Ok(file_path)
+}
+
+/// Runs git with the provided `args` in `directory`.
+fn git(directory: &Path, args: &[&str]) -> Result<(), GitSourceError> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(directory)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(GitSourceError::Git {
+            args: args.join(" "),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+#[derive(Error, Debug)]
+pub enum GitSourceError {
+    #[error("invalid git source \"{git_source}\", expected git+<repository>//<path>@<ref>")]
+    Invalid { git_source: String },
+
+    #[error("\"git {args}\" failed: {stderr}")]
+    Git { args: String, stderr: String },
+
+    #[error("{path} does not exist in {repository}")]
+    FileNotFound { path: String, repository: String },
+
+    #[error(transparent)]
+    IO(#[from] io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_repository(directory: &Path) {
+        fs::write(directory.join("deploy.yaml"), "commands: {}").unwrap();
+        for args in [
+            vec!["init", "--quiet"],
+            vec!["add", "deploy.yaml"],
+            vec![
+                "-c",
+                "user.name=dingus",
+                "-c",
+                "user.email=dingus@example.com",
+                "commit",
+                "--quiet",
+                "-m",
+                "Add deploy.yaml",
+            ],
+            vec!["tag", "v1"],
+        ] {
+            git(directory, &args).unwrap();
+        }
+    }
+
+    #[test]
+    fn parse_git_source_splits_repository_path_and_ref() {
+        // Act
+        let git_source =
+            parse_git_source("git+https://github.com/org/tasks.git//ci/deploy.yaml@v1");
+
+        // Assert
+        assert_eq!(
+            git_source.unwrap().unwrap(),
+            GitSource {
+                repository: "https://github.com/org/tasks.git".to_string(),
+                path: "ci/deploy.yaml".to_string(),
+                reference: Some("v1".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_git_source_ref_is_optional() {
+        // Act
+        let git_source = parse_git_source("git+ssh://git@github.com/org/tasks.git//deploy.yaml");
+
+        // Assert
+        assert_eq!(
+            git_source.unwrap().unwrap(),
+            GitSource {
+                repository: "ssh://git@github.com/org/tasks.git".to_string(),
+                path: "deploy.yaml".to_string(),
+                reference: None,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_git_source_ignores_other_sources() {
+        // Act
+        let git_source = parse_git_source("./docs/dingus.yaml");
+
+        // Assert
+        assert!(git_source.is_none());
+    }
+
+    #[test]
+    fn parse_git_source_fails_without_path() {
+        // Act
+        let git_source = parse_git_source("git+https://github.com/org/tasks.git@v1");
+
+        // Assert
+        assert!(matches!(
+            git_source,
+            Some(Err(GitSourceError::Invalid { .. }))
+        ));
+    }
+
+    #[test]
+    fn fetch_git_source_clones_ref() {
+        // Arrange
+        let repository_dir = TempDir::new().unwrap();
+        create_repository(repository_dir.path());
+        let cache_dir = TempDir::new().unwrap();
+        let git_source = GitSource {
+            repository: repository_dir.path().display().to_string(),
+            path: "deploy.yaml".to_string(),
+            reference: Some("v1".to_string()),
+        };
+
+        // Act
+        let file_path = fetch_git_source(&git_source, cache_dir.path()).unwrap();
+
+        // Assert
+        assert!(file_path.starts_with(cache_dir.path()));
+        assert_eq!(fs::read_to_string(file_path).unwrap(), "commands: {}");
+    }
+
+    #[test]
+    fn fetch_git_source_fails_for_missing_file_and_ref() {
+        // Arrange
+        let repository_dir = TempDir::new().unwrap();
+        create_repository(repository_dir.path());
+        let cache_dir = TempDir::new().unwrap();
+        let repository = repository_dir.path().display().to_string();
+        let missing_file = GitSource {
+            repository: repository.clone(),
+            path: "build.yaml".to_string(),
+            reference: Some("v1".to_string()),
+        };
+        let missing_ref = GitSource {
+            repository,
+            path: "deploy.yaml".to_string(),
+            reference: Some("v2".to_string()),
+        };
+
+        // Act
+        let missing_file_result = fetch_git_source(&missing_file, cache_dir.path());
+        let missing_ref_result = fetch_git_source(&missing_ref, cache_dir.path());
+
+        // Assert
+        assert!(matches!(
+            missing_file_result,
+            Err(GitSourceError::FileNotFound { path, .. }) if path == "build.yaml"
+        ));
+        assert!(matches!(
+            missing_ref_result,
+            Err(GitSourceError::Git { .. })
+        ));
+    }
+}
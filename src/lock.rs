@@ -0,0 +1,151 @@
+use crate::config::LockConfig;
+use colored::Colorize;
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+use thiserror::Error;
+
+/// A held lock. The lock is released when this is dropped, even if the command fails.
+pub struct CommandLock {
+    _file: File,
+}
+
+/// Returns the name of the lock described by the provided [`LockConfig`], if any.
+/// Locks which are just enabled are named after the command's path.
+pub fn lock_name(lock_config: &Option<LockConfig>, command_path: &[String]) -> Option<String> {
+    match lock_config {
+        Some(LockConfig::Enabled(true)) => Some(command_path.join(".")),
+        Some(LockConfig::Named(name)) => Some(name.clone()),
+        Some(LockConfig::Enabled(false)) | None => None,
+    }
+}
+
+/// The directory that lock files for the project in `project_directory` are kept in.
+/// Each project gets its own directory so that commands with the same name in different projects
+/// don't block each other.
+pub fn lock_directory(project_directory: &Path) -> PathBuf {
+    // The hash has to stay the same across Rust releases, which DefaultHasher doesn't promise
+    let hash: String = Sha256::digest(project_directory.as_os_str().as_encoded_bytes())[..8]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+
+    let runtime_directory = env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(env::temp_dir);
+
+    runtime_directory.join("dingus").join("locks").join(hash)
+}
+
+/// Acquires the lock named `name` in `lock_directory`.
+/// If the lock is already held, this waits for it to be released when `wait` is `true`, otherwise
+/// it fails straight away.
+pub fn acquire_lock(
+    lock_directory: &Path,
+    name: &str,
+    wait: bool,
+) -> Result<CommandLock, LockError> {
+    fs::create_dir_all(lock_directory).map_err(LockError::Io)?;
+
+    let file_name = format!("{}.lock", name.replace(['/', '\\'], "_"));
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_directory.join(file_name))
+        .map_err(LockError::Io)?;
+
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(fs::TryLockError::WouldBlock) if wait => {
+            let message = format!("Waiting for another run holding the \"{name}\" lock...");
+            eprintln!("{}", message.yellow());
+            file.lock().map_err(LockError::Io)?;
+        }
+        Err(fs::TryLockError::WouldBlock) => {
+            return Err(LockError::Locked {
+                name: name.to_string(),
+            })
+        }
+        Err(fs::TryLockError::Error(err)) => return Err(LockError::Io(err)),
+    }
+
+    Ok(CommandLock { _file: file })
+}
+
+#[derive(Error, Debug)]
+pub enum LockError {
+    #[error("another run is holding the \"{name}\" lock, use --wait to wait for it to finish")]
+    Locked { name: String },
+
+    #[error("failed to acquire lock")]
+    Io(#[source] io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn lock_name_uses_command_path_when_enabled() {
+        // Arrange
+        let command_path = vec!["docker".to_string(), "deploy".to_string()];
+
+        // Act
+        let enabled_name = lock_name(&Some(LockConfig::Enabled(true)), &command_path);
+        let named_name = lock_name(
+            &Some(LockConfig::Named("deployments".to_string())),
+            &command_path,
+        );
+        let disabled_name = lock_name(&Some(LockConfig::Enabled(false)), &command_path);
+
+        // Assert
+        assert_eq!(enabled_name, Some("docker.deploy".to_string()));
+        assert_eq!(named_name, Some("deployments".to_string()));
+        assert_eq!(disabled_name, None);
+    }
+
+    #[test]
+    fn lock_directory_is_named_after_stable_hash_of_project() {
+        // Arrange
+        let project_directory = Path::new("/project");
+
+        // Act
+        let directory = lock_directory(project_directory);
+
+        // Assert
+        assert_eq!(directory.file_name().unwrap(), "ea0135bca5e3bd81");
+    }
+
+    #[test]
+    fn acquire_lock_fails_when_already_held() {
+        // Arrange
+        let temp_dir = TempDir::new().unwrap();
+        let _held_lock = acquire_lock(temp_dir.path(), "deploy", false).unwrap();
+
+        // Act
+        let result = acquire_lock(temp_dir.path(), "deploy", false);
+
+        // Assert
+        assert!(matches!(
+            result,
+            Err(LockError::Locked { name }) if name == "deploy"
+        ));
+    }
+
+    #[test]
+    fn acquire_lock_succeeds_once_released() {
+        // Arrange
+        let temp_dir = TempDir::new().unwrap();
+        let held_lock = acquire_lock(temp_dir.path(), "deploy", false).unwrap();
+        drop(held_lock);
+
+        // Act
+        let result = acquire_lock(temp_dir.path(), "deploy", false);
+
+        // Assert
+        assert!(result.is_ok());
+    }
+}
@@ -1,22 +1,70 @@
-use crate::actions::ActionExecutor;
-use crate::args::ClapArgumentResolver;
-use crate::config::ConfigError;
-use crate::exec::create_command_executor;
-use crate::platform::current_platform_provider;
-use crate::prompt::TerminalPromptExecutor;
-use crate::variables::{RealVariableResolver, VariableResolver};
+use crate::actions::{format_timings, with_extra_args, with_passthrough_args, ActionExecutor};
+use crate::aliases::command_alias_args;
+use crate::ambiguity::{create_candidate_selector, disambiguate};
+use crate::args::{ClapArgumentResolver, EXTRA_ARGS_NAME, PASSTHROUGH_ARGS_NAME};
+use crate::args_file::expand_args_file;
+use crate::cli::{
+    describe_arg_matches, matched_command_path, CHECK_VARS_ARG_NAME, DEBUG_ARGS_ARG_NAME,
+    DUMP_ENV_ARG_NAME, DUMP_PLAN_ARG_NAME, EXPORT_ENV_ARG_NAME, INTERACTIVE_VARS_ARG_NAME,
+    QUIET_ARG_NAME, SINCE_ARG_NAME, TIMINGS_ARG_NAME, WAIT_ARG_NAME, WATCH_ARG_NAME,
+};
+use crate::config::{ActionConfig, CommandConfig, ConfigError, DingusOptions};
+use crate::dependencies::resolve_dependencies;
+use crate::doctor::{doctor_report, is_doctor_requested};
+use crate::example::EXAMPLE_CONFIG;
+use crate::exec::{
+    create_command_executor_with_deadline, create_command_executor_with_search_path, format_env,
+    SearchPath,
+};
+use crate::exit_code::exit_code;
+use crate::lock::{acquire_lock, lock_directory, lock_name};
+use crate::opener::SystemOpener;
+use crate::plan::build_plan;
+use crate::platform::{current_platform_provider, platform_action};
+use crate::profiles::{apply_profile, PROFILE_ARG_NAME};
+use crate::prompt::{is_cancellation, DefaultPromptExecutor, TerminalPromptExecutor};
+use crate::sources::have_sources_changed;
+use crate::values::load_values;
+use crate::variables::{
+    format_exports, remove_unreferenced_lazy_variables, sensitive_variable_names,
+    unexported_variable_names, RealVariableResolver, VariableResolver,
+};
+use crate::watch::watch;
+use crate::workdir::{apply_workdir_anchor, enter_config_directory};
 use anyhow::Result;
-use std::env;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use std::{env, io, process};
 use thiserror::Error;
 
 mod actions;
+mod aliases;
+mod ambiguity;
 mod args;
+mod args_file;
+mod builtins;
 mod cli;
 mod config;
+mod dependencies;
+mod doctor;
+mod example;
 mod exec;
+mod exit_code;
+mod git_source;
+mod lock;
+mod opener;
+mod plan;
 mod platform;
+mod profiles;
 mod prompt;
+mod script;
+mod sources;
+mod update;
+mod values;
 mod variables;
+mod watch;
+mod workdir;
 
 // Ideas:
 // - Preconditions: Specify a list of applications that must be installed, or a custom script that must succeed before running a command
@@ -27,11 +75,57 @@ mod variables;
 // - Include other config files with a remote link
 // - YAML schema.
 
-fn main() -> Result<()> {
-    let config_result = config::load();
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("Error: {err:?}");
+        process::exit(exit_code(&err));
+    }
+}
+
+fn run() -> Result<()> {
+    // Moving to the --from directory first means it affects which config file is found, just as if
+    // dingus had been started there.
+    let args = expand_args_file(env::args().collect())?;
+    if let Some(directory) = cli::from_directory(&args) {
+        env::set_current_dir(&directory).map_err(|err| FromDirectoryError {
+            path: directory.display().to_string(),
+            source: err,
+        })?;
+    }
+
+    let color_choice = cli::color_choice(&args);
+    cli::apply_color_choice(color_choice);
+
+    if cli::dump_schema_yaml(&args) {
+        print!("{EXAMPLE_CONFIG}");
+        return Ok(());
+    }
+
+    if let Some(template_name) = cli::init_template(&args) {
+        let current_directory = env::current_dir()?;
+        let file_name = config::init_with(&current_directory, &template_name)?;
+        println!("created {file_name}");
+        return Ok(());
+    }
+
+    let stdin_is_terminal = cli::stdin_is_terminal(cli::tty_override(&args));
+    let config_result = config::load(cli::trace_config(&args), stdin_is_terminal);
 
     // Offer to create the config file if one doesn't exist
     if let Err(config_err) = config_result {
+        // Doctor helps figure out why the config couldn't be loaded, so it doesn't need one
+        if is_doctor_requested(&args) {
+            for line in doctor_report(
+                Err(&config_err),
+                &DingusOptions::default().bash_path,
+                env::var_os("PATH"),
+            ) {
+                println!("{line}");
+            }
+
+            return Ok(());
+        }
+
         return match config_err {
             ConfigError::FileNotFound => {
                 let should_init = inquire::Confirm::new(
@@ -53,21 +147,54 @@ fn main() -> Result<()> {
     }
 
     let found_config = config_result?;
-    let config = found_config.config;
+    let mut config = found_config.config;
+
+    // Clap can't tell arguments with the same name apart, so this can't wait for `dingus check`
+    config::validate_argument_names(&config)?;
 
     // Change the current working directory to the directory that the config file came from.
-    if let config::Source::File(config_file_path) = found_config.source {
-        if let Some(parent_directory) = config_file_path.parent() {
-            env::set_current_dir(parent_directory)?;
-        }
-    }
+    enter_config_directory(&found_config.source, &config.options)?;
+
+    // Locks are kept per project, before the working directory is moved by any workdir anchor
+    let project_directory = env::current_dir()?;
 
     let platform_provider = current_platform_provider();
 
-    let root_command = cli::create_root_command(&config, &platform_provider);
+    // Commands which share a name are only resolved once the user tries to run one of them, after
+    // which the arguments are parsed again for the picked command.
+    // Aliases for other commands are parsed again as the command they point to.
+    let candidate_selector = create_candidate_selector(stdin_is_terminal);
+    let mut command_args = args.clone();
+    let (root_command, arg_matches) = loop {
+        let root_command =
+            cli::create_root_command(&config, &platform_provider).color(color_choice);
 
-    // This will exit on any match failures
-    let arg_matches = root_command.clone().get_matches();
+        // This will exit on any match failures
+        let arg_matches = root_command.clone().get_matches_from(&command_args);
+
+        let disambiguated = disambiguate(
+            &arg_matches,
+            &mut config.commands,
+            candidate_selector.as_ref(),
+        );
+        if exit_if_cancelled(disambiguated, config.options.cancel_exit_code)? {
+            continue;
+        }
+
+        match command_alias_args(
+            &arg_matches,
+            &command_args,
+            &config.commands,
+            platform_provider.as_ref(),
+        )? {
+            Some(alias_args) => command_args = alias_args,
+            None => break (root_command, arg_matches),
+        }
+    };
+
+    if builtins::execute_builtin(&arg_matches, &config, &found_config.source)? {
+        return Ok(());
+    }
 
     // Otherwise, look for a configured command
     let find_result = cli::find_subcommand(
@@ -77,30 +204,284 @@ fn main() -> Result<()> {
         &config.variables,
     );
 
-    if let Some((target_command, available_variable_configs, sucbommand_arg_matches)) = find_result
+    if let Some((target_command, mut available_variable_configs, sucbommand_arg_matches)) =
+        find_result
     {
-        if let Some(command_action) = target_command.action {
+        if sucbommand_arg_matches.get_flag(DEBUG_ARGS_ARG_NAME) {
+            for line in describe_arg_matches(&sucbommand_arg_matches) {
+                println!("{line}");
+            }
+
+            return Ok(());
+        }
+
+        if let Some(command_action) = &platform_action(&target_command, platform_provider.as_ref())
+        {
+            // Values from the values file have the lowest priority, the configured variables are
+            // layered on top of them.
+            let mut variable_configs =
+                load_values(&config.options.values_file, &env::current_dir()?)?;
+            variable_configs.extend(available_variable_configs);
+            available_variable_configs = variable_configs;
+
+            // Profiles take precedence over the values defined in the config, but values provided
+            // via the command-line still win.
+            if let Ok(Some(profile_name)) =
+                sucbommand_arg_matches.try_get_one::<String>(PROFILE_ARG_NAME)
+            {
+                apply_profile(
+                    profile_name,
+                    &config.profiles,
+                    &mut available_variable_configs,
+                )?;
+            }
+
+            let workdir_anchor = target_command
+                .workdir_anchor
+                .clone()
+                .unwrap_or(config.options.workdir_anchor.clone());
+            apply_workdir_anchor(&workdir_anchor)?;
+
+            // Commands without any sources can't be skipped, we don't know what they depend on.
+            if let Ok(Some(since)) = sucbommand_arg_matches.try_get_one::<String>(SINCE_ARG_NAME) {
+                if !target_command.sources.is_empty()
+                    && !have_sources_changed(&target_command.sources, since)?
+                {
+                    println!("Skipping, no sources have changed since {since}");
+                    return Ok(());
+                }
+            }
+
+            let export_env = sucbommand_arg_matches.get_flag(EXPORT_ENV_ARG_NAME);
+            let dump_env = sucbommand_arg_matches.get_flag(DUMP_ENV_ARG_NAME);
+            let check_vars = sucbommand_arg_matches.get_flag(CHECK_VARS_ARG_NAME);
+            let dump_plan = sucbommand_arg_matches
+                .try_get_one::<String>(DUMP_PLAN_ARG_NAME)
+                .is_ok_and(|format| format.is_some());
+
+            // Variables shouldn't be logged when exporting, the output needs to be eval-able.
+            let mut dingus_options = config.options.clone();
+            if export_env || dump_env || dump_plan {
+                dingus_options.print_variables = false;
+            }
+
+            let dependencies = resolve_dependencies(&target_command, &config.commands)?;
+
+            // Lazy variables are only resolved when something that runs references them, but
+            // exporting the environment or checking the variables needs all of them
+            if !export_env && !dump_env && !check_vars {
+                let dependency_actions: Vec<ActionConfig> = dependencies
+                    .iter()
+                    .filter_map(|dependency| {
+                        platform_action(dependency, platform_provider.as_ref())
+                    })
+                    .collect();
+                let referencing_text = serde_yaml::to_string(&(
+                    command_action,
+                    &dependency_actions,
+                    &config.pre_run,
+                    &config.post_run,
+                    &config.options.on_success,
+                    &config.options.on_failure,
+                    &target_command.path_prepend,
+                    &target_command.path_append,
+                ))?;
+                remove_unreferenced_lazy_variables(
+                    &mut available_variable_configs,
+                    &referencing_text,
+                );
+            }
+
             // Set up the dependencies
+            let unexported_names = unexported_variable_names(&available_variable_configs);
+            let search_path = SearchPath {
+                prepend: target_command.path_prepend.clone(),
+                append: target_command.path_append.clone(),
+            };
             let arg_resolver = ClapArgumentResolver::from_arg_matches(&sucbommand_arg_matches);
             let variable_resolver = RealVariableResolver {
-                command_executor: create_command_executor(&config.options),
-                prompt_executor: Box::new(TerminalPromptExecutor::new(create_command_executor(
+                command_executor: create_command_executor_with_search_path(
                     &config.options,
-                ))),
+                    &config.default_shell,
+                    &unexported_names,
+                    &search_path,
+                ),
+                // Checking the variables shouldn't wait on the user, prompts use their defaults
+                prompt_executor: if check_vars {
+                    Box::new(DefaultPromptExecutor)
+                } else {
+                    Box::new(TerminalPromptExecutor::new(
+                        create_command_executor_with_search_path(
+                            &config.options,
+                            &config.default_shell,
+                            &unexported_names,
+                            &search_path,
+                        ),
+                    ))
+                },
                 argument_resolver: Box::new(arg_resolver),
-                dingus_options: config.options.clone(),
+                dingus_options,
+                interactive: !check_vars
+                    && !dump_plan
+                    && sucbommand_arg_matches.get_flag(INTERACTIVE_VARS_ARG_NAME),
+                // Plans are only a preview, so they shouldn't wait on the user either
+                placeholder_prompts: dump_plan,
             };
 
-            let variables = variable_resolver.resolve_variables(&available_variable_configs)?;
+            if check_vars {
+                let variables = variable_resolver.check_variables(&available_variable_configs)?;
+                println!("{} variable(s) resolved", variables.len());
+                return Ok(());
+            }
+
+            let variables = exit_if_cancelled(
+                variable_resolver.resolve_variables(&available_variable_configs),
+                config.options.cancel_exit_code,
+            )?;
 
-            let action_executor = ActionExecutor {
-                command_executor: create_command_executor(&config.options),
+            // Unexported variables aren't part of the command's environment
+            let mut exported_variables = variables.clone();
+            exported_variables.retain(|name, _| !unexported_names.contains(name));
+
+            if export_env {
+                for line in format_exports(&exported_variables) {
+                    println!("{line}");
+                }
+
+                return Ok(());
+            }
+
+            if dump_env {
+                let sensitive_names = sensitive_variable_names(&available_variable_configs);
+                for line in format_env(&exported_variables, &sensitive_names) {
+                    println!("{line}");
+                }
+
+                return Ok(());
+            }
+
+            // Only the target command receives the passthrough arguments, not its dependencies
+            let command_action =
+                match sucbommand_arg_matches.try_get_many::<String>(PASSTHROUGH_ARGS_NAME) {
+                    Ok(Some(args)) => with_passthrough_args(
+                        command_action,
+                        &args.cloned().collect::<Vec<String>>(),
+                        &config.default_shell,
+                    ),
+                    _ => command_action.clone(),
+                };
+            let command_action =
+                match sucbommand_arg_matches.try_get_many::<String>(EXTRA_ARGS_NAME) {
+                    Ok(Some(args)) => {
+                        with_extra_args(&command_action, &args.cloned().collect::<Vec<String>>())
+                    }
+                    _ => command_action,
+                };
+
+            let command_path = matched_command_path(&arg_matches);
+            if dump_plan {
+                let dependency_actions: Vec<(CommandConfig, ActionConfig)> = dependencies
+                    .iter()
+                    .filter_map(|dependency| {
+                        platform_action(dependency, platform_provider.as_ref())
+                            .map(|dependency_action| (dependency.clone(), dependency_action))
+                    })
+                    .collect();
+                let plan = build_plan(
+                    &config,
+                    &command_path,
+                    &target_command,
+                    &command_action,
+                    &dependency_actions,
+                    &variables,
+                    &sensitive_variable_names(&available_variable_configs),
+                );
+                println!("{}", serde_json::to_string_pretty(&plan)?);
+
+                return Ok(());
+            }
+
+            // Held until the command finishes, the lock is released on drop even if the command fails
+            let _lock = match lock_name(&target_command.lock, &command_path) {
+                Some(name) => Some(acquire_lock(
+                    &lock_directory(&project_directory),
+                    &name,
+                    sucbommand_arg_matches.get_flag(WAIT_ARG_NAME),
+                )?),
+                None => None,
+            };
+
+            let timings = sucbommand_arg_matches
+                .get_flag(TIMINGS_ARG_NAME)
+                .then(|| Rc::new(RefCell::new(Vec::new())));
+            let create_action_executor = |deadline: Option<Instant>| ActionExecutor {
+                command_executor: create_command_executor_with_deadline(
+                    &config.options,
+                    &config.default_shell,
+                    &unexported_names,
+                    &search_path,
+                    deadline,
+                ),
                 arg_resolver: Box::new(ClapArgumentResolver::from_arg_matches(
                     &sucbommand_arg_matches,
                 )),
+                quiet: sucbommand_arg_matches.get_flag(QUIET_ARG_NAME),
+                opener: Box::new(SystemOpener),
+                timings: timings.clone(),
+            };
+            let action_executor = create_action_executor(None);
+
+            let run = || -> Result<()> {
+                let result = action_executor.execute_with_hooks(
+                    &config.pre_run,
+                    &config.post_run,
+                    &variables,
+                    || -> Result<()> {
+                        // Dependencies share the variables resolved for the target command
+                        for dependency in &dependencies {
+                            if let Some(dependency_action) =
+                                &platform_action(dependency, platform_provider.as_ref())
+                            {
+                                action_executor.execute(dependency_action, &variables)?;
+                            }
+                        }
+
+                        // The timeout only bounds the command's own action, so the post_run actions
+                        // still get a chance to clean up after it
+                        let deadline = target_command
+                            .timeout
+                            .map(|timeout| Instant::now() + Duration::from_secs(timeout));
+                        create_action_executor(deadline).execute(&command_action, &variables)?;
+                        Ok(())
+                    },
+                );
+
+                // These run after the post_run actions, and can't change the command's result
+                action_executor.execute_result_hook(
+                    &config.options.on_success,
+                    &config.options.on_failure,
+                    &command_path,
+                    result.as_ref().map_or_else(exit_code, |_| 0),
+                    &variables,
+                );
+
+                if let Some(timings) = &timings {
+                    for line in format_timings(&timings.take()) {
+                        eprintln!("{line}");
+                    }
+                }
+
+                result
             };
 
-            action_executor.execute(&command_action, &variables)?;
+            if sucbommand_arg_matches.get_flag(WATCH_ARG_NAME) {
+                // The working directory may have been moved by a workdir anchor, so the project
+                // directory is watched instead
+                watch(&project_directory, &config.options.watch, run)?;
+            } else {
+                run()?;
+            }
+
             return Ok(());
         }
     }
@@ -108,8 +489,30 @@ fn main() -> Result<()> {
     Err(CommandError::CommandNotFound.into())
 }
 
+/// Exits with `cancel_exit_code` if `result` failed because the user cancelled a prompt, so that
+/// scripts can tell a cancellation apart from a failure.
+fn exit_if_cancelled<T, E>(result: Result<T, E>, cancel_exit_code: i32) -> Result<T, E>
+where
+    E: std::error::Error + 'static,
+{
+    if let Err(err) = &result {
+        if is_cancellation(err) {
+            process::exit(cancel_exit_code);
+        }
+    }
+
+    result
+}
+
 #[derive(Error, Debug, Clone)]
 enum CommandError {
     #[error("could not find a suitable command")]
     CommandNotFound,
 }
+
+#[derive(Error, Debug)]
+#[error("could not run from \"{path}\"")]
+struct FromDirectoryError {
+    path: String,
+    source: io::Error,
+}
@@ -0,0 +1,17 @@
+use mockall::automock;
+use std::io;
+
+#[automock]
+pub trait Opener {
+    /// Opens the provided URL or path with the default application for it.
+    fn open(&self, target: &str) -> io::Result<()>;
+}
+
+/// Opens targets with the operating system's default opener, such as `xdg-open` on Linux.
+pub struct SystemOpener;
+
+impl Opener for SystemOpener {
+    fn open(&self, target: &str) -> io::Result<()> {
+        open::that(target)
+    }
+}
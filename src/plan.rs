@@ -0,0 +1,283 @@
+use crate::aliases::command_alias_text;
+use crate::config::{
+    ActionConfig, CommandConfig, Config, ExecutionConfigVariant, RawCommandConfig,
+    RawCommandConfigVariant, Shell, ShellCommandConfigVariant,
+};
+use crate::script::raw_command_text;
+use crate::variables::{substitute_variables, VariableMap, REDACTED_VALUE};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// The version of the plan's JSON schema.
+/// This is incremented whenever a change is made that existing tools may not understand.
+pub const PLAN_SCHEMA_VERSION: u32 = 1;
+
+/// Everything that would be executed when running a command, without executing any of it.
+#[derive(Serialize, PartialEq, Debug)]
+pub struct Plan {
+    pub schema_version: u32,
+
+    /// The resolved variables, with sensitive values redacted.
+    pub variables: BTreeMap<String, String>,
+
+    /// The steps executed before anything else.
+    pub pre_run: Vec<PlanStep>,
+
+    /// The commands this command depends on, in the order they're executed.
+    pub dependencies: Vec<PlanCommand>,
+
+    /// The command being run.
+    pub command: PlanCommand,
+
+    /// The steps executed after everything else, even if something failed.
+    pub post_run: Vec<PlanStep>,
+}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct PlanCommand {
+    /// The command's path, such as `["db", "migrate"]`.
+    /// This is empty for dependencies, which are identified by their description instead.
+    pub path: Vec<String>,
+    pub description: Option<String>,
+
+    /// Whether the steps are executed in a single shell.
+    pub shared_shell: bool,
+    pub steps: Vec<PlanStep>,
+}
+
+/// A single step, with variables substituted into it.
+#[derive(Serialize, PartialEq, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PlanStep {
+    /// A command executed without a shell.
+    Raw { command: String },
+
+    /// A command executed with the provided shell.
+    Shell { shell: Shell, command: String },
+
+    /// Another program which receives any extra arguments.
+    Alias { alias: String },
+
+    /// A file written with the provided content.
+    WriteFile { path: String },
+
+    /// A URL or file opened with its default application.
+    Open { target: String },
+
+    /// A bash command which fails with the message if it exits with a non-zero exit code.
+    Assert { command: String, message: String },
+}
+
+/// Builds the [`Plan`] for running `action_config` as the command at `command_path`, along with
+/// the `pre_run` and `post_run` actions from the [`Config`].
+/// The values of any variables in `sensitive_variable_names` are redacted, both from the variables
+/// and the steps they're substituted into.
+pub fn build_plan(
+    config: &Config,
+    command_path: &[String],
+    command_config: &CommandConfig,
+    action_config: &ActionConfig,
+    dependencies: &[(CommandConfig, ActionConfig)],
+    variables: &VariableMap,
+    sensitive_variable_names: &[String],
+) -> Plan {
+    let default_shell = &config.default_shell;
+    let variables: VariableMap = variables
+        .iter()
+        .map(|(name, value)| {
+            if sensitive_variable_names.contains(name) {
+                (name.clone(), REDACTED_VALUE.to_string())
+            } else {
+                (name.clone(), value.clone())
+            }
+        })
+        .collect();
+
+    let plan_steps = |execution_configs: &[ExecutionConfigVariant]| -> Vec<PlanStep> {
+        execution_configs
+            .iter()
+            .map(|execution_config| execution_step(execution_config, default_shell, &variables))
+            .collect()
+    };
+
+    let plan_command =
+        |path: Vec<String>, command_config: &CommandConfig, action_config: &ActionConfig| {
+            PlanCommand {
+                path,
+                description: command_config.description.clone(),
+                shared_shell: matches!(
+                    action_config,
+                    ActionConfig::MultiStep(multi_action_config) if multi_action_config.shared_shell
+                ),
+                steps: action_steps(action_config, default_shell, &variables),
+            }
+        };
+
+    Plan {
+        schema_version: PLAN_SCHEMA_VERSION,
+        pre_run: plan_steps(&config.pre_run),
+        dependencies: dependencies
+            .iter()
+            .map(|(dependency, dependency_action)| {
+                plan_command(vec![], dependency, dependency_action)
+            })
+            .collect(),
+        command: plan_command(command_path.to_vec(), command_config, action_config),
+        post_run: plan_steps(&config.post_run),
+        variables: variables
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect(),
+    }
+}
+
+fn action_steps(
+    action_config: &ActionConfig,
+    default_shell: &Option<Shell>,
+    variables: &VariableMap,
+) -> Vec<PlanStep> {
+    match action_config {
+        ActionConfig::SingleStep(single_action_config) => vec![execution_step(
+            &single_action_config.action,
+            default_shell,
+            variables,
+        )],
+        ActionConfig::MultiStep(multi_action_config) => multi_action_config
+            .actions
+            .iter()
+            .map(|execution_config| execution_step(execution_config, default_shell, variables))
+            .collect(),
+        ActionConfig::Alias(alias_action_config) => vec![PlanStep::Alias {
+            alias: substitute_variables(&alias_action_config.alias, variables),
+        }],
+        ActionConfig::CommandAlias(command_alias_action_config) => vec![PlanStep::Alias {
+            alias: command_alias_text(&command_alias_action_config.alias.command),
+        }],
+        ActionConfig::WriteFile(write_file_action_config) => vec![PlanStep::WriteFile {
+            path: substitute_variables(&write_file_action_config.path, variables),
+        }],
+        ActionConfig::Open(open_action_config) => vec![PlanStep::Open {
+            target: substitute_variables(&open_action_config.target, variables),
+        }],
+        ActionConfig::Assert(assert_action_config) => vec![PlanStep::Assert {
+            command: substitute_variables(&assert_action_config.assert.command, variables),
+            message: substitute_variables(&assert_action_config.assert.message, variables),
+        }],
+    }
+}
+
+fn execution_step(
+    execution_config: &ExecutionConfigVariant,
+    default_shell: &Option<Shell>,
+    variables: &VariableMap,
+) -> PlanStep {
+    match execution_config {
+        ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(bash_config)) => {
+            PlanStep::Shell {
+                shell: Shell::Bash,
+                command: substitute_variables(&bash_config.command, variables),
+            }
+        }
+        ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(command)) => {
+            let command = substitute_variables(command, variables);
+            match default_shell {
+                Some(shell) => PlanStep::Shell {
+                    shell: shell.clone(),
+                    command,
+                },
+                None => PlanStep::Raw { command },
+            }
+        }
+        ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::RawCommandConfig(
+            raw_config,
+        )) => PlanStep::Raw {
+            // The extra arguments are passed as-is, without variables substituted into them
+            command: raw_command_text(&RawCommandConfig {
+                command: substitute_variables(&raw_config.command, variables),
+                ..raw_config.clone()
+            }),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{parse_config, Platform};
+
+    #[test]
+    fn build_plan_lists_steps_and_redacts_sensitive_variables() {
+        // Arrange
+        let config = parse_config(
+            &"pre_run:
+  - echo Starting
+commands:
+  build:
+    description: Build the app
+    action: cargo build
+  deploy:
+    description: Deploy the app
+    actions:
+      - bash: ./deploy.sh --token $token
+      - echo Deployed $version
+"
+            .to_string(),
+            Platform::Linux,
+        )
+        .unwrap();
+        let build_command = config.commands.get("build").unwrap();
+        let deploy_command = config.commands.get("deploy").unwrap();
+        let variables = VariableMap::from([
+            ("token".to_string(), "secret".to_string()),
+            ("version".to_string(), "1.2.3".to_string()),
+        ]);
+
+        // Act
+        let plan = build_plan(
+            &config,
+            &["deploy".to_string()],
+            deploy_command,
+            deploy_command.action.as_ref().unwrap(),
+            &[(build_command.clone(), build_command.action.clone().unwrap())],
+            &variables,
+            &["token".to_string()],
+        );
+
+        // Assert
+        assert_eq!(
+            serde_json::to_value(&plan).unwrap(),
+            serde_json::json!({
+                "schema_version": PLAN_SCHEMA_VERSION,
+                "variables": {
+                    "token": REDACTED_VALUE,
+                    "version": "1.2.3",
+                },
+                "pre_run": [
+                    { "type": "raw", "command": "echo Starting" },
+                ],
+                "dependencies": [
+                    {
+                        "path": [],
+                        "description": "Build the app",
+                        "shared_shell": false,
+                        "steps": [{ "type": "raw", "command": "cargo build" }],
+                    },
+                ],
+                "command": {
+                    "path": ["deploy"],
+                    "description": "Deploy the app",
+                    "shared_shell": false,
+                    "steps": [
+                        {
+                            "type": "shell",
+                            "shell": "bash",
+                            "command": format!("./deploy.sh --token {REDACTED_VALUE}"),
+                        },
+                        { "type": "raw", "command": "echo Deployed 1.2.3" },
+                    ],
+                },
+                "post_run": [],
+            })
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use crate::config::{OneOrManyPlatforms, Platform};
+use crate::config::{ActionConfig, CommandConfig, OneOrManyPlatforms, Platform, PlatformFilter};
 use mockall::automock;
 use std::env;
 
@@ -11,11 +11,42 @@ pub fn is_current_platform(
     platform_or_platforms: &OneOrManyPlatforms,
 ) -> bool {
     match platform_or_platforms {
-        OneOrManyPlatforms::One(platform) => platform.platform == current_platform,
-        OneOrManyPlatforms::Many(platforms) => platforms.platforms.contains(&current_platform),
+        OneOrManyPlatforms::One(platform) => platform.platform.includes(&current_platform),
+        OneOrManyPlatforms::Many(platforms) => platforms
+            .platforms
+            .iter()
+            .any(|platform| platform.includes(&current_platform)),
     }
 }
 
+/// Returns the [`ActionConfig`] that the provided [`CommandConfig`] should perform on the current
+/// platform, falling back to an action for the current platform's family, then its `action` if
+/// there isn't one specific to the current platform.
+pub fn platform_action(
+    command_config: &CommandConfig,
+    platform_provider: &dyn PlatformProvider,
+) -> Option<ActionConfig> {
+    if !command_config.platform_actions.is_empty() {
+        let current_platform = platform_provider.get_platform();
+        if let Some(action) = command_config
+            .platform_actions
+            .get(&PlatformFilter::from(current_platform.clone()))
+        {
+            return Some(action.clone());
+        }
+
+        if let Some((_, action)) = command_config
+            .platform_actions
+            .iter()
+            .find(|(platform, _)| platform.includes(&current_platform))
+        {
+            return Some(action.clone());
+        }
+    }
+
+    command_config.action.clone()
+}
+
 #[automock]
 pub trait PlatformProvider {
     fn get_platform(&self) -> Platform;
@@ -32,3 +63,89 @@ impl PlatformProvider for RealPlatformProvider {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RawCommandConfigVariant::Shorthand;
+    use crate::config::{ExecutionConfigVariant, PlatformFamily, SingleActionConfig};
+    use linked_hash_map::LinkedHashMap;
+
+    fn action(command: &str) -> ActionConfig {
+        ActionConfig::SingleStep(SingleActionConfig {
+            action: ExecutionConfigVariant::RawCommand(Shorthand(command.to_string())),
+        })
+    }
+
+    fn platform_provider(platform: Platform) -> Box<dyn PlatformProvider> {
+        let mut platform_provider = MockPlatformProvider::new();
+        platform_provider
+            .expect_get_platform()
+            .return_const(platform);
+
+        Box::new(platform_provider)
+    }
+
+    fn command_config() -> CommandConfig {
+        let mut platform_actions = LinkedHashMap::new();
+        platform_actions.insert(Platform::Linux.into(), action("./build.sh"));
+        platform_actions.insert(Platform::Windows.into(), action("./build.ps1"));
+
+        CommandConfig {
+            action: Some(action("make")),
+            platform_actions,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn platform_action_selects_action_for_current_platform() {
+        // Arrange
+        let command_config = command_config();
+
+        // Act
+        let linux_action =
+            platform_action(&command_config, platform_provider(Platform::Linux).as_ref());
+        let windows_action = platform_action(
+            &command_config,
+            platform_provider(Platform::Windows).as_ref(),
+        );
+
+        // Assert
+        assert_eq!(linux_action, Some(action("./build.sh")));
+        assert_eq!(windows_action, Some(action("./build.ps1")));
+    }
+
+    #[test]
+    fn platform_action_falls_back_to_action() {
+        // Arrange
+        let command_config = command_config();
+
+        // Act
+        let macos_action =
+            platform_action(&command_config, platform_provider(Platform::MacOS).as_ref());
+
+        // Assert
+        assert_eq!(macos_action, Some(action("make")));
+    }
+
+    #[test]
+    fn platform_action_falls_back_to_family_action() {
+        // Arrange
+        let mut command_config = command_config();
+        command_config.platform_actions.insert(
+            PlatformFilter::Family(PlatformFamily::Unix),
+            action("./build-unix.sh"),
+        );
+
+        // Act
+        let linux_action =
+            platform_action(&command_config, platform_provider(Platform::Linux).as_ref());
+        let macos_action =
+            platform_action(&command_config, platform_provider(Platform::MacOS).as_ref());
+
+        // Assert
+        assert_eq!(linux_action, Some(action("./build.sh")));
+        assert_eq!(macos_action, Some(action("./build-unix.sh")));
+    }
+}
@@ -0,0 +1,124 @@
+use crate::config::{ProfileConfigMap, VariableConfig, VariableConfigMap};
+use crate::variables::apply_overrides;
+use clap::Arg;
+use thiserror::Error;
+
+pub const PROFILE_ARG_NAME: &str = "PROFILE";
+
+/// Creates the global `--profile` [`Arg`] used to select a profile.
+pub fn create_profile_arg(profiles: &ProfileConfigMap) -> Arg {
+    let profile_names: Vec<String> = profiles.keys().cloned().collect();
+
+    Arg::new(PROFILE_ARG_NAME)
+        .long("profile")
+        .global(true)
+        .help(format!(
+            "The profile to use for variable values. [possible values: {}]",
+            profile_names.join(", ")
+        ))
+}
+
+/// Applies the variable values from the profile named `profile_name` to `variable_configs`.
+/// Variables that only exist in the profile are added as literal variables.
+pub fn apply_profile(
+    profile_name: &str,
+    profiles: &ProfileConfigMap,
+    variable_configs: &mut VariableConfigMap,
+) -> Result<(), ProfileError> {
+    let profile = profiles
+        .get(profile_name)
+        .ok_or_else(|| ProfileError::NotFound {
+            name: profile_name.to_string(),
+            available: profiles.keys().cloned().collect::<Vec<String>>().join(", "),
+        })?;
+
+    apply_overrides(variable_configs, &profile.variables);
+
+    for (key, value) in profile.variables.iter() {
+        if !variable_configs.contains_key(key) {
+            variable_configs.insert(key.clone(), VariableConfig::ShorthandLiteral(value.clone()));
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Error, Debug)]
+pub enum ProfileError {
+    #[error("could not find profile \"{name}\", available profiles are: {available}")]
+    NotFound { name: String, available: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ProfileConfig, VariableOverrideMap};
+
+    fn profiles() -> ProfileConfigMap {
+        let mut prod_variables = VariableOverrideMap::new();
+        prod_variables.insert("region".to_string(), "eu-west-1".to_string());
+        prod_variables.insert("replicas".to_string(), "3".to_string());
+
+        let mut profiles = ProfileConfigMap::new();
+        profiles.insert("dev".to_string(), ProfileConfig::default());
+        profiles.insert(
+            "prod".to_string(),
+            ProfileConfig {
+                variables: prod_variables,
+            },
+        );
+        profiles
+    }
+
+    #[test]
+    fn apply_profile_overrides_variable_values() {
+        // Arrange
+        let mut variable_configs = VariableConfigMap::new();
+        variable_configs.insert(
+            "region".to_string(),
+            VariableConfig::ShorthandLiteral("us-east-1".to_string()),
+        );
+
+        // Act
+        let result = apply_profile("prod", &profiles(), &mut variable_configs);
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(
+            variable_configs.get("region"),
+            Some(&VariableConfig::ShorthandLiteral("eu-west-1".to_string()))
+        );
+    }
+
+    #[test]
+    fn apply_profile_adds_variables_missing_from_config() {
+        // Arrange
+        let mut variable_configs = VariableConfigMap::new();
+
+        // Act
+        let result = apply_profile("prod", &profiles(), &mut variable_configs);
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(
+            variable_configs.get("replicas"),
+            Some(&VariableConfig::ShorthandLiteral("3".to_string()))
+        );
+    }
+
+    #[test]
+    fn apply_profile_fails_for_unknown_profile() {
+        // Arrange
+        let mut variable_configs = VariableConfigMap::new();
+
+        // Act
+        let result = apply_profile("staging", &profiles(), &mut variable_configs);
+
+        // Assert
+        assert!(matches!(
+            result,
+            Err(ProfileError::NotFound { name, available })
+                if name == "staging" && available == "dev, prod"
+        ));
+    }
+}
@@ -1,8 +1,10 @@
 use crate::config::{
-    PromptConfig, PromptOptionsVariant, SelectOptionsConfig, SelectPromptOptions, TextPromptOptions,
+    AutocompletePromptOptions, PromptConfig, PromptOptionsVariant, SelectOptionsConfig,
+    SelectPromptOptions, TextPromptOptions,
 };
 use crate::exec::{CommandExecutor, ExecutionError};
-use inquire::{InquireError, Password, PasswordDisplayMode, Select, Text};
+use inquire::validator::Validation;
+use inquire::{CustomUserError, InquireError, Password, PasswordDisplayMode, Select, Text};
 use mockall::automock;
 use std::collections::HashMap;
 use std::string::FromUtf8Error;
@@ -18,6 +20,29 @@ pub enum PromptError {
 
     #[error("failed to parse prompt options")]
     ParseError(#[source] FromUtf8Error),
+
+    #[error("failed to parse prompt options as a JSON array of strings")]
+    JsonError(#[source] serde_json::Error),
+
+    #[error("\"{message}\" has no default value to use without prompting")]
+    NoDefault { message: String },
+}
+
+/// Whether the provided error, or any of its sources, was caused by the user cancelling a prompt.
+pub fn is_cancellation(err: &(dyn std::error::Error + 'static)) -> bool {
+    let mut current_err = Some(err);
+    while let Some(err) = current_err {
+        if matches!(
+            err.downcast_ref::<InquireError>(),
+            Some(InquireError::OperationCanceled | InquireError::OperationInterrupted)
+        ) {
+            return true;
+        }
+
+        current_err = err.source();
+    }
+
+    false
 }
 
 #[automock]
@@ -39,29 +64,95 @@ impl TerminalPromptExecutor {
 impl PromptExecutor for TerminalPromptExecutor {
     fn execute(&self, prompt_config: &PromptConfig) -> Result<String, PromptError> {
         match prompt_config.clone().options {
-            PromptOptionsVariant::Text(text_prompt_options) => {
-                execute_text_prompt(prompt_config.message.as_str(), &text_prompt_options)
-            }
+            PromptOptionsVariant::Text(text_prompt_options) => execute_text_prompt(
+                prompt_config.message.as_str(),
+                prompt_config.help.as_deref(),
+                &text_prompt_options,
+            ),
             PromptOptionsVariant::Select(select_prompt_config) => execute_select_prompt(
                 prompt_config.message.as_str(),
+                prompt_config.help.as_deref(),
                 &select_prompt_config,
                 &self.command_executor,
             ),
+            PromptOptionsVariant::Autocomplete(autocomplete_prompt_options) => {
+                let suggestions = get_options(
+                    &autocomplete_prompt_options.suggestions,
+                    &self.command_executor,
+                )?;
+
+                execute_autocomplete_prompt(
+                    prompt_config.message.as_str(),
+                    prompt_config.help.as_deref(),
+                    &autocomplete_prompt_options,
+                    suggestions,
+                )
+            }
+        }
+    }
+}
+
+/// Answers prompts with their default value without asking the user, failing for prompts that
+/// don't have one.
+pub struct DefaultPromptExecutor;
+
+impl PromptExecutor for DefaultPromptExecutor {
+    fn execute(&self, prompt_config: &PromptConfig) -> Result<String, PromptError> {
+        match &prompt_config.options {
+            PromptOptionsVariant::Text(TextPromptOptions {
+                default: Some(default),
+                ..
+            }) => Ok(default.clone()),
+            _ => Err(PromptError::NoDefault {
+                message: prompt_config.message.clone(),
+            }),
         }
     }
 }
 
 fn execute_text_prompt(
     message: &str,
+    help: Option<&str>,
     text_prompt_options: &TextPromptOptions,
 ) -> Result<String, PromptError> {
+    let min_length = text_prompt_options.min_length;
+    let max_length = text_prompt_options.max_length;
+    let has_default = text_prompt_options.default.is_some();
+    let length_validator = move |input: &str| -> Result<Validation, CustomUserError> {
+        // Empty input will be replaced with the default value
+        if input.is_empty() && has_default {
+            return Ok(Validation::Valid);
+        }
+
+        Ok(validate_length(input, min_length, max_length))
+    };
+
     let result = if text_prompt_options.sensitive {
-        Password::new(message)
+        // Password prompts don't support default values, so empty input is replaced manually.
+        let mut password = Password::new(message)
             .with_display_mode(PasswordDisplayMode::Masked)
             .without_confirmation()
+            .with_validator(length_validator);
+        if let Some(help) = help {
+            password = password.with_help_message(help);
+        }
+
+        password
             .prompt()
+            .map(|value| match &text_prompt_options.default {
+                Some(default) if value.is_empty() => default.clone(),
+                _ => value,
+            })
     } else {
-        Text::new(message).prompt()
+        let mut text = Text::new(message).with_validator(length_validator);
+        if let Some(default) = &text_prompt_options.default {
+            text = text.with_default(default);
+        }
+        if let Some(help) = help {
+            text = text.with_help_message(help);
+        }
+
+        text.prompt()
     };
 
     match result {
@@ -70,20 +161,100 @@ fn execute_text_prompt(
     }
 }
 
+/// Validates that the number of characters in `input` is within the provided bounds.
+fn validate_length(
+    input: &str,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+) -> Validation {
+    let length = input.chars().count();
+
+    if let Some(min_length) = min_length {
+        if length < min_length {
+            return Validation::Invalid(
+                format!("Must be at least {min_length} characters long.").into(),
+            );
+        }
+    }
+
+    if let Some(max_length) = max_length {
+        if length > max_length {
+            return Validation::Invalid(
+                format!("Must be at most {max_length} characters long.").into(),
+            );
+        }
+    }
+
+    Validation::Valid
+}
+
 fn execute_select_prompt(
     message: &str,
+    help: Option<&str>,
     select_prompt_options: &SelectPromptOptions,
     command_executor: &Box<dyn CommandExecutor>,
 ) -> Result<String, PromptError> {
     let options = get_options(&select_prompt_options.options, command_executor)?;
-    let result = Select::new(message, options).prompt();
+    let mut select = Select::new(message, options);
+    if let Some(help) = help {
+        select = select.with_help_message(help);
+    }
+
+    let result = select.prompt();
     match result {
         Ok(value) => Ok(value),
         Err(err) => Err(PromptError::InquireError(err)),
     }
 }
 
-fn get_options(
+fn execute_autocomplete_prompt(
+    message: &str,
+    help: Option<&str>,
+    autocomplete_prompt_options: &AutocompletePromptOptions,
+    suggestions: Vec<String>,
+) -> Result<String, PromptError> {
+    let autocomplete_suggestions = suggestions.clone();
+    let mut prompt = Text::new(message).with_autocomplete(
+        move |input: &str| -> Result<Vec<String>, CustomUserError> {
+            Ok(filter_suggestions(&autocomplete_suggestions, input))
+        },
+    );
+
+    if let Some(help) = help {
+        prompt = prompt.with_help_message(help);
+    }
+
+    if autocomplete_prompt_options.restrict_to_suggestions {
+        prompt = prompt.with_validator(move |input: &str| -> Result<Validation, CustomUserError> {
+            if suggestions.iter().any(|suggestion| suggestion == input) {
+                Ok(Validation::Valid)
+            } else {
+                Ok(Validation::Invalid(
+                    "Please choose one of the suggestions.".into(),
+                ))
+            }
+        });
+    }
+
+    match prompt.prompt() {
+        Ok(value) => Ok(value),
+        Err(err) => Err(PromptError::InquireError(err)),
+    }
+}
+
+/// Returns the suggestions which contain `input`, ignoring case.
+fn filter_suggestions(suggestions: &[String], input: &str) -> Vec<String> {
+    let input = input.to_lowercase();
+    suggestions
+        .iter()
+        .filter(|suggestion| suggestion.to_lowercase().contains(&input))
+        .cloned()
+        .collect()
+}
+
+/// Returns the options from the provided [`SelectOptionsConfig`], executing its command if it has
+/// one.
+pub fn get_options(
     select_options_config: &SelectOptionsConfig,
     command_executor: &Box<dyn CommandExecutor>,
 ) -> Result<Vec<String>, PromptError> {
@@ -93,8 +264,13 @@ fn get_options(
             let output = command_executor
                 .get_output(&execution_config.execution, &HashMap::new())
                 .map_err(|err| PromptError::ExecutionError(err))?;
-            let stdout =
-                String::from_utf8(output.stdout).map_err(|err| PromptError::ParseError(err))?;
+            let stdout = output
+                .stdout_string(&execution_config.encoding)
+                .map_err(|err| PromptError::ParseError(err))?;
+            if execution_config.json {
+                return serde_json::from_str(&stdout).map_err(PromptError::JsonError);
+            }
+
             let options = stdout.clone().lines().map(|s| String::from(s)).collect();
             Ok(options)
         }
@@ -102,3 +278,140 @@ fn get_options(
 }
 
 // This is hard to write tests for. Fow now, let's assume the Inquire crate has sufficient tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{
+        Encoding, ExecutionConfigVariant, ExecutionSelectOptionsConfig, RawCommandConfigVariant,
+    };
+    use crate::exec::{ExitStatus, MockCommandExecutor, Output};
+
+    #[test]
+    fn default_prompt_executor_uses_default_value() {
+        // Arrange
+        let prompt_config = PromptConfig {
+            message: "What's your name?".to_string(),
+            options: PromptOptionsVariant::Text(TextPromptOptions {
+                multi_line: false,
+                sensitive: false,
+                min_length: None,
+                max_length: None,
+                default: Some("Dingus".to_string()),
+            }),
+            help: None,
+        };
+        let required_prompt_config = PromptConfig {
+            options: PromptOptionsVariant::Text(TextPromptOptions {
+                multi_line: false,
+                sensitive: false,
+                min_length: None,
+                max_length: None,
+                default: None,
+            }),
+            ..prompt_config.clone()
+        };
+
+        // Act
+        let value = DefaultPromptExecutor.execute(&prompt_config);
+        let required_result = DefaultPromptExecutor.execute(&required_prompt_config);
+
+        // Assert
+        assert_eq!(value.unwrap(), "Dingus");
+        assert!(matches!(
+            required_result,
+            Err(PromptError::NoDefault { message }) if message == "What's your name?"
+        ));
+    }
+
+    #[test]
+    fn validate_length_accepts_input_within_bounds() {
+        // Act
+        let validation = validate_length("dingus", Some(3), Some(6));
+
+        // Assert
+        assert_eq!(validation, Validation::Valid);
+    }
+
+    #[test]
+    fn validate_length_rejects_short_input() {
+        // Act
+        let validation = validate_length("hi", Some(3), None);
+
+        // Assert
+        assert!(matches!(validation, Validation::Invalid(_)));
+    }
+
+    #[test]
+    fn validate_length_rejects_long_input() {
+        // Act
+        let validation = validate_length("dingus", None, Some(5));
+
+        // Assert
+        assert!(matches!(validation, Validation::Invalid(_)));
+    }
+
+    #[test]
+    fn is_cancellation_detects_cancelled_prompts() {
+        // Arrange
+        let cancelled = PromptError::InquireError(InquireError::OperationCanceled);
+        let failed = PromptError::InquireError(InquireError::NotTTY);
+
+        // Act
+        let is_cancelled = is_cancellation(&cancelled);
+        let is_failed_cancelled = is_cancellation(&failed);
+
+        // Assert
+        assert!(is_cancelled);
+        assert!(!is_failed_cancelled);
+    }
+
+    #[test]
+    fn validate_length_counts_characters() {
+        // Act
+        let validation = validate_length("ñandú", None, Some(5));
+
+        // Assert
+        assert_eq!(validation, Validation::Valid);
+    }
+
+    fn json_select_options(stdout: &'static str) -> Result<Vec<String>, PromptError> {
+        let mut command_executor = MockCommandExecutor::new();
+        command_executor.expect_get_output().returning(move |_, _| {
+            Ok(Output {
+                status: ExitStatus::Success,
+                stdout: stdout.as_bytes().to_vec(),
+                stderr: vec![],
+            })
+        });
+        let command_executor: Box<dyn CommandExecutor> = Box::new(command_executor);
+
+        let select_options_config = SelectOptionsConfig::Execution(ExecutionSelectOptionsConfig {
+            execution: ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(
+                "list-environments --json".to_string(),
+            )),
+            encoding: Encoding::Strict,
+            json: true,
+        });
+
+        get_options(&select_options_config, &command_executor)
+    }
+
+    #[test]
+    fn get_options_parses_json_array() {
+        // Act
+        let options = json_select_options("[\"a\",\"b\"]\n").unwrap();
+
+        // Assert
+        assert_eq!(options, ["a", "b"]);
+    }
+
+    #[test]
+    fn get_options_fails_for_invalid_json() {
+        // Act
+        let result = json_select_options("a\nb\n");
+
+        // Assert
+        assert!(matches!(result, Err(PromptError::JsonError(_))));
+    }
+}
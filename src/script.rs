@@ -0,0 +1,397 @@
+use crate::aliases::command_alias_text;
+use crate::config::{
+    ActionConfig, CommandConfig, ExecutionConfigVariant, RandomConfig, RandomKind,
+    RawCommandConfig, RawCommandConfigVariant, ShellCommandConfigVariant, StdinConfig,
+    VariableConfig, VariableConfigMap,
+};
+use crate::variables::{is_variable_sensitive, shell_quote, DEFAULT_RANDOM_HEX_LENGTH};
+
+/// Generates a standalone bash script which exports the provided variables, then executes the
+/// actions of each dependency followed by the actions of the provided [`CommandConfig`].
+/// Values which can't be known ahead of time, such as sensitive prompts, are replaced with
+/// placeholders.
+pub fn generate_script(
+    command_config: &CommandConfig,
+    variable_configs: &VariableConfigMap,
+    dependencies: &[CommandConfig],
+) -> String {
+    let mut lines = vec!["#!/usr/bin/env bash".to_string(), "set -e".to_string()];
+
+    if !variable_configs.is_empty() {
+        lines.push(String::new());
+    }
+
+    for (key, variable_config) in variable_configs.iter() {
+        let name = variable_config.environment_variable_name(key);
+        lines.extend(variable_lines(&name, variable_config));
+    }
+
+    for action_config in dependencies
+        .iter()
+        .chain(std::iter::once(command_config))
+        .filter_map(|command_config| command_config.action.as_ref())
+    {
+        lines.push(String::new());
+        lines.extend(action_lines(action_config));
+    }
+
+    lines.join("\n") + "\n"
+}
+
+fn variable_lines(name: &str, variable_config: &VariableConfig) -> Vec<String> {
+    if is_variable_sensitive(variable_config) {
+        return vec![format!("export {name}={}", placeholder(name))];
+    }
+
+    match variable_config {
+        VariableConfig::ShorthandLiteral(value) => {
+            vec![format!("export {name}={}", shell_quote(value))]
+        }
+        VariableConfig::Literal(literal_conf) => {
+            vec![format!(
+                "export {name}={}",
+                shell_quote(&literal_conf.value)
+            )]
+        }
+        VariableConfig::Execution(execution_conf) => vec![format!(
+            "export {name}=\"$({})\"",
+            command_text(&execution_conf.execution)
+        )],
+        VariableConfig::Prompt(prompt_conf) => vec![
+            format!(
+                "read -r -p {} {name}",
+                shell_quote(&format!("{} ", prompt_conf.prompt.message))
+            ),
+            format!("export {name}"),
+        ],
+        VariableConfig::Random(random_conf) => vec![format!(
+            "export {name}=\"$({})\"",
+            random_command_text(&random_conf.random)
+        )],
+        VariableConfig::File(file_conf) => {
+            vec![format!("export {name}=\"$(cat \"{}\")\"", file_conf.path)]
+        }
+        VariableConfig::Argument(_) => vec![format!("export {name}={}", placeholder(name))],
+    }
+}
+
+/// A line of bash which prints a random value generated like the provided [`RandomConfig`].
+fn random_command_text(random_config: &RandomConfig) -> String {
+    match random_config.kind {
+        // Not every Linux distribution has uuidgen, and macOS doesn't have /proc
+        RandomKind::Uuid => {
+            "cat /proc/sys/kernel/random/uuid 2>/dev/null || uuidgen | tr '[:upper:]' '[:lower:]'"
+                .to_string()
+        }
+        RandomKind::Hex => {
+            let length = random_config.length.unwrap_or(DEFAULT_RANDOM_HEX_LENGTH);
+            format!(
+                "od -An -N{} -tx1 /dev/urandom | tr -d ' \\n' | cut -c1-{length}",
+                length.div_ceil(2)
+            )
+        }
+    }
+}
+
+fn action_lines(action_config: &ActionConfig) -> Vec<String> {
+    match action_config {
+        ActionConfig::SingleStep(single_action_config) => {
+            vec![command_text(&single_action_config.action)]
+        }
+        ActionConfig::MultiStep(multi_action_config) => multi_action_config
+            .actions
+            .iter()
+            .map(command_text)
+            .collect(),
+        ActionConfig::Alias(alias_action_config) => {
+            vec![format!("{} \"$@\"", alias_action_config.alias)]
+        }
+        ActionConfig::CommandAlias(command_alias_action_config) => vec![format!(
+            "{} \"$@\"",
+            command_alias_text(&command_alias_action_config.alias.command)
+        )],
+        ActionConfig::WriteFile(write_file_action_config) => vec![
+            format!(
+                "mkdir -p \"$(dirname \"{}\")\"",
+                write_file_action_config.path
+            ),
+            format!("cat > \"{}\" <<DINGUS_EOF", write_file_action_config.path),
+            write_file_action_config
+                .content
+                .strip_suffix('\n')
+                .unwrap_or(&write_file_action_config.content)
+                .to_string(),
+            "DINGUS_EOF".to_string(),
+        ],
+        ActionConfig::Open(open_action_config) => vec![format!(
+            "\"$(command -v xdg-open || echo open)\" \"{}\"",
+            open_action_config.target
+        )],
+        ActionConfig::Assert(assert_action_config) => vec![
+            format!("if ! {}; then", assert_action_config.assert.command),
+            format!(
+                "    echo \"assertion failed: {}\" >&2",
+                assert_action_config.assert.message
+            ),
+            "    exit 1".to_string(),
+            "fi".to_string(),
+        ],
+    }
+}
+
+/// Converts the provided [`ExecutionConfigVariant`] into a line of bash.
+pub fn command_text(execution_config: &ExecutionConfigVariant) -> String {
+    let (command, working_directory, run_as, stdin, stdout_file, stderr_file, append) =
+        match execution_config {
+            ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(bash_config)) => (
+                match &bash_config.run_as {
+                    Some(_) => format!("bash -c {}", shell_quote(&bash_config.command)),
+                    None => bash_config.command.clone(),
+                },
+                &bash_config.working_directory,
+                &bash_config.run_as,
+                &bash_config.stdin,
+                &bash_config.stdout_file,
+                &bash_config.stderr_file,
+                bash_config.append,
+            ),
+            ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(command)) => (
+                command.clone(),
+                &None,
+                &None,
+                &StdinConfig::Inherit,
+                &None,
+                &None,
+                false,
+            ),
+            ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::RawCommandConfig(
+                raw_config,
+            )) => (
+                raw_command_text(raw_config),
+                &raw_config.working_directory,
+                &raw_config.run_as,
+                &raw_config.stdin,
+                &raw_config.stdout_file,
+                &raw_config.stderr_file,
+                raw_config.append,
+            ),
+        };
+
+    let command = match run_as {
+        Some(user) => format!("sudo -u {user} --preserve-env -- {command}"),
+        None => command,
+    };
+
+    let command = match working_directory {
+        Some(working_directory) => format!("(cd \"{working_directory}\" && {command})"),
+        None => command,
+    };
+
+    // Output files are relative to where dingus was run, so redirect outside of any `cd`
+    let operator = if append { ">>" } else { ">" };
+    let mut redirections = match stdin {
+        StdinConfig::Inherit => vec![],
+        StdinConfig::Null => vec!["< /dev/null".to_string()],
+        StdinConfig::Text(text) => vec![format!("<<< \"{text}\"")],
+        StdinConfig::File(file) => vec![format!("< \"{file}\"")],
+    };
+    if let Some(stdout_file) = stdout_file {
+        redirections.push(format!("{operator} \"{stdout_file}\""));
+    }
+    if let Some(stderr_file) = stderr_file {
+        redirections.push(format!("2{operator} \"{stderr_file}\""));
+    }
+
+    if redirections.is_empty() {
+        command
+    } else {
+        format!("{{ {}\n}} {}", command.trim_end(), redirections.join(" "))
+    }
+}
+
+/// The command of the provided [`RawCommandConfig`], followed by its quoted extra arguments.
+pub fn raw_command_text(raw_config: &RawCommandConfig) -> String {
+    let quoted_args = raw_config.extra_args.iter().map(|arg| shell_quote(arg));
+    std::iter::once(raw_config.command.clone())
+        .chain(quoted_args)
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn placeholder(name: &str) -> String {
+    shell_quote(&format!("<{name}>"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{
+        BashCommandConfig, ExecutionVariableConfig, MultiActionConfig, PromptConfig,
+        PromptOptionsVariant, PromptVariableConfig, RawCommandConfig, SingleActionConfig,
+        TextPromptOptions, Trim,
+    };
+
+    #[test]
+    fn command_text_redirects_output() {
+        // Arrange
+        let execution_config = ExecutionConfigVariant::RawCommand(
+            RawCommandConfigVariant::RawCommandConfig(RawCommandConfig {
+                working_directory: Some("./server".to_string()),
+                command: "./server".to_string(),
+                run_as: None,
+                template: Default::default(),
+                stdout_file: Some("logs/server.log".to_string()),
+                stderr_file: Some("logs/server.err".to_string()),
+                append: true,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
+            }),
+        );
+
+        // Act
+        let text = command_text(&execution_config);
+
+        // Assert
+        assert_eq!(
+            text,
+            "{ (cd \"./server\" && ./server)\n} >> \"logs/server.log\" 2>> \"logs/server.err\""
+        );
+    }
+
+    #[test]
+    fn command_text_redirects_stdin() {
+        // Arrange
+        let execution_config = ExecutionConfigVariant::RawCommand(
+            RawCommandConfigVariant::RawCommandConfig(RawCommandConfig {
+                working_directory: None,
+                command: "./seed.sh".to_string(),
+                run_as: None,
+                template: Default::default(),
+                stdout_file: Some("seed.log".to_string()),
+                stderr_file: None,
+                append: false,
+                banner: None,
+                stdin: StdinConfig::File("./seed.sql".to_string()),
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
+            }),
+        );
+
+        // Act
+        let text = command_text(&execution_config);
+
+        // Assert
+        assert_eq!(text, "{ ./seed.sh\n} < \"./seed.sql\" > \"seed.log\"");
+    }
+
+    #[test]
+    fn generate_script_contains_exports_and_commands() {
+        // Arrange
+        let mut variable_configs = VariableConfigMap::new();
+        variable_configs.insert(
+            "name".to_string(),
+            VariableConfig::ShorthandLiteral("Dingus".to_string()),
+        );
+        variable_configs.insert(
+            "version".to_string(),
+            VariableConfig::Execution(ExecutionVariableConfig {
+                argument: None,
+                environment_variable_name: Some("VERSION".to_string()),
+                execution: ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(
+                    "cat VERSION".to_string(),
+                )),
+                encoding: Default::default(),
+                trim: Trim::End,
+                prompt_if_missing: None,
+                transform: vec![],
+                export: true,
+                r#match: None,
+                group: None,
+                lazy: false,
+                sensitive: false,
+            }),
+        );
+        variable_configs.insert(
+            "password".to_string(),
+            VariableConfig::Prompt(PromptVariableConfig {
+                argument: None,
+                environment_variable_name: None,
+                prompt: PromptConfig {
+                    message: "Password?".to_string(),
+                    options: PromptOptionsVariant::Text(TextPromptOptions {
+                        multi_line: false,
+                        sensitive: true,
+                        min_length: None,
+                        max_length: None,
+                        default: None,
+                    }),
+                    help: None,
+                },
+                transform: vec![],
+                export: true,
+                sensitive: false,
+            }),
+        );
+
+        let dependency = CommandConfig {
+            action: Some(ActionConfig::SingleStep(SingleActionConfig {
+                action: ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(
+                    "cargo build".to_string(),
+                )),
+            })),
+            ..Default::default()
+        };
+
+        let command_config = CommandConfig {
+            action: Some(ActionConfig::MultiStep(MultiActionConfig {
+                actions: vec![
+                    ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+                        BashCommandConfig {
+                            working_directory: Some("./deploy".to_string()),
+                            command: "./deploy.sh $VERSION".to_string(),
+                            run_as: None,
+                            stdout_file: None,
+                            stderr_file: None,
+                            append: false,
+                            allow_extra_args: false,
+                            banner: None,
+                            stdin: StdinConfig::Inherit,
+                            label: None,
+                            max_output_bytes: None,
+                            extra_args: vec![],
+                        },
+                    )),
+                    ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(
+                        "echo Done".to_string(),
+                    )),
+                ],
+                shared_shell: false,
+            })),
+            ..Default::default()
+        };
+
+        // Act
+        let script = generate_script(&command_config, &variable_configs, &[dependency]);
+
+        // Assert
+        assert_eq!(
+            script,
+            "#!/usr/bin/env bash
+set -e
+
+export name='Dingus'
+export VERSION=\"$(cat VERSION)\"
+export password='<password>'
+
+cargo build
+
+(cd \"./deploy\" && ./deploy.sh $VERSION)
+echo Done
+"
+        );
+    }
+}
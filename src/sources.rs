@@ -0,0 +1,99 @@
+use glob::{Pattern, PatternError};
+use std::io;
+use std::process::Command;
+use std::string::FromUtf8Error;
+use thiserror::Error;
+
+/// Whether any files in the current directory matching the `sources` glob patterns have changed
+/// since the git ref `since`.
+/// Uncommitted and untracked files are considered to have changed.
+pub fn have_sources_changed(sources: &[String], since: &str) -> Result<bool, SourcesError> {
+    let mut changed_files = git(&["diff", "--name-only", "--relative", since, "--"])?;
+    changed_files.extend(git(&["ls-files", "--others", "--exclude-standard"])?);
+
+    matches_sources(&changed_files, sources)
+}
+
+/// Whether any of the `changed_files` match any of the `sources` glob patterns.
+fn matches_sources(changed_files: &[String], sources: &[String]) -> Result<bool, SourcesError> {
+    let patterns = sources
+        .iter()
+        .map(|source| Pattern::new(source))
+        .collect::<Result<Vec<Pattern>, PatternError>>()?;
+
+    Ok(changed_files
+        .iter()
+        .any(|changed_file| patterns.iter().any(|pattern| pattern.matches(changed_file))))
+}
+
+/// Runs git with the provided `args` in the current directory, returning each line of output.
+fn git(args: &[&str]) -> Result<Vec<String>, SourcesError> {
+    let output = Command::new("git").args(args).output()?;
+
+    if !output.status.success() {
+        return Err(SourcesError::Git {
+            args: args.join(" "),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+
+    Ok(String::from_utf8(output.stdout)?
+        .lines()
+        .map(|line| line.to_string())
+        .collect())
+}
+
+#[derive(Error, Debug)]
+pub enum SourcesError {
+    #[error("invalid source pattern")]
+    Pattern(#[from] PatternError),
+
+    #[error("\"git {args}\" failed: {stderr}")]
+    Git { args: String, stderr: String },
+
+    #[error("failed to parse git output")]
+    Parse(#[from] FromUtf8Error),
+
+    #[error(transparent)]
+    IO(#[from] io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_sources_matches_changed_files() {
+        // Arrange
+        let changed_files = vec!["README.md".to_string(), "src/main.rs".to_string()];
+        let sources = vec!["src/**/*.rs".to_string(), "Cargo.toml".to_string()];
+
+        // Act
+        let matched = matches_sources(&changed_files, &sources).unwrap();
+
+        // Assert
+        assert!(matched);
+    }
+
+    #[test]
+    fn matches_sources_ignores_unrelated_files() {
+        // Arrange
+        let changed_files = vec!["docs/index.md".to_string()];
+        let sources = vec!["src/**/*.rs".to_string()];
+
+        // Act
+        let matched = matches_sources(&changed_files, &sources).unwrap();
+
+        // Assert
+        assert!(!matched);
+    }
+
+    #[test]
+    fn matches_sources_fails_for_invalid_pattern() {
+        // Act
+        let result = matches_sources(&[], &["src/[".to_string()]);
+
+        // Assert
+        assert!(matches!(result, Err(SourcesError::Pattern(_))));
+    }
+}
@@ -0,0 +1,335 @@
+use crate::config::Platform;
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::io::{Cursor, Read};
+use std::path::Path;
+use std::{env, fs, io};
+use tempfile::NamedTempFile;
+use thiserror::Error;
+
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/YuKitsune/dingus/releases/latest";
+const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Deserialize, Debug)]
+struct Release {
+    tag_name: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Checks for a newer release of dingus, and replaces the running executable with it unless
+/// `check_only` is `true`.
+/// Returns a message describing what happened.
+pub fn self_update(platform: &Platform, check_only: bool) -> Result<String, UpdateError> {
+    let release = fetch_latest_release()?;
+    let latest_version = release.tag_name.trim_start_matches('v');
+
+    if !is_newer(latest_version, CURRENT_VERSION) {
+        return Ok(format!("dingus is up to date ({CURRENT_VERSION})"));
+    }
+
+    if check_only {
+        return Ok(format!(
+            "dingus {latest_version} is available (currently {CURRENT_VERSION})"
+        ));
+    }
+
+    let target =
+        release_target(platform, env::consts::ARCH).ok_or_else(|| UpdateError::Unsupported {
+            platform: format!("{platform:?} ({})", env::consts::ARCH),
+        })?;
+
+    let extension = match platform {
+        Platform::Windows => "zip",
+        Platform::MacOS | Platform::Linux => "tar.gz",
+    };
+    let archive_name = format!("dingus-{}-{target}.{extension}", release.tag_name);
+    let checksum_name = format!("{archive_name}.sha256");
+
+    let archive = download(find_asset(&release, &archive_name)?)?;
+    let checksum_text =
+        String::from_utf8_lossy(&download(find_asset(&release, &checksum_name)?)?).to_string();
+
+    let expected_checksum =
+        parse_checksum(&checksum_text).ok_or_else(|| UpdateError::InvalidChecksum {
+            name: checksum_name.clone(),
+        })?;
+    if !verify_checksum(&archive, &expected_checksum) {
+        return Err(UpdateError::ChecksumMismatch { name: archive_name });
+    }
+
+    let binary = match platform {
+        Platform::Windows => extract_from_zip(&archive, "dingus.exe")?,
+        Platform::MacOS | Platform::Linux => extract_from_tar_gz(&archive, "dingus")?,
+    };
+    let binary = binary.ok_or(UpdateError::BinaryNotFound { name: archive_name })?;
+
+    replace_current_executable(&binary).map_err(UpdateError::Io)?;
+
+    Ok(format!(
+        "Updated dingus from {CURRENT_VERSION} to {latest_version}"
+    ))
+}
+
+fn fetch_latest_release() -> Result<Release, UpdateError> {
+    let body = ureq::get(LATEST_RELEASE_URL)
+        .set("User-Agent", "dingus")
+        .call()
+        .map_err(|err| UpdateError::Request(Box::new(err)))?
+        .into_string()
+        .map_err(UpdateError::Io)?;
+
+    serde_json::from_str(&body).map_err(UpdateError::Parse)
+}
+
+fn find_asset<'a>(release: &'a Release, name: &str) -> Result<&'a Asset, UpdateError> {
+    release
+        .assets
+        .iter()
+        .find(|asset| asset.name == name)
+        .ok_or_else(|| UpdateError::AssetNotFound {
+            name: name.to_string(),
+        })
+}
+
+fn download(asset: &Asset) -> Result<Vec<u8>, UpdateError> {
+    let mut bytes = Vec::new();
+    ureq::get(&asset.browser_download_url)
+        .set("User-Agent", "dingus")
+        .call()
+        .map_err(|err| UpdateError::Request(Box::new(err)))?
+        .into_reader()
+        .read_to_end(&mut bytes)
+        .map_err(UpdateError::Io)?;
+
+    Ok(bytes)
+}
+
+/// The target triple of the release built for the provided platform and architecture, if any.
+fn release_target(platform: &Platform, arch: &str) -> Option<&'static str> {
+    match (platform, arch) {
+        (Platform::Linux, "x86_64") => Some("x86_64-unknown-linux-gnu"),
+        (Platform::Linux, "aarch64") => Some("aarch64-unknown-linux-gnu"),
+        (Platform::Linux, "arm") => Some("armv7-unknown-linux-gnueabihf"),
+        (Platform::MacOS, "x86_64") => Some("x86_64-apple-darwin"),
+        (Platform::MacOS, "aarch64") => Some("aarch64-apple-darwin"),
+        (Platform::Windows, "x86_64") => Some("x86_64-pc-windows-gnu"),
+        _ => None,
+    }
+}
+
+/// Whether the `latest` version is newer than the `current` version.
+/// Versions that can't be compared are treated as newer if they're different.
+fn is_newer(latest: &str, current: &str) -> bool {
+    let parse = |version: &str| -> Option<Vec<u64>> {
+        version
+            .split('.')
+            .map(|part| part.parse::<u64>().ok())
+            .collect()
+    };
+
+    match (parse(latest), parse(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => latest != current,
+    }
+}
+
+/// Finds the SHA-256 hash in the contents of a checksum file.
+/// Both `shasum` and `certutil` output are supported.
+fn parse_checksum(text: &str) -> Option<String> {
+    text.split_whitespace()
+        .find(|word| word.len() == 64 && word.chars().all(|ch| ch.is_ascii_hexdigit()))
+        .map(|word| word.to_lowercase())
+}
+
+fn verify_checksum(bytes: &[u8], expected_checksum: &str) -> bool {
+    let checksum: String = Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+
+    checksum == expected_checksum
+}
+
+fn extract_from_tar_gz(archive: &[u8], file_name: &str) -> Result<Option<Vec<u8>>, UpdateError> {
+    let mut tar = tar::Archive::new(GzDecoder::new(archive));
+
+    for entry in tar.entries().map_err(UpdateError::Io)? {
+        let mut entry = entry.map_err(UpdateError::Io)?;
+        let path = entry.path().map_err(UpdateError::Io)?;
+        if path.file_name().and_then(|name| name.to_str()) != Some(file_name) {
+            continue;
+        }
+
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes).map_err(UpdateError::Io)?;
+        return Ok(Some(bytes));
+    }
+
+    Ok(None)
+}
+
+fn extract_from_zip(archive: &[u8], file_name: &str) -> Result<Option<Vec<u8>>, UpdateError> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(archive)).map_err(UpdateError::Zip)?;
+
+    for index in 0..zip.len() {
+        let mut file = zip.by_index(index).map_err(UpdateError::Zip)?;
+        if Path::new(file.name())
+            .file_name()
+            .and_then(|name| name.to_str())
+            != Some(file_name)
+        {
+            continue;
+        }
+
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes).map_err(UpdateError::Io)?;
+        return Ok(Some(bytes));
+    }
+
+    Ok(None)
+}
+
+/// Replaces the running executable with `binary`.
+/// The new binary is written next to the current one, then moved into place so that the current
+/// executable is never left half-written.
+fn replace_current_executable(binary: &[u8]) -> io::Result<()> {
+    let current_exe = env::current_exe()?;
+    let directory = current_exe
+        .parent()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "executable has no parent"))?;
+
+    let mut temp_file = NamedTempFile::new_in(directory)?;
+    io::Write::write_all(&mut temp_file, binary)?;
+    fs::set_permissions(temp_file.path(), fs::metadata(&current_exe)?.permissions())?;
+
+    // Windows won't let a running executable be replaced, but it can be renamed
+    #[cfg(windows)]
+    fs::rename(&current_exe, current_exe.with_extension("old"))?;
+
+    temp_file.persist(&current_exe).map_err(|err| err.error)?;
+
+    Ok(())
+}
+
+#[derive(Error, Debug)]
+pub enum UpdateError {
+    #[error("failed to reach GitHub")]
+    Request(#[source] Box<ureq::Error>),
+
+    #[error("failed to parse the latest release")]
+    Parse(#[source] serde_json::Error),
+
+    #[error("no release is available for {platform}")]
+    Unsupported { platform: String },
+
+    #[error("the latest release doesn't contain {name}")]
+    AssetNotFound { name: String },
+
+    #[error("could not find a checksum in {name}")]
+    InvalidChecksum { name: String },
+
+    #[error("the checksum for {name} doesn't match")]
+    ChecksumMismatch { name: String },
+
+    #[error("could not find the dingus executable in {name}")]
+    BinaryNotFound { name: String },
+
+    #[error("failed to read the release archive")]
+    Zip(#[source] zip::result::ZipError),
+
+    #[error("failed to update dingus")]
+    Io(#[source] io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    #[test]
+    fn release_target_matches_release_builds() {
+        // Act
+        let linux_target = release_target(&Platform::Linux, "x86_64");
+        let mac_target = release_target(&Platform::MacOS, "aarch64");
+        let unknown_target = release_target(&Platform::Windows, "aarch64");
+
+        // Assert
+        assert_eq!(linux_target, Some("x86_64-unknown-linux-gnu"));
+        assert_eq!(mac_target, Some("aarch64-apple-darwin"));
+        assert_eq!(unknown_target, None);
+    }
+
+    #[test]
+    fn is_newer_compares_each_part() {
+        // Assert
+        assert!(is_newer("0.10.0", "0.6.0"));
+        assert!(is_newer("1.0.0", "0.9.9"));
+        assert!(!is_newer("0.6.0", "0.6.0"));
+        assert!(!is_newer("0.5.1", "0.6.0"));
+    }
+
+    #[test]
+    fn parse_checksum_supports_shasum_and_certutil() {
+        // Arrange
+        let hash = "a".repeat(64);
+        let shasum_output = format!("{hash}  dingus-0.7.0-x86_64-unknown-linux-gnu.tar.gz\n");
+        let certutil_output = format!(
+            "SHA256 hash of dingus-0.7.0-x86_64-pc-windows-gnu.zip:\r\n{}\r\nCertUtil: -hashfile command completed successfully.\r\n",
+            hash.to_uppercase()
+        );
+
+        // Act
+        let shasum_checksum = parse_checksum(&shasum_output);
+        let certutil_checksum = parse_checksum(&certutil_output);
+
+        // Assert
+        assert_eq!(shasum_checksum, Some(hash.clone()));
+        assert_eq!(certutil_checksum, Some(hash));
+    }
+
+    #[test]
+    fn verify_checksum_detects_mismatch() {
+        // Arrange
+        let bytes = b"Hello, World!";
+        let checksum = "dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f";
+
+        // Act
+        let valid = verify_checksum(bytes, checksum);
+        let invalid = verify_checksum(b"Goodbye, World!", checksum);
+
+        // Assert
+        assert!(valid);
+        assert!(!invalid);
+    }
+
+    #[test]
+    fn extract_from_tar_gz_finds_binary() {
+        // Arrange
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (path, content) in [
+            ("dingus-0.7.0/README.md", "Read me".as_bytes()),
+            ("dingus-0.7.0/dingus", "binary".as_bytes()),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o755);
+            header.set_cksum();
+            builder.append_data(&mut header, path, content).unwrap();
+        }
+        let archive = builder.into_inner().unwrap().finish().unwrap();
+
+        // Act
+        let binary = extract_from_tar_gz(&archive, "dingus").unwrap();
+
+        // Assert
+        assert_eq!(binary, Some("binary".as_bytes().to_vec()));
+    }
+}
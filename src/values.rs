@@ -0,0 +1,246 @@
+use crate::config::{VariableConfig, VariableConfigMap};
+use serde_yaml::Value;
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+use thiserror::Error;
+
+const DEFAULT_VALUES_FILE_NAMES: [&str; 4] =
+    ["values.yaml", "values.yml", "values.json", "values.toml"];
+
+/// The separator between each level of a nested key.
+/// This has to be valid in an environment variable name so that the values can be referenced.
+const KEY_SEPARATOR: &str = "_";
+
+/// Loads the variable values from `values_file`, or from a default values file in `directory` if
+/// `values_file` is `None`.
+/// Nested keys are flattened into `parent_child` keys.
+/// Keys that are already set as environment variables are skipped so that the environment takes
+/// precedence.
+pub fn load_values(
+    values_file: &Option<String>,
+    directory: &Path,
+) -> Result<VariableConfigMap, ValuesError> {
+    let path = match values_file {
+        Some(values_file) => {
+            let path = directory.join(values_file);
+            if !path.exists() {
+                return Err(ValuesError::NotFound { path });
+            }
+
+            path
+        }
+        None => {
+            let default_path = DEFAULT_VALUES_FILE_NAMES
+                .iter()
+                .map(|file_name| directory.join(file_name))
+                .find(|path| path.exists());
+
+            match default_path {
+                Some(path) => path,
+                None => return Ok(VariableConfigMap::new()),
+            }
+        }
+    };
+
+    let text = fs::read_to_string(&path).map_err(|err| ValuesError::ReadFailed {
+        path: path.clone(),
+        source: err,
+    })?;
+
+    let value = parse_values(&path, &text)?;
+
+    let mut variable_configs = VariableConfigMap::new();
+    for (key, value) in flatten_values(&value) {
+        if env::var(&key).is_ok() {
+            continue;
+        }
+
+        variable_configs.insert(key, VariableConfig::ShorthandLiteral(value));
+    }
+
+    Ok(variable_configs)
+}
+
+/// Parses `text` as TOML if `path` has a `.toml` extension, otherwise as YAML.
+/// JSON is a subset of YAML, so JSON files are parsed as YAML.
+fn parse_values(path: &Path, text: &str) -> Result<Value, ValuesError> {
+    let is_toml = path
+        .extension()
+        .is_some_and(|extension| extension == "toml");
+
+    if is_toml {
+        toml::from_str(text).map_err(|err| ValuesError::ParseFailed {
+            path: path.to_path_buf(),
+            message: err.to_string(),
+        })
+    } else {
+        serde_yaml::from_str(text).map_err(|err| ValuesError::ParseFailed {
+            path: path.to_path_buf(),
+            message: err.to_string(),
+        })
+    }
+}
+
+/// Flattens `value` into a list of key/value pairs, joining nested keys with `_`.
+fn flatten_values(value: &Value) -> Vec<(String, String)> {
+    let mut values = Vec::new();
+    flatten_value(None, value, &mut values);
+    values
+}
+
+fn flatten_value(prefix: Option<String>, value: &Value, values: &mut Vec<(String, String)>) {
+    let join = |key: String| match &prefix {
+        Some(prefix) => format!("{prefix}{KEY_SEPARATOR}{key}"),
+        None => key,
+    };
+
+    match value {
+        Value::Mapping(mapping) => {
+            for (key, value) in mapping {
+                if let Some(key) = scalar_to_string(key) {
+                    flatten_value(Some(join(key)), value, values);
+                }
+            }
+        }
+        Value::Sequence(sequence) => {
+            for (index, value) in sequence.iter().enumerate() {
+                flatten_value(Some(join(index.to_string())), value, values);
+            }
+        }
+        Value::Tagged(tagged) => flatten_value(prefix, &tagged.value, values),
+        _ => {
+            if let (Some(key), Some(value)) = (prefix, scalar_to_string(value)) {
+                values.push((key, value));
+            }
+        }
+    }
+}
+
+fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => Some(String::new()),
+        Value::Bool(bool) => Some(bool.to_string()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::String(string) => Some(string.clone()),
+        _ => None,
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ValuesError {
+    #[error("could not find values file {}", path.display())]
+    NotFound { path: PathBuf },
+
+    #[error("failed to read values file {}", path.display())]
+    ReadFailed { path: PathBuf, source: io::Error },
+
+    #[error("failed to parse values file {}: {message}", path.display())]
+    ParseFailed { path: PathBuf, message: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variables::{substitute_variables, VariableMap};
+    use tempfile::TempDir;
+
+    #[test]
+    fn load_values_flattens_nested_keys() {
+        // Arrange
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("values.json"),
+            r#"{ "region": "eu-west-1", "database": { "host": "localhost", "port": 5432 } }"#,
+        )
+        .unwrap();
+
+        // Act
+        let variable_configs = load_values(&None, temp_dir.path()).unwrap();
+
+        // Assert
+        assert_eq!(
+            variable_configs.get("region"),
+            Some(&VariableConfig::ShorthandLiteral("eu-west-1".to_string()))
+        );
+        assert_eq!(
+            variable_configs.get("database_host"),
+            Some(&VariableConfig::ShorthandLiteral("localhost".to_string()))
+        );
+        assert_eq!(
+            variable_configs.get("database_port"),
+            Some(&VariableConfig::ShorthandLiteral("5432".to_string()))
+        );
+    }
+
+    #[test]
+    fn load_values_nested_keys_can_be_substituted() {
+        // Arrange
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("values.yaml"),
+            "database:\n  host: localhost\n  port: 5432\n",
+        )
+        .unwrap();
+
+        // Act
+        let variable_configs = load_values(&None, temp_dir.path()).unwrap();
+
+        // Assert
+        let variables: VariableMap = variable_configs
+            .into_iter()
+            .filter_map(|(key, variable_config)| match variable_config {
+                VariableConfig::ShorthandLiteral(value) => Some((key, value)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            substitute_variables("psql -h $database_host -p $database_port", &variables),
+            "psql -h localhost -p 5432"
+        );
+    }
+
+    #[test]
+    fn load_values_reads_toml() {
+        // Arrange
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("defaults.toml"),
+            "[database]\nhost = \"localhost\"\n",
+        )
+        .unwrap();
+
+        // Act
+        let variable_configs =
+            load_values(&Some("defaults.toml".to_string()), temp_dir.path()).unwrap();
+
+        // Assert
+        assert_eq!(
+            variable_configs.get("database_host"),
+            Some(&VariableConfig::ShorthandLiteral("localhost".to_string()))
+        );
+    }
+
+    #[test]
+    fn load_values_ignores_missing_default_file() {
+        // Arrange
+        let temp_dir = TempDir::new().unwrap();
+
+        // Act
+        let variable_configs = load_values(&None, temp_dir.path()).unwrap();
+
+        // Assert
+        assert!(variable_configs.is_empty());
+    }
+
+    #[test]
+    fn load_values_fails_for_missing_explicit_file() {
+        // Arrange
+        let temp_dir = TempDir::new().unwrap();
+
+        // Act
+        let result = load_values(&Some("values.yaml".to_string()), temp_dir.path());
+
+        // Assert
+        assert!(matches!(result, Err(ValuesError::NotFound { .. })));
+    }
+}
@@ -1,21 +1,44 @@
 use crate::args::ArgumentResolver;
-use crate::config::{DingusOptions, PromptOptionsVariant, VariableConfig, VariableConfigMap};
+use crate::config::{
+    AutocompletePromptOptions, DingusOptions, Encoding, ExecutionConfigVariant,
+    LiteralVariableConfig, PromptConfig, PromptOptionsVariant, RandomConfig, RandomKind,
+    SelectOptionsConfig, SelectPromptOptions, TemplateEngine, TextPromptOptions, Transform,
+    VariableConfig, VariableConfigMap, VariableOverrideMap,
+};
 use crate::exec::{CommandExecutor, ExecutionError, ExitStatus};
-use crate::prompt::{PromptError, PromptExecutor};
+use crate::prompt::{get_options, PromptError, PromptExecutor};
+use crate::workdir::expand_tilde;
+use base64::prelude::*;
 use colored::Colorize;
-use std::collections::HashMap;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
 use std::string::FromUtf8Error;
+use std::{fs, io};
 use thiserror::Error;
 
 /// A [`HashMap`] where the key is the variable name, and the value is that variables value.
 pub type VariableMap = HashMap<String, String>;
 
+/// Printed in place of sensitive values.
+/// This is hard coded to obscure the length of the value.
+pub const REDACTED_VALUE: &str = "********";
+
+/// The number of characters in random hex values that don't specify a length.
+pub const DEFAULT_RANDOM_HEX_LENGTH: usize = 16;
+
 pub trait VariableResolver {
     /// Resolves variables from the provided [`VariableConfigMap`] into a [`VariableMap`].
     fn resolve_variables(
         &self,
         variable_configs: &VariableConfigMap,
     ) -> Result<VariableMap, VariableResolutionError>;
+
+    /// Resolves every variable like [`VariableResolver::resolve_variables`], but keeps going after
+    /// a variable fails so that every failure can be reported at once.
+    fn check_variables(
+        &self,
+        variable_configs: &VariableConfigMap,
+    ) -> Result<VariableMap, VariableCheckError>;
 }
 
 pub struct RealVariableResolver {
@@ -23,6 +46,14 @@ pub struct RealVariableResolver {
     pub prompt_executor: Box<dyn PromptExecutor>,
     pub argument_resolver: Box<dyn ArgumentResolver>,
     pub dingus_options: DingusOptions,
+
+    /// When set to `true`, the user will be prompted to confirm or change the value of every
+    /// variable that doesn't already use a prompt.
+    pub interactive: bool,
+
+    /// When set to `true`, prompts aren't shown and a `<key>` placeholder is used as their value
+    /// instead, so that previewing a command doesn't wait on the user.
+    pub placeholder_prompts: bool,
 }
 
 impl VariableResolver for RealVariableResolver {
@@ -36,110 +67,741 @@ impl VariableResolver for RealVariableResolver {
         let mut sensitive_variable_names: Vec<String> = vec![];
 
         for (key, config) in variable_configs.iter() {
-            let name = config.environment_variable_name(key);
+            self.resolve_variable(
+                key,
+                config,
+                &mut resolved_variables,
+                &mut sensitive_variable_names,
+            )?;
+        }
 
-            // Args from the command-line have the highest priority, check there first.
-            if let Some(arg_value) = self.argument_resolver.get(key) {
-                resolved_variables.insert(name.clone(), arg_value.clone());
-            } else {
-                match config {
-                    VariableConfig::ShorthandLiteral(value) => {
-                        resolved_variables.insert(name.clone(), value.clone());
-                    }
+        self.log_variables(&resolved_variables, &sensitive_variable_names);
 
-                    VariableConfig::Literal(literal_conf) => {
-                        resolved_variables.insert(name.clone(), literal_conf.value.clone());
-                    }
+        Ok(resolved_variables)
+    }
 
-                    VariableConfig::Execution(execution_conf) => {
-                        // Exec variables need access to the variables defined above them.
-                        let output = self
-                            .command_executor
-                            .get_output(&execution_conf.execution, &resolved_variables)
-                            .map_err(|err| VariableResolutionError::Execution {
-                                key: key.clone(),
-                                source: err,
-                            })?;
-
-                        // TODO: Make this configurable.
-                        // If the command has a non-zero exit code, we probably shouldn't trust it's output.
-                        // Return an error instead.
-                        if let ExitStatus::Fail(_) = output.status {
-                            return Err(VariableResolutionError::ExitStatus {
-                                key: key.clone(),
-                                status: output.status.clone(),
-                            });
-                        }
+    fn check_variables(
+        &self,
+        variable_configs: &VariableConfigMap,
+    ) -> Result<VariableMap, VariableCheckError> {
+        let mut resolved_variables = VariableMap::new();
+        let mut sensitive_variable_names: Vec<String> = vec![];
+        let mut errors = vec![];
 
-                        let value = String::from_utf8(output.stdout)
-                            .map_err(|err| VariableResolutionError::Parse {
-                                key: key.clone(),
-                                source: err,
-                            })?
-                            .trim_end()
-                            .to_string();
+        // Variables that fail are left out, so any variables using them are resolved without them
+        for (key, config) in variable_configs.iter() {
+            if let Err(err) = self.resolve_variable(
+                key,
+                config,
+                &mut resolved_variables,
+                &mut sensitive_variable_names,
+            ) {
+                errors.push(err);
+            }
+        }
 
-                        resolved_variables.insert(name.clone(), value.clone());
-                    }
+        if !errors.is_empty() {
+            return Err(VariableCheckError { errors });
+        }
+
+        self.log_variables(&resolved_variables, &sensitive_variable_names);
+
+        Ok(resolved_variables)
+    }
+}
+
+impl RealVariableResolver {
+    /// Resolves the variable with the provided `key`, adding it to `resolved_variables`.
+    fn resolve_variable(
+        &self,
+        key: &String,
+        config: &VariableConfig,
+        resolved_variables: &mut VariableMap,
+        sensitive_variable_names: &mut Vec<String>,
+    ) -> Result<(), VariableResolutionError> {
+        let name = config.environment_variable_name(key);
+        if is_variable_sensitive(config) {
+            sensitive_variable_names.push(name.clone());
+        }
+
+        // Args from the command-line have the highest priority, check there first.
+        if let Some(arg_value) = self.argument_resolver.get(key) {
+            self.check_option(key, config, &arg_value, resolved_variables)?;
+            resolved_variables.insert(name.clone(), arg_value.clone());
+        } else {
+            match config {
+                VariableConfig::ShorthandLiteral(value) => {
+                    resolved_variables.insert(name.clone(), value.clone());
+                }
+
+                VariableConfig::Literal(literal_conf) => {
+                    let value = self.prompt_if_missing(
+                        key,
+                        &name,
+                        literal_conf.value.clone(),
+                        &literal_conf.prompt_if_missing,
+                        resolved_variables,
+                        sensitive_variable_names,
+                    )?;
+
+                    resolved_variables.insert(name.clone(), value);
+                }
+
+                VariableConfig::Execution(execution_conf) => {
+                    // Exec variables need access to the variables defined above them.
+                    let stdout = self.command_output(
+                        key,
+                        &execution_conf.execution,
+                        &execution_conf.encoding,
+                        resolved_variables,
+                    )?;
+
+                    let value = execution_conf.trim.apply(&stdout);
+                    let value = match &execution_conf.r#match {
+                        Some(pattern) => extract_match(pattern, execution_conf.group, &value)
+                            .map_err(|err| match err {
+                                MatchError::Regex(source) => {
+                                    VariableResolutionError::InvalidMatch {
+                                        key: key.clone(),
+                                        source,
+                                    }
+                                }
+                                MatchError::NoMatch => VariableResolutionError::NoMatch {
+                                    key: key.clone(),
+                                    pattern: pattern.clone(),
+                                },
+                            })?,
+                        None => value,
+                    };
+
+                    let value = self.prompt_if_missing(
+                        key,
+                        &name,
+                        value,
+                        &execution_conf.prompt_if_missing,
+                        resolved_variables,
+                        sensitive_variable_names,
+                    )?;
+
+                    resolved_variables.insert(name.clone(), value.clone());
+                }
+
+                VariableConfig::Prompt(prompt_config) => {
+                    let prompt = substitute_prompt(&prompt_config.prompt, resolved_variables);
+                    let value = self.prompt(key, &prompt)?;
+
+                    resolved_variables.insert(name.clone(), value.clone());
+                }
 
-                    VariableConfig::Prompt(prompt_config) => {
-                        let value = self
-                            .prompt_executor
-                            .execute(&prompt_config.prompt)
-                            .map_err(|err| VariableResolutionError::Prompt {
-                                key: key.clone(),
-                                source: err,
-                            })?;
+                VariableConfig::Random(random_conf) => {
+                    let value = random_value(&random_conf.random).map_err(|err| {
+                        VariableResolutionError::Random {
+                            key: key.clone(),
+                            source: err,
+                        }
+                    })?;
 
-                        resolved_variables.insert(name.clone(), value.clone());
+                    resolved_variables.insert(name.clone(), value);
+                }
 
-                        if is_variable_sensitive(config) {
-                            sensitive_variable_names.push(name.clone());
+                VariableConfig::File(file_conf) => {
+                    // File paths can use the variables defined above them.
+                    let path =
+                        expand_tilde(&substitute_variables(&file_conf.path, resolved_variables));
+                    let content = fs::read(&path).map_err(|err| VariableResolutionError::File {
+                        key: key.clone(),
+                        path: path.clone(),
+                        source: err,
+                    })?;
+                    let content = String::from_utf8(content).map_err(|err| {
+                        VariableResolutionError::Parse {
+                            key: key.clone(),
+                            source: err,
                         }
+                    })?;
+
+                    resolved_variables.insert(name.clone(), file_conf.trim.apply(&content));
+                }
+
+                // Arguments are checked above, so only the default is needed here.
+                VariableConfig::Argument(argument_conf) => {
+                    if let Some(default_command) = &argument_conf.default_from_command {
+                        let stdout = self.command_output(
+                            key,
+                            default_command,
+                            &Encoding::default(),
+                            resolved_variables,
+                        )?;
+
+                        resolved_variables.insert(name.clone(), argument_conf.trim.apply(&stdout));
                     }
+                }
+            }
 
-                    // Arguments are checked above, nothing to do here.
-                    VariableConfig::Argument(_) => {}
+            if self.interactive && !matches!(config, VariableConfig::Prompt(_)) {
+                if let Some(value) = resolved_variables.get(&name).cloned() {
+                    let is_sensitive = sensitive_variable_names.contains(&name);
+                    let value = self.prompt_for_value(key, value, is_sensitive)?;
+                    resolved_variables.insert(name.clone(), value);
                 }
             }
         }
 
-        self.log_variables(&resolved_variables, &sensitive_variable_names);
+        if let Some(value) = resolved_variables.get(&name) {
+            let value = apply_transforms(value, config.transforms()).map_err(|err| {
+                VariableResolutionError::Transform {
+                    key: key.clone(),
+                    source: err,
+                }
+            })?;
+            resolved_variables.insert(name.clone(), value);
+        }
 
-        Ok(resolved_variables)
+        Ok(())
+    }
+
+    /// Checks that a `value` provided for a select prompt variable, or an autocomplete prompt
+    /// variable restricted to its suggestions, is one of the options the prompt would have
+    /// offered.
+    fn check_option(
+        &self,
+        key: &str,
+        config: &VariableConfig,
+        value: &str,
+        variables: &VariableMap,
+    ) -> Result<(), VariableResolutionError> {
+        let VariableConfig::Prompt(prompt_config) = config else {
+            return Ok(());
+        };
+
+        let prompt = substitute_prompt(&prompt_config.prompt, variables);
+        let options_config = match &prompt.options {
+            PromptOptionsVariant::Select(select_options) => &select_options.options,
+            PromptOptionsVariant::Autocomplete(autocomplete_options)
+                if autocomplete_options.restrict_to_suggestions =>
+            {
+                &autocomplete_options.suggestions
+            }
+            _ => return Ok(()),
+        };
+
+        let options = get_options(options_config, &self.command_executor).map_err(|err| {
+            VariableResolutionError::Prompt {
+                key: key.to_string(),
+                source: err,
+            }
+        })?;
+
+        if !options.iter().any(|option| option == value) {
+            return Err(VariableResolutionError::InvalidOption {
+                key: key.to_string(),
+                value: value.to_string(),
+                options: options.join(", "),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Executes the provided [`ExecutionConfigVariant`] and returns its stdout, failing if the
+    /// command fails.
+    fn command_output(
+        &self,
+        key: &str,
+        execution_config: &ExecutionConfigVariant,
+        encoding: &Encoding,
+        variables: &VariableMap,
+    ) -> Result<String, VariableResolutionError> {
+        let output = self
+            .command_executor
+            .get_output(execution_config, variables)
+            .map_err(|err| match err {
+                ExecutionError::OutputTooLarge { max_output_bytes } => {
+                    VariableResolutionError::OutputTooLarge {
+                        key: key.to_string(),
+                        max_output_bytes,
+                    }
+                }
+                err => VariableResolutionError::Execution {
+                    key: key.to_string(),
+                    source: err,
+                },
+            })?;
+
+        // TODO: Make this configurable.
+        // If the command has a non-zero exit code, we probably shouldn't trust it's output.
+        // Return an error instead.
+        if let ExitStatus::Fail(_) = output.status {
+            return Err(VariableResolutionError::ExitStatus {
+                key: key.to_string(),
+                status: output.status.clone(),
+            });
+        }
+
+        output
+            .stdout_string(encoding)
+            .map_err(|err| VariableResolutionError::Parse {
+                key: key.to_string(),
+                source: err,
+            })
+    }
+
+    /// Prompts the user for the value of the variable with the provided `key`, using `value` as
+    /// the default.
+    fn prompt_for_value(
+        &self,
+        key: &str,
+        value: String,
+        is_sensitive: bool,
+    ) -> Result<String, VariableResolutionError> {
+        let prompt_config = PromptConfig {
+            message: key.to_string(),
+            options: PromptOptionsVariant::Text(TextPromptOptions {
+                multi_line: false,
+                sensitive: is_sensitive,
+                min_length: None,
+                max_length: None,
+                default: Some(value),
+            }),
+            help: None,
+        };
+
+        self.prompt(key, &prompt_config)
+    }
+
+    /// Returns `value`, or the result of `fallback_prompt` if `value` is empty.
+    fn prompt_if_missing(
+        &self,
+        key: &str,
+        name: &str,
+        value: String,
+        fallback_prompt: &Option<PromptConfig>,
+        resolved_variables: &VariableMap,
+        sensitive_variable_names: &mut Vec<String>,
+    ) -> Result<String, VariableResolutionError> {
+        let Some(prompt_config) = fallback_prompt else {
+            return Ok(value);
+        };
+
+        if !value.is_empty() {
+            return Ok(value);
+        }
+
+        if is_prompt_sensitive(prompt_config) {
+            sensitive_variable_names.push(name.to_string());
+        }
+
+        self.prompt(key, &substitute_prompt(prompt_config, resolved_variables))
+    }
+
+    /// Prompts the user for the value of the variable with the provided `key`, or returns a
+    /// placeholder without prompting if [`RealVariableResolver::placeholder_prompts`] is set.
+    fn prompt(
+        &self,
+        key: &str,
+        prompt_config: &PromptConfig,
+    ) -> Result<String, VariableResolutionError> {
+        if self.placeholder_prompts {
+            return Ok(format!("<{key}>"));
+        }
+
+        self.prompt_executor
+            .execute(prompt_config)
+            .map_err(|err| VariableResolutionError::Prompt {
+                key: key.to_string(),
+                source: err,
+            })
     }
-}
 
-impl RealVariableResolver {
     fn log_variables(&self, variables: &VariableMap, sensitive_variable_names: &Vec<String>) {
         if !self.dingus_options.print_variables {
             return;
         }
 
-        for (name, value) in variables {
-            let is_sensitive = sensitive_variable_names.contains(name);
+        for (name, value) in redact_variables(variables, sensitive_variable_names) {
+            println!("{}={}", name, value.green());
+        }
+    }
+}
+
+/// Generates a new random value like the provided [`RandomConfig`].
+fn random_value(random_config: &RandomConfig) -> Result<String, getrandom::Error> {
+    let hex = |bytes: &[u8]| -> String { bytes.iter().map(|byte| format!("{byte:02x}")).collect() };
+
+    match random_config.kind {
+        RandomKind::Uuid => {
+            let mut bytes = [0u8; 16];
+            getrandom::getrandom(&mut bytes)?;
+
+            // Marks the UUID as a random (version 4) UUID
+            bytes[6] = (bytes[6] & 0x0f) | 0x40;
+            bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+            let hex = hex(&bytes);
+            Ok(format!(
+                "{}-{}-{}-{}-{}",
+                &hex[..8],
+                &hex[8..12],
+                &hex[12..16],
+                &hex[16..20],
+                &hex[20..]
+            ))
+        }
+        RandomKind::Hex => {
+            let length = random_config.length.unwrap_or(DEFAULT_RANDOM_HEX_LENGTH);
+            let mut bytes = vec![0u8; length.div_ceil(2)];
+            getrandom::getrandom(&mut bytes)?;
+
+            let mut hex = hex(&bytes);
+            hex.truncate(length);
+            Ok(hex)
+        }
+    }
+}
+
+/// Applies each of the provided [`Transform`]s to `value`, in order.
+pub fn apply_transforms(value: &str, transforms: &[Transform]) -> Result<String, TransformError> {
+    let mut value = value.to_string();
+
+    for transform in transforms {
+        value = match transform {
+            Transform::Upper => value.to_uppercase(),
+            Transform::Lower => value.to_lowercase(),
+            Transform::Trim => value.trim().to_string(),
+            Transform::Base64 => BASE64_STANDARD.encode(value),
+            Transform::Base64d => String::from_utf8(BASE64_STANDARD.decode(value.trim())?)?,
+        };
+    }
+
+    Ok(value)
+}
+
+/// Substitutes `variables` into the message and any literal options of the provided
+/// [`PromptConfig`].
+fn substitute_prompt(prompt_config: &PromptConfig, variables: &VariableMap) -> PromptConfig {
+    let substitute_options = |options: &SelectOptionsConfig| match options {
+        SelectOptionsConfig::Literal(options) => SelectOptionsConfig::Literal(
+            options
+                .iter()
+                .map(|option| substitute_variables(option, variables))
+                .collect(),
+        ),
+        SelectOptionsConfig::Execution(_) => options.clone(),
+    };
+
+    let options = match &prompt_config.options {
+        PromptOptionsVariant::Text(_) => prompt_config.options.clone(),
+        PromptOptionsVariant::Select(select_options) => {
+            PromptOptionsVariant::Select(SelectPromptOptions {
+                options: substitute_options(&select_options.options),
+            })
+        }
+        PromptOptionsVariant::Autocomplete(autocomplete_options) => {
+            PromptOptionsVariant::Autocomplete(AutocompletePromptOptions {
+                suggestions: substitute_options(&autocomplete_options.suggestions),
+                ..autocomplete_options.clone()
+            })
+        }
+    };
+
+    PromptConfig {
+        message: substitute_variables(&prompt_config.message, variables),
+        help: prompt_config
+            .help
+            .as_ref()
+            .map(|help| substitute_variables(help, variables)),
+        options,
+    }
+}
 
-            let variable_to_print = if is_sensitive {
-                "********".to_string() // Hard coded value to obscure the length
+/// Returns a copy of `variables` with the values of any variables in `sensitive_variable_names`
+/// replaced with [`REDACTED_VALUE`].
+pub fn redact_variables(
+    variables: &VariableMap,
+    sensitive_variable_names: &[String],
+) -> VariableMap {
+    variables
+        .iter()
+        .map(|(name, value)| {
+            if sensitive_variable_names.contains(name) {
+                (name.clone(), REDACTED_VALUE.to_string())
             } else {
-                value.clone()
+                (name.clone(), value.clone())
+            }
+        })
+        .collect()
+}
+
+/// Whether the value of the provided [`VariableConfig`] should be kept secret.
+pub fn is_variable_sensitive(variable_config: &VariableConfig) -> bool {
+    match variable_config {
+        VariableConfig::Prompt(prompt_variable) => {
+            prompt_variable.sensitive || is_prompt_sensitive(&prompt_variable.prompt)
+        }
+        _ => variable_config.is_sensitive(),
+    }
+}
+
+/// Returns the names of the variables in `variable_configs` that may hold sensitive values,
+/// including those that fall back to a sensitive prompt.
+pub fn sensitive_variable_names(variable_configs: &VariableConfigMap) -> Vec<String> {
+    variable_configs
+        .iter()
+        .filter(|(_, variable_config)| {
+            let fallback_prompt = match variable_config {
+                VariableConfig::Literal(literal_conf) => &literal_conf.prompt_if_missing,
+                VariableConfig::Execution(execution_conf) => &execution_conf.prompt_if_missing,
+                _ => &None,
             };
 
-            println!("{}={}", name, variable_to_print.green());
+            is_variable_sensitive(variable_config)
+                || fallback_prompt.as_ref().is_some_and(is_prompt_sensitive)
+        })
+        .map(|(key, variable_config)| variable_config.environment_variable_name(key))
+        .collect()
+}
+
+/// Returns the names of the variables in `variable_configs` that shouldn't be passed to commands
+/// as environment variables.
+pub fn unexported_variable_names(variable_configs: &VariableConfigMap) -> Vec<String> {
+    variable_configs
+        .iter()
+        .filter(|(_, variable_config)| !variable_config.is_exported())
+        .map(|(key, variable_config)| variable_config.environment_variable_name(key))
+        .collect()
+}
+
+fn is_prompt_sensitive(prompt_config: &PromptConfig) -> bool {
+    match &prompt_config.options {
+        PromptOptionsVariant::Select(_) => false,
+        PromptOptionsVariant::Autocomplete(_) => false,
+        PromptOptionsVariant::Text(text_prompt_options) => text_prompt_options.sensitive,
+    }
+}
+
+/// Removes the lazy variables from `variable_configs` that aren't referenced by `referencing_text`,
+/// or by the config of any variable that's kept, so that their commands never run.
+/// Exported lazy variables are always kept, since scripts can read them from the environment
+/// without referencing them.
+pub fn remove_unreferenced_lazy_variables(
+    variable_configs: &mut VariableConfigMap,
+    referencing_text: &str,
+) {
+    let mut referenced_names = referenced_variable_names(referencing_text);
+    for (_, variable_config) in variable_configs.iter() {
+        if !variable_config.is_lazy() {
+            referenced_names.extend(referenced_variable_names_in_config(variable_config));
+        }
+    }
+
+    // Lazy variables can be referenced by other lazy variables, so keep going until nothing changes
+    let mut kept_keys: HashSet<String> = HashSet::new();
+    loop {
+        let newly_referenced: Vec<(String, &VariableConfig)> = variable_configs
+            .iter()
+            .filter(|(key, variable_config)| {
+                variable_config.is_lazy()
+                    && !kept_keys.contains(*key)
+                    && (variable_config.is_exported()
+                        || referenced_names
+                            .contains(&variable_config.environment_variable_name(key)))
+            })
+            .map(|(key, variable_config)| (key.clone(), variable_config))
+            .collect();
+
+        if newly_referenced.is_empty() {
+            break;
         }
+
+        for (key, variable_config) in newly_referenced {
+            referenced_names.extend(referenced_variable_names_in_config(variable_config));
+            kept_keys.insert(key);
+        }
+    }
+
+    let unreferenced_keys: Vec<String> = variable_configs
+        .iter()
+        .filter(|(key, variable_config)| variable_config.is_lazy() && !kept_keys.contains(*key))
+        .map(|(key, _)| key.clone())
+        .collect();
+    for key in unreferenced_keys {
+        variable_configs.remove(&key);
     }
 }
 
-fn is_variable_sensitive(variable_config: &VariableConfig) -> bool {
-    match variable_config {
-        VariableConfig::Prompt(prompt_variable) => match prompt_variable.clone().prompt.options {
-            PromptOptionsVariant::Select(_) => false,
-            PromptOptionsVariant::Text(text_prompt_options) => text_prompt_options.sensitive,
-        },
-        _ => false,
+fn referenced_variable_names_in_config(variable_config: &VariableConfig) -> HashSet<String> {
+    serde_yaml::to_string(variable_config)
+        .map(|text| referenced_variable_names(&text))
+        .unwrap_or_default()
+}
+
+/// Returns the names of the variables referenced in `text` as either `$name` or `${name}`, along
+/// with any names used in a Tera `{{ expression }}` or `{% tag %}`.
+/// Names in Tera expressions aren't necessarily variables, but keeping an extra lazy variable is
+/// better than failing to render.
+fn referenced_variable_names(text: &str) -> HashSet<String> {
+    let mut names = referenced_tera_names(text);
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch != '$' {
+            continue;
+        }
+
+        if chars.peek() == Some(&'{') {
+            chars.next();
+        }
+
+        let mut name = String::new();
+        while let Some(&next_ch) = chars.peek() {
+            if next_ch.is_alphanumeric() || next_ch == '_' {
+                name.push(next_ch);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+
+        if !name.is_empty() {
+            names.insert(name);
+        }
+    }
+
+    names
+}
+
+fn referenced_tera_names(text: &str) -> HashSet<String> {
+    let mut names = HashSet::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('{') {
+        rest = &rest[start + 1..];
+        let closing = match rest.chars().next() {
+            Some('{') => "}}",
+            Some('%') => "%}",
+            _ => continue,
+        };
+
+        let end = rest.find(closing).unwrap_or(rest.len());
+        let expression = &rest[1..end];
+        names.extend(
+            expression
+                .split(|ch: char| !ch.is_alphanumeric() && ch != '_')
+                .filter(|word| word.starts_with(|ch: char| ch.is_alphabetic() || ch == '_'))
+                .map(|word| word.to_string()),
+        );
+        rest = &rest[end..];
+    }
+
+    names
+}
+
+/// Formats the provided [`VariableMap`] as `export KEY='value'` lines that can be evaluated by a
+/// POSIX shell, sorted by key.
+pub fn format_exports(variables: &VariableMap) -> Vec<String> {
+    let mut names: Vec<&String> = variables.keys().collect();
+    names.sort();
+
+    names
+        .into_iter()
+        .map(|name| format!("export {}={}", name, shell_quote(&variables[name])))
+        .collect()
+}
+
+/// Wraps `value` in single quotes, escaping any single quotes within it.
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Replaces the value of each variable in `variable_configs` that has an entry in `overrides`.
+/// Overridden variables become literals, but keep their argument and environment variable name so
+/// that values provided via the command-line still take precedence.
+/// Overrides for variables that aren't in `variable_configs` are ignored.
+pub fn apply_overrides(variable_configs: &mut VariableConfigMap, overrides: &VariableOverrideMap) {
+    for (key, value) in overrides.iter() {
+        let Some(variable_config) = variable_configs.get_mut(key) else {
+            continue;
+        };
+
+        let transform = variable_config.transforms().to_vec();
+        // A sensitive prompt's value is still sensitive once it's been overridden
+        let sensitive = is_variable_sensitive(variable_config);
+        let export = variable_config.is_exported();
+        let (argument, environment_variable_name, prompt_if_missing) = match variable_config.clone()
+        {
+            VariableConfig::ShorthandLiteral(_) => {
+                *variable_config = VariableConfig::ShorthandLiteral(value.clone());
+                continue;
+            }
+            VariableConfig::Literal(literal_conf) => (
+                literal_conf.argument,
+                literal_conf.environment_variable_name,
+                literal_conf.prompt_if_missing,
+            ),
+            VariableConfig::Execution(execution_conf) => (
+                execution_conf.argument,
+                execution_conf.environment_variable_name,
+                execution_conf.prompt_if_missing,
+            ),
+            VariableConfig::Prompt(prompt_conf) => (
+                prompt_conf.argument,
+                prompt_conf.environment_variable_name,
+                None,
+            ),
+            VariableConfig::Random(random_conf) => (
+                random_conf.argument,
+                random_conf.environment_variable_name,
+                None,
+            ),
+            VariableConfig::File(file_conf) => (
+                file_conf.argument,
+                file_conf.environment_variable_name,
+                None,
+            ),
+            VariableConfig::Argument(argument_conf) => (
+                Some(argument_conf.argument),
+                argument_conf.environment_variable_name,
+                None,
+            ),
+        };
+
+        *variable_config = VariableConfig::Literal(LiteralVariableConfig {
+            argument,
+            environment_variable_name,
+            value: value.clone(),
+            prompt_if_missing,
+            transform,
+            export,
+            sensitive,
+        });
+    }
+}
+
+/// Renders `template` with the provided [`VariableMap`] using the provided [`TemplateEngine`].
+pub fn render_template(
+    template: &str,
+    template_engine: &TemplateEngine,
+    variables: &VariableMap,
+) -> Result<String, TemplateError> {
+    match template_engine {
+        TemplateEngine::Simple => Ok(substitute_variables(template, variables)),
+        TemplateEngine::Tera => render_tera_template(template, variables),
     }
 }
 
+#[cfg(feature = "tera")]
+fn render_tera_template(template: &str, variables: &VariableMap) -> Result<String, TemplateError> {
+    let context = tera::Context::from_serialize(variables).map_err(TemplateError::Tera)?;
+    tera::Tera::one_off(template, &context, false).map_err(TemplateError::Tera)
+}
+
+#[cfg(not(feature = "tera"))]
+fn render_tera_template(_: &str, _: &VariableMap) -> Result<String, TemplateError> {
+    Err(TemplateError::Unsupported {
+        feature: "tera".to_string(),
+    })
+}
+
 /// Uses bash-style variable substitution to replace variable names with their values.
 pub fn substitute_variables(template: &str, variables: &VariableMap) -> String {
     let mut result = String::new();
@@ -189,6 +851,17 @@ pub fn substitute_variables(template: &str, variables: &VariableMap) -> String {
     result
 }
 
+#[derive(Error, Debug)]
+pub enum TemplateError {
+    #[cfg(feature = "tera")]
+    #[error("failed to render template")]
+    Tera(#[source] tera::Error),
+
+    #[cfg(not(feature = "tera"))]
+    #[error("templates require the \"{feature}\" feature to be enabled")]
+    Unsupported { feature: String },
+}
+
 #[derive(Error, Debug)]
 #[error("failed to resolve variable \"{key}\"")]
 pub enum VariableResolutionError {
@@ -203,6 +876,30 @@ pub enum VariableResolutionError {
         status: ExitStatus,
     },
 
+    #[error("failed to resolve variable \"{key}\": output exceeded {max_output_bytes} bytes")]
+    OutputTooLarge {
+        key: String,
+        max_output_bytes: u64,
+    },
+
+    InvalidMatch {
+        key: String,
+        source: regex::Error,
+    },
+
+    #[error("invalid value \"{value}\" for variable \"{key}\", expected one of: {options}")]
+    InvalidOption {
+        key: String,
+        value: String,
+        options: String,
+    },
+
+    #[error("failed to resolve variable \"{key}\": output didn't match \"{pattern}\"")]
+    NoMatch {
+        key: String,
+        pattern: String,
+    },
+
     Parse {
         key: String,
         source: FromUtf8Error,
@@ -212,20 +909,92 @@ pub enum VariableResolutionError {
         key: String,
         source: PromptError,
     },
+
+    Transform {
+        key: String,
+        source: TransformError,
+    },
+
+    Random {
+        key: String,
+        source: getrandom::Error,
+    },
+
+    #[error("failed to resolve variable \"{key}\": couldn't read {path}")]
+    File {
+        key: String,
+        path: String,
+        source: io::Error,
+    },
+}
+
+/// Every failure from [`VariableResolver::check_variables`].
+#[derive(Error, Debug)]
+#[error("{} variable(s) failed to resolve{}", errors.len(), describe_errors(errors))]
+pub struct VariableCheckError {
+    pub errors: Vec<VariableResolutionError>,
+}
+
+/// Describes each error on its own line, along with its sources.
+fn describe_errors(errors: &[VariableResolutionError]) -> String {
+    errors
+        .iter()
+        .map(|err| {
+            let mut description = format!("\n  - {err}");
+            let mut source = std::error::Error::source(err);
+            while let Some(err) = source {
+                description.push_str(&format!(": {err}"));
+                source = err.source();
+            }
+
+            description
+        })
+        .collect()
+}
+
+/// Extracts the `group` capture group of the first match of `pattern` in `value`.
+/// If no group is specified, the first capture group is used, or the whole match if there are no
+/// capture groups.
+fn extract_match(pattern: &str, group: Option<usize>, value: &str) -> Result<String, MatchError> {
+    let regex = Regex::new(pattern).map_err(MatchError::Regex)?;
+    let group = group.unwrap_or(if regex.captures_len() > 1 { 1 } else { 0 });
+
+    regex
+        .captures(value)
+        .and_then(|captures| captures.get(group))
+        .map(|found| found.as_str().to_string())
+        .ok_or(MatchError::NoMatch)
+}
+
+enum MatchError {
+    Regex(regex::Error),
+    NoMatch,
+}
+
+#[derive(Error, Debug)]
+pub enum TransformError {
+    #[error("invalid base64")]
+    Base64(#[from] base64::DecodeError),
+
+    #[error("decoded value is not valid UTF-8")]
+    Utf8(#[from] FromUtf8Error),
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::args::MockArgumentResolver;
-    use crate::config::VariableConfig::Prompt;
+    use crate::args::{ClapArgumentResolver, MockArgumentResolver, ASSIGNMENTS_ARG_NAME};
+    use crate::config::VariableConfig::{Prompt, ShorthandLiteral};
     use crate::config::{
-        BashCommandConfig, ExecutionConfigVariant, ExecutionVariableConfig, LiteralVariableConfig,
-        PromptConfig, PromptOptionsVariant, PromptVariableConfig, SelectOptionsConfig,
-        SelectPromptOptions, ShellCommandConfigVariant, VariableConfig,
+        ArgumentConfigVariant, ArgumentVariableConfig, BashCommandConfig, Encoding,
+        ExecutionConfigVariant, ExecutionVariableConfig, FileVariableConfig, LiteralVariableConfig,
+        PromptConfig, PromptOptionsVariant, PromptVariableConfig, RawCommandConfigVariant,
+        SelectOptionsConfig, SelectPromptOptions, ShellCommandConfigVariant, StdinConfig,
+        TextPromptOptions, Transform, Trim, VariableConfig, VariableOverrideMap,
     };
     use crate::exec::{ExitStatus, MockCommandExecutor, Output};
     use crate::prompt::MockPromptExecutor;
+    use mockall::predicate::eq;
 
     #[test]
     fn variable_resolver_resolves_shorthand_literal() {
@@ -244,6 +1013,8 @@ mod tests {
             prompt_executor: Box::new(prompt_executor),
             argument_resolver: Box::new(argument_resolver),
             dingus_options: Default::default(),
+            interactive: false,
+            placeholder_prompts: false,
         };
 
         let name = "name";
@@ -281,6 +1052,8 @@ mod tests {
             prompt_executor: Box::new(prompt_executor),
             argument_resolver: Box::new(argument_resolver),
             dingus_options: Default::default(),
+            interactive: false,
+            placeholder_prompts: false,
         };
 
         let name = "name";
@@ -292,6 +1065,10 @@ mod tests {
                 value: value.to_string(),
                 argument: None,
                 environment_variable_name: None,
+                prompt_if_missing: None,
+                transform: vec![],
+                export: true,
+                sensitive: false,
             }),
         );
 
@@ -306,6 +1083,34 @@ mod tests {
         assert_eq!(resolved_value, value);
     }
 
+    #[test]
+    fn variable_resolver_uses_assignment_over_literal() {
+        // Arrange
+        let arg_matches = clap::Command::new("greet")
+            .arg(clap::Arg::new(ASSIGNMENTS_ARG_NAME).num_args(1..))
+            .get_matches_from(vec!["greet", "name=Foo"]);
+
+        let variable_resolver = RealVariableResolver {
+            command_executor: Box::new(MockCommandExecutor::new()),
+            prompt_executor: Box::new(MockPromptExecutor::new()),
+            argument_resolver: Box::new(ClapArgumentResolver::from_arg_matches(&arg_matches)),
+            dingus_options: Default::default(),
+            interactive: false,
+            placeholder_prompts: false,
+        };
+
+        let mut variable_configs = VariableConfigMap::new();
+        variable_configs.insert("name".to_string(), ShorthandLiteral("Dingus".to_string()));
+
+        // Act
+        let resolved_variables = variable_resolver
+            .resolve_variables(&variable_configs)
+            .unwrap();
+
+        // Assert
+        assert_eq!(resolved_variables.get("name").unwrap(), "Foo");
+    }
+
     #[test]
     fn variable_resolver_resolves_execution_variable() {
         // Arrange
@@ -331,6 +1136,8 @@ mod tests {
             prompt_executor: Box::new(prompt_executor),
             argument_resolver: Box::new(argument_resolver),
             dingus_options: Default::default(),
+            interactive: false,
+            placeholder_prompts: false,
         };
 
         let name = "name";
@@ -344,8 +1151,27 @@ mod tests {
                     BashCommandConfig {
                         working_directory: None,
                         command: format!("echo \"{value}\""),
+                        run_as: None,
+                        stdout_file: None,
+                        stderr_file: None,
+                        append: false,
+                        allow_extra_args: false,
+                        banner: None,
+                        stdin: StdinConfig::Inherit,
+                        label: None,
+                        max_output_bytes: None,
+                        extra_args: vec![],
                     },
                 )),
+                encoding: Encoding::Strict,
+                trim: Trim::End,
+                prompt_if_missing: None,
+                transform: vec![],
+                export: true,
+                r#match: None,
+                group: None,
+                lazy: false,
+                sensitive: false,
             }),
         );
 
@@ -361,19 +1187,837 @@ mod tests {
     }
 
     #[test]
-    fn variable_resolver_resolves_text_prompt_variable() {
+    fn variable_resolver_trims_end_of_execution_variable_output_by_default() {
         // Arrange
-        let command_executor = MockCommandExecutor::new();
+        let stdout = "  Dingus\n";
 
-        let mut argument_resolver = MockArgumentResolver::new();
-        argument_resolver
-            .expect_get()
-            .times(0..)
-            .returning(|_| None);
+        // Act
+        let value = resolve_execution_variable_with_output(stdout, Trim::default(), vec![]);
 
-        let value = "Dingus";
-        let mut prompt_executor = MockPromptExecutor::new();
-        prompt_executor
+        // Assert
+        assert_eq!(value, "  Dingus");
+    }
+
+    #[test]
+    fn variable_resolver_trims_start_of_execution_variable_output() {
+        // Arrange
+        let stdout = "  Dingus\n";
+
+        // Act
+        let value = resolve_execution_variable_with_output(stdout, Trim::Start, vec![]);
+
+        // Assert
+        assert_eq!(value, "Dingus\n");
+    }
+
+    #[test]
+    fn variable_resolver_trims_both_ends_of_execution_variable_output() {
+        // Arrange
+        let stdout = "  Dingus\n";
+
+        // Act
+        let value = resolve_execution_variable_with_output(stdout, Trim::Both, vec![]);
+
+        // Assert
+        assert_eq!(value, "Dingus");
+    }
+
+    #[test]
+    fn variable_resolver_keeps_untrimmed_execution_variable_output() {
+        // Arrange
+        let stdout = "  Dingus\n";
+
+        // Act
+        let value = resolve_execution_variable_with_output(stdout, Trim::None, vec![]);
+
+        // Assert
+        assert_eq!(value, stdout);
+    }
+
+    #[test]
+    fn variable_resolver_applies_transforms_to_execution_variable_in_order() {
+        // Arrange
+        let stdout = "  dingus\n";
+
+        // Act
+        let value = resolve_execution_variable_with_output(
+            stdout,
+            Trim::None,
+            vec![Transform::Trim, Transform::Upper],
+        );
+
+        // Assert
+        assert_eq!(value, "DINGUS");
+    }
+
+    #[test]
+    fn apply_transforms_round_trips_base64() {
+        // Arrange
+        let value = "Hello, Dingus!";
+
+        // Act
+        let encoded = apply_transforms(value, &[Transform::Base64]).unwrap();
+        let decoded = apply_transforms(&encoded, &[Transform::Base64d]).unwrap();
+
+        // Assert
+        assert_eq!(encoded, "SGVsbG8sIERpbmd1cyE=");
+        assert_eq!(decoded, value);
+    }
+
+    #[test]
+    fn apply_transforms_fails_for_invalid_base64() {
+        // Act
+        let result = apply_transforms("not base64!", &[Transform::Base64d]);
+
+        // Assert
+        assert!(matches!(result, Err(TransformError::Base64(_))));
+    }
+
+    #[test]
+    fn random_value_generates_uuids_and_hex_strings() {
+        // Arrange
+        let uuid_config = RandomConfig {
+            kind: RandomKind::Uuid,
+            length: None,
+        };
+        let hex_config = RandomConfig {
+            kind: RandomKind::Hex,
+            length: Some(7),
+        };
+
+        // Act
+        let uuid = random_value(&uuid_config).unwrap();
+        let other_uuid = random_value(&uuid_config).unwrap();
+        let hex = random_value(&hex_config).unwrap();
+
+        // Assert
+        let uuid_regex =
+            Regex::new("^[0-9a-f]{8}-[0-9a-f]{4}-4[0-9a-f]{3}-[89ab][0-9a-f]{3}-[0-9a-f]{12}$")
+                .unwrap();
+        assert!(uuid_regex.is_match(&uuid), "invalid uuid {uuid}");
+        assert_ne!(uuid, other_uuid);
+        assert_eq!(hex.len(), 7);
+        assert!(hex.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+
+    fn resolve_execution_variable_with_output(
+        stdout: &'static str,
+        trim: Trim,
+        transform: Vec<Transform>,
+    ) -> String {
+        try_resolve_execution_variable(stdout, trim, transform, None, None).unwrap()
+    }
+
+    fn try_resolve_execution_variable(
+        stdout: &'static str,
+        trim: Trim,
+        transform: Vec<Transform>,
+        r#match: Option<String>,
+        group: Option<usize>,
+    ) -> Result<String, VariableResolutionError> {
+        let mut command_executor = MockCommandExecutor::new();
+        command_executor.expect_get_output().returning(move |_, _| {
+            Ok(Output {
+                status: ExitStatus::Success,
+                stdout: stdout.as_bytes().to_vec(),
+                stderr: vec![],
+            })
+        });
+
+        let mut argument_resolver = MockArgumentResolver::new();
+        argument_resolver
+            .expect_get()
+            .times(0..)
+            .returning(|_| None);
+
+        let variable_resolver = RealVariableResolver {
+            command_executor: Box::new(command_executor),
+            prompt_executor: Box::new(MockPromptExecutor::new()),
+            argument_resolver: Box::new(argument_resolver),
+            dingus_options: Default::default(),
+            interactive: false,
+            placeholder_prompts: false,
+        };
+
+        let name = "name";
+        let mut variable_configs = VariableConfigMap::new();
+        variable_configs.insert(
+            name.to_string(),
+            VariableConfig::Execution(ExecutionVariableConfig {
+                argument: None,
+                environment_variable_name: None,
+                execution: ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+                    BashCommandConfig {
+                        working_directory: None,
+                        command: "echo Dingus".to_string(),
+                        run_as: None,
+                        stdout_file: None,
+                        stderr_file: None,
+                        append: false,
+                        allow_extra_args: false,
+                        banner: None,
+                        stdin: StdinConfig::Inherit,
+                        label: None,
+                        max_output_bytes: None,
+                        extra_args: vec![],
+                    },
+                )),
+                encoding: Encoding::Strict,
+                trim,
+                prompt_if_missing: None,
+                transform,
+                export: true,
+                r#match,
+                group,
+                lazy: false,
+                sensitive: false,
+            }),
+        );
+
+        let resolved_variables = variable_resolver.resolve_variables(&variable_configs)?;
+        Ok(resolved_variables.get(name).unwrap().clone())
+    }
+
+    #[test]
+    fn variable_resolver_redacts_sensitive_execution_variable() {
+        // Arrange
+        let mut command_executor = MockCommandExecutor::new();
+        command_executor.expect_get_output().returning(|_, _| {
+            Ok(Output {
+                status: ExitStatus::Success,
+                stdout: b"hunter2\n".to_vec(),
+                stderr: vec![],
+            })
+        });
+
+        let mut argument_resolver = MockArgumentResolver::new();
+        argument_resolver
+            .expect_get()
+            .times(0..)
+            .returning(|_| None);
+
+        let variable_resolver = RealVariableResolver {
+            command_executor: Box::new(command_executor),
+            prompt_executor: Box::new(MockPromptExecutor::new()),
+            argument_resolver: Box::new(argument_resolver),
+            dingus_options: Default::default(),
+            interactive: false,
+            placeholder_prompts: false,
+        };
+
+        let variable_config: VariableConfig = serde_yaml::from_str(
+            "execute: vault read -field=token secret/deploy
+sensitive: true",
+        )
+        .unwrap();
+
+        let mut resolved_variables = VariableMap::new();
+        let mut sensitive_variable_names = vec![];
+
+        // Act
+        variable_resolver
+            .resolve_variable(
+                &"TOKEN".to_string(),
+                &variable_config,
+                &mut resolved_variables,
+                &mut sensitive_variable_names,
+            )
+            .unwrap();
+
+        // Assert
+        assert_eq!(resolved_variables.get("TOKEN").unwrap(), "hunter2");
+        assert_eq!(
+            redact_variables(&resolved_variables, &sensitive_variable_names),
+            VariableMap::from([("TOKEN".to_string(), "********".to_string())])
+        );
+    }
+
+    #[test]
+    fn variable_resolver_extracts_first_capture_group_from_execution_variable_output() {
+        // Arrange
+        let stdout = "v1.2.3\n";
+
+        // Act
+        let value = try_resolve_execution_variable(
+            stdout,
+            Trim::default(),
+            vec![],
+            Some(r"v(\d+\.\d+\.\d+)".to_string()),
+            None,
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!(value, "1.2.3");
+    }
+
+    #[test]
+    fn variable_resolver_extracts_capture_group_by_index_from_execution_variable_output() {
+        // Arrange
+        let stdout = "v1.2.3\n";
+
+        // Act
+        let value = try_resolve_execution_variable(
+            stdout,
+            Trim::default(),
+            vec![],
+            Some(r"v(\d+)\.(\d+)\.(\d+)".to_string()),
+            Some(2),
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!(value, "2");
+    }
+
+    #[test]
+    fn variable_resolver_fails_when_execution_variable_output_does_not_match() {
+        // Arrange
+        let stdout = "unknown\n";
+
+        // Act
+        let result = try_resolve_execution_variable(
+            stdout,
+            Trim::default(),
+            vec![],
+            Some(r"v(\d+\.\d+\.\d+)".to_string()),
+            None,
+        );
+
+        // Assert
+        assert!(matches!(
+            result,
+            Err(VariableResolutionError::NoMatch { key, .. }) if key == "name"
+        ));
+    }
+
+    fn argument_variable_with_default_command() -> VariableConfigMap {
+        let mut variable_configs = VariableConfigMap::new();
+        variable_configs.insert(
+            "branch".to_string(),
+            VariableConfig::Argument(ArgumentVariableConfig {
+                argument: ArgumentConfigVariant::Shorthand("branch".to_string()),
+                environment_variable_name: None,
+                default_from_command: Some(ExecutionConfigVariant::RawCommand(
+                    RawCommandConfigVariant::Shorthand("git branch --show-current".to_string()),
+                )),
+                trim: Trim::End,
+                transform: vec![],
+                export: true,
+                sensitive: false,
+            }),
+        );
+        variable_configs
+    }
+
+    #[test]
+    fn variable_resolver_uses_default_command_when_argument_is_missing() {
+        // Arrange
+        let mut command_executor = MockCommandExecutor::new();
+        command_executor
+            .expect_get_output()
+            .times(1)
+            .returning(|_, _| {
+                Ok(Output {
+                    status: ExitStatus::Success,
+                    stdout: "main\n".as_bytes().to_vec(),
+                    stderr: vec![],
+                })
+            });
+
+        let mut argument_resolver = MockArgumentResolver::new();
+        argument_resolver.expect_get().returning(|_| None);
+
+        let variable_resolver = RealVariableResolver {
+            command_executor: Box::new(command_executor),
+            prompt_executor: Box::new(MockPromptExecutor::new()),
+            argument_resolver: Box::new(argument_resolver),
+            dingus_options: Default::default(),
+            interactive: false,
+            placeholder_prompts: false,
+        };
+
+        // Act
+        let resolved_variables = variable_resolver
+            .resolve_variables(&argument_variable_with_default_command())
+            .unwrap();
+
+        // Assert
+        assert_eq!(resolved_variables.get("branch").unwrap(), "main");
+    }
+
+    #[test]
+    fn variable_resolver_does_not_run_default_command_when_argument_is_provided() {
+        // Arrange
+        let mut command_executor = MockCommandExecutor::new();
+        command_executor.expect_get_output().never();
+
+        let mut argument_resolver = MockArgumentResolver::new();
+        argument_resolver
+            .expect_get()
+            .returning(|_| Some("release".to_string()));
+
+        let variable_resolver = RealVariableResolver {
+            command_executor: Box::new(command_executor),
+            prompt_executor: Box::new(MockPromptExecutor::new()),
+            argument_resolver: Box::new(argument_resolver),
+            dingus_options: Default::default(),
+            interactive: false,
+            placeholder_prompts: false,
+        };
+
+        // Act
+        let resolved_variables = variable_resolver
+            .resolve_variables(&argument_variable_with_default_command())
+            .unwrap();
+
+        // Assert
+        assert_eq!(resolved_variables.get("branch").unwrap(), "release");
+    }
+
+    #[test]
+    fn variable_resolver_prompts_when_execution_variable_output_is_empty() {
+        // Arrange
+        let mut command_executor = MockCommandExecutor::new();
+        command_executor.expect_get_output().returning(|_, _| {
+            Ok(Output {
+                status: ExitStatus::Success,
+                stdout: "\n".as_bytes().to_vec(),
+                stderr: vec![],
+            })
+        });
+
+        let mut argument_resolver = MockArgumentResolver::new();
+        argument_resolver
+            .expect_get()
+            .times(0..)
+            .returning(|_| None);
+
+        let mut prompt_executor = MockPromptExecutor::new();
+        prompt_executor
+            .expect_execute()
+            .times(1)
+            .returning(|_| Ok("Prompted".to_string()));
+
+        let variable_resolver = RealVariableResolver {
+            command_executor: Box::new(command_executor),
+            prompt_executor: Box::new(prompt_executor),
+            argument_resolver: Box::new(argument_resolver),
+            dingus_options: Default::default(),
+            interactive: false,
+            placeholder_prompts: false,
+        };
+
+        let name = "name";
+        let mut variable_configs = VariableConfigMap::new();
+        variable_configs.insert(
+            name.to_string(),
+            VariableConfig::Execution(ExecutionVariableConfig {
+                argument: None,
+                environment_variable_name: None,
+                execution: ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+                    BashCommandConfig {
+                        working_directory: None,
+                        command: "cat name.txt".to_string(),
+                        run_as: None,
+                        stdout_file: None,
+                        stderr_file: None,
+                        append: false,
+                        allow_extra_args: false,
+                        banner: None,
+                        stdin: StdinConfig::Inherit,
+                        label: None,
+                        max_output_bytes: None,
+                        extra_args: vec![],
+                    },
+                )),
+                encoding: Encoding::Strict,
+                trim: Trim::End,
+                prompt_if_missing: Some(PromptConfig {
+                    message: "Enter your name".to_string(),
+                    options: Default::default(),
+                    help: None,
+                }),
+                transform: vec![],
+                export: true,
+                r#match: None,
+                group: None,
+                lazy: false,
+                sensitive: false,
+            }),
+        );
+
+        // Act
+        let resolved_variables = variable_resolver.resolve_variables(&variable_configs);
+
+        // Assert
+        let binding = resolved_variables.unwrap();
+        let resolved_value = binding.get(name).unwrap().as_str();
+        assert_eq!(resolved_value, "Prompted");
+    }
+
+    #[test]
+    fn variable_resolver_does_not_prompt_when_execution_variable_has_output() {
+        // Arrange
+        let mut command_executor = MockCommandExecutor::new();
+        command_executor.expect_get_output().returning(|_, _| {
+            Ok(Output {
+                status: ExitStatus::Success,
+                stdout: "Dingus\n".as_bytes().to_vec(),
+                stderr: vec![],
+            })
+        });
+
+        let mut argument_resolver = MockArgumentResolver::new();
+        argument_resolver
+            .expect_get()
+            .times(0..)
+            .returning(|_| None);
+
+        let mut prompt_executor = MockPromptExecutor::new();
+        prompt_executor
+            .expect_execute()
+            .times(0)
+            .returning(|_| Ok("Prompted".to_string()));
+
+        let variable_resolver = RealVariableResolver {
+            command_executor: Box::new(command_executor),
+            prompt_executor: Box::new(prompt_executor),
+            argument_resolver: Box::new(argument_resolver),
+            dingus_options: Default::default(),
+            interactive: false,
+            placeholder_prompts: false,
+        };
+
+        let name = "name";
+        let mut variable_configs = VariableConfigMap::new();
+        variable_configs.insert(
+            name.to_string(),
+            VariableConfig::Execution(ExecutionVariableConfig {
+                argument: None,
+                environment_variable_name: None,
+                execution: ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+                    BashCommandConfig {
+                        working_directory: None,
+                        command: "cat name.txt".to_string(),
+                        run_as: None,
+                        stdout_file: None,
+                        stderr_file: None,
+                        append: false,
+                        allow_extra_args: false,
+                        banner: None,
+                        stdin: StdinConfig::Inherit,
+                        label: None,
+                        max_output_bytes: None,
+                        extra_args: vec![],
+                    },
+                )),
+                encoding: Encoding::Strict,
+                trim: Trim::End,
+                prompt_if_missing: Some(PromptConfig {
+                    message: "Enter your name".to_string(),
+                    options: Default::default(),
+                    help: None,
+                }),
+                transform: vec![],
+                export: true,
+                r#match: None,
+                group: None,
+                lazy: false,
+                sensitive: false,
+            }),
+        );
+
+        // Act
+        let resolved_variables = variable_resolver.resolve_variables(&variable_configs);
+
+        // Assert
+        let binding = resolved_variables.unwrap();
+        let resolved_value = binding.get(name).unwrap().as_str();
+        assert_eq!(resolved_value, "Dingus");
+    }
+
+    #[test]
+    fn variable_resolver_prompts_for_every_variable_when_interactive() {
+        // Arrange
+        let mut command_executor = MockCommandExecutor::new();
+        command_executor.expect_get_output().returning(|_, _| {
+            Ok(Output {
+                status: ExitStatus::Success,
+                stdout: "1.0.0\n".as_bytes().to_vec(),
+                stderr: vec![],
+            })
+        });
+
+        let mut argument_resolver = MockArgumentResolver::new();
+        argument_resolver
+            .expect_get()
+            .times(0..)
+            .returning(|_| None);
+
+        // Each prompt returns its default value, or the message for the prompt variable.
+        let mut prompt_executor = MockPromptExecutor::new();
+        prompt_executor
+            .expect_execute()
+            .times(3)
+            .returning(|prompt_config| match &prompt_config.options {
+                PromptOptionsVariant::Text(TextPromptOptions {
+                    default: Some(default),
+                    ..
+                }) => Ok(default.clone()),
+                _ => Ok(prompt_config.message.clone()),
+            });
+
+        let variable_resolver = RealVariableResolver {
+            command_executor: Box::new(command_executor),
+            prompt_executor: Box::new(prompt_executor),
+            argument_resolver: Box::new(argument_resolver),
+            dingus_options: Default::default(),
+            interactive: true,
+            placeholder_prompts: false,
+        };
+
+        let mut variable_configs = VariableConfigMap::new();
+        variable_configs.insert(
+            "name".to_string(),
+            VariableConfig::ShorthandLiteral("Dingus".to_string()),
+        );
+        variable_configs.insert(
+            "version".to_string(),
+            VariableConfig::Execution(ExecutionVariableConfig {
+                argument: None,
+                environment_variable_name: None,
+                execution: ExecutionConfigVariant::RawCommand(RawCommandConfigVariant::Shorthand(
+                    "cat VERSION".to_string(),
+                )),
+                encoding: Encoding::Strict,
+                trim: Trim::End,
+                prompt_if_missing: None,
+                transform: vec![],
+                export: true,
+                r#match: None,
+                group: None,
+                lazy: false,
+                sensitive: false,
+            }),
+        );
+        variable_configs.insert(
+            "greeting".to_string(),
+            Prompt(PromptVariableConfig {
+                argument: None,
+                environment_variable_name: None,
+                prompt: PromptConfig {
+                    message: "Hello".to_string(),
+                    options: Default::default(),
+                    help: None,
+                },
+                transform: vec![],
+                export: true,
+                sensitive: false,
+            }),
+        );
+
+        // Act
+        let resolved_variables = variable_resolver
+            .resolve_variables(&variable_configs)
+            .unwrap();
+
+        // Assert
+        assert_eq!(resolved_variables.get("name").unwrap(), "Dingus");
+        assert_eq!(resolved_variables.get("version").unwrap(), "1.0.0");
+        assert_eq!(resolved_variables.get("greeting").unwrap(), "Hello");
+    }
+
+    #[test]
+    fn variable_resolver_resolves_text_prompt_variable() {
+        // Arrange
+        let command_executor = MockCommandExecutor::new();
+
+        let mut argument_resolver = MockArgumentResolver::new();
+        argument_resolver
+            .expect_get()
+            .times(0..)
+            .returning(|_| None);
+
+        let value = "Dingus";
+        let mut prompt_executor = MockPromptExecutor::new();
+        prompt_executor
+            .expect_execute()
+            .once()
+            .returning(|_| Ok(value.to_string()));
+
+        let variable_resolver = RealVariableResolver {
+            command_executor: Box::new(command_executor),
+            prompt_executor: Box::new(prompt_executor),
+            argument_resolver: Box::new(argument_resolver),
+            dingus_options: Default::default(),
+            interactive: false,
+            placeholder_prompts: false,
+        };
+
+        let name = "name";
+        let mut variable_configs = VariableConfigMap::new();
+        variable_configs.insert(
+            name.to_string(),
+            Prompt(PromptVariableConfig {
+                argument: None,
+                environment_variable_name: None,
+                prompt: PromptConfig {
+                    message: "Enter your name".to_string(),
+                    options: Default::default(),
+                    help: None,
+                },
+                transform: vec![],
+                export: true,
+                sensitive: false,
+            }),
+        );
+
+        // Act
+        let resolved_variables = variable_resolver.resolve_variables(&variable_configs);
+
+        // Assert
+        assert!(!resolved_variables.is_err());
+
+        let binding = resolved_variables.unwrap().clone();
+        let resolved_value = binding.get(name).unwrap().as_str();
+        assert_eq!(resolved_value, value);
+    }
+
+    #[test]
+    fn variable_resolver_uses_placeholders_instead_of_prompting() {
+        // Arrange
+        let command_executor = MockCommandExecutor::new();
+
+        let mut argument_resolver = MockArgumentResolver::new();
+        argument_resolver
+            .expect_get()
+            .times(0..)
+            .returning(|_| None);
+
+        let mut prompt_executor = MockPromptExecutor::new();
+        prompt_executor.expect_execute().never();
+
+        let variable_resolver = RealVariableResolver {
+            command_executor: Box::new(command_executor),
+            prompt_executor: Box::new(prompt_executor),
+            argument_resolver: Box::new(argument_resolver),
+            dingus_options: Default::default(),
+            interactive: false,
+            placeholder_prompts: true,
+        };
+
+        let prompt_config = PromptConfig {
+            message: "Enter your name".to_string(),
+            options: Default::default(),
+            help: None,
+        };
+        let mut variable_configs = VariableConfigMap::new();
+        variable_configs.insert(
+            "name".to_string(),
+            Prompt(PromptVariableConfig {
+                argument: None,
+                environment_variable_name: None,
+                prompt: prompt_config.clone(),
+                transform: vec![],
+                export: true,
+                sensitive: false,
+            }),
+        );
+        variable_configs.insert(
+            "token".to_string(),
+            VariableConfig::Literal(LiteralVariableConfig {
+                argument: None,
+                environment_variable_name: None,
+                value: String::new(),
+                prompt_if_missing: Some(prompt_config),
+                transform: vec![],
+                export: true,
+                sensitive: false,
+            }),
+        );
+
+        // Act
+        let resolved_variables = variable_resolver
+            .resolve_variables(&variable_configs)
+            .unwrap();
+
+        // Assert
+        assert_eq!(resolved_variables.get("name").unwrap(), "<name>");
+        assert_eq!(resolved_variables.get("token").unwrap(), "<token>");
+    }
+
+    #[test]
+    fn variable_resolver_resolves_file_variable() {
+        // Arrange
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("VERSION"), "1.2.3\n").unwrap();
+
+        let mut argument_resolver = MockArgumentResolver::new();
+        argument_resolver
+            .expect_get()
+            .times(0..)
+            .returning(|_| None);
+
+        let variable_resolver = RealVariableResolver {
+            command_executor: Box::new(MockCommandExecutor::new()),
+            prompt_executor: Box::new(MockPromptExecutor::new()),
+            argument_resolver: Box::new(argument_resolver),
+            dingus_options: Default::default(),
+            interactive: false,
+            placeholder_prompts: false,
+        };
+
+        let file_variable = |path: &str| {
+            VariableConfig::File(FileVariableConfig {
+                argument: None,
+                environment_variable_name: None,
+                path: path.to_string(),
+                trim: Trim::End,
+                transform: vec![],
+                export: true,
+                sensitive: false,
+            })
+        };
+        let mut variable_configs = VariableConfigMap::new();
+        variable_configs.insert(
+            "dir".to_string(),
+            ShorthandLiteral(temp_dir.path().display().to_string()),
+        );
+        variable_configs.insert("version".to_string(), file_variable("$dir/VERSION"));
+
+        let mut missing_variable_configs = variable_configs.clone();
+        missing_variable_configs.insert("notes".to_string(), file_variable("$dir/NOTES"));
+
+        // Act
+        let resolved_variables = variable_resolver
+            .resolve_variables(&variable_configs)
+            .unwrap();
+        let missing_result = variable_resolver.resolve_variables(&missing_variable_configs);
+
+        // Assert
+        assert_eq!(resolved_variables.get("version").unwrap(), "1.2.3");
+        assert!(matches!(
+            missing_result,
+            Err(VariableResolutionError::File { key, path, .. })
+                if key == "notes" && path.ends_with("NOTES")
+        ));
+    }
+
+    #[test]
+    fn variable_resolver_resolves_select_prompt_variable() {
+        // Arrange
+        let command_executor = MockCommandExecutor::new();
+
+        let mut argument_resolver = MockArgumentResolver::new();
+        argument_resolver
+            .expect_get()
+            .times(0..)
+            .returning(|_| None);
+
+        let value = "Dingus";
+        let mut prompt_executor = MockPromptExecutor::new();
+        prompt_executor
             .expect_execute()
             .once()
             .returning(|_| Ok(value.to_string()));
@@ -383,6 +2027,8 @@ mod tests {
             prompt_executor: Box::new(prompt_executor),
             argument_resolver: Box::new(argument_resolver),
             dingus_options: Default::default(),
+            interactive: false,
+            placeholder_prompts: false,
         };
 
         let name = "name";
@@ -393,9 +2039,20 @@ mod tests {
                 argument: None,
                 environment_variable_name: None,
                 prompt: PromptConfig {
-                    message: "Enter your name".to_string(),
-                    options: Default::default(),
+                    message: "Select your name".to_string(),
+                    options: PromptOptionsVariant::Select(SelectPromptOptions {
+                        options: SelectOptionsConfig::Literal(vec![
+                            "Alice".to_string(),
+                            "Bob".to_string(),
+                            "Charlie".to_string(),
+                            "Dingus".to_string(),
+                        ]),
+                    }),
+                    help: None,
                 },
+                transform: vec![],
+                export: true,
+                sensitive: false,
             }),
         );
 
@@ -411,7 +2068,68 @@ mod tests {
     }
 
     #[test]
-    fn variable_resolver_resolves_select_prompt_variable() {
+    fn variable_resolver_checks_select_prompt_variable_arguments_against_options() {
+        // Arrange
+        let variable_resolver = |argument: &'static str| {
+            let mut argument_resolver = MockArgumentResolver::new();
+            argument_resolver
+                .expect_get()
+                .times(0..)
+                .returning(move |_| Some(argument.to_string()));
+
+            let mut prompt_executor = MockPromptExecutor::new();
+            prompt_executor.expect_execute().never();
+
+            RealVariableResolver {
+                command_executor: Box::new(MockCommandExecutor::new()),
+                prompt_executor: Box::new(prompt_executor),
+                argument_resolver: Box::new(argument_resolver),
+                dingus_options: Default::default(),
+                interactive: false,
+                placeholder_prompts: false,
+            }
+        };
+
+        let mut variable_configs = VariableConfigMap::new();
+        variable_configs.insert(
+            "environment".to_string(),
+            Prompt(PromptVariableConfig {
+                argument: None,
+                environment_variable_name: None,
+                prompt: PromptConfig {
+                    message: "Which environment?".to_string(),
+                    options: PromptOptionsVariant::Select(SelectPromptOptions {
+                        options: SelectOptionsConfig::Literal(vec![
+                            "Development".to_string(),
+                            "Production".to_string(),
+                        ]),
+                    }),
+                    help: None,
+                },
+                transform: vec![],
+                export: true,
+                sensitive: false,
+            }),
+        );
+
+        // Act
+        let valid_result = variable_resolver("Production").resolve_variables(&variable_configs);
+        let invalid_result = variable_resolver("Staging").resolve_variables(&variable_configs);
+
+        // Assert
+        assert_eq!(
+            valid_result.unwrap().get("environment").unwrap(),
+            "Production"
+        );
+        assert!(matches!(
+            invalid_result,
+            Err(VariableResolutionError::InvalidOption { value, options, .. })
+                if value == "Staging" && options == "Development, Production"
+        ));
+    }
+
+    #[test]
+    fn variable_resolver_substitutes_resolved_variables_into_prompts() {
         // Arrange
         let command_executor = MockCommandExecutor::new();
 
@@ -421,38 +2139,51 @@ mod tests {
             .times(0..)
             .returning(|_| None);
 
-        let value = "Dingus";
         let mut prompt_executor = MockPromptExecutor::new();
         prompt_executor
             .expect_execute()
             .once()
-            .returning(|_| Ok(value.to_string()));
+            .with(eq(PromptConfig {
+                message: "Deploy to prod?".to_string(),
+                options: PromptOptionsVariant::Select(SelectPromptOptions {
+                    options: SelectOptionsConfig::Literal(vec![
+                        "Deploy prod".to_string(),
+                        "Cancel".to_string(),
+                    ]),
+                }),
+                help: None,
+            }))
+            .returning(|_| Ok("Deploy prod".to_string()));
 
         let variable_resolver = RealVariableResolver {
             command_executor: Box::new(command_executor),
             prompt_executor: Box::new(prompt_executor),
             argument_resolver: Box::new(argument_resolver),
             dingus_options: Default::default(),
+            interactive: false,
+            placeholder_prompts: false,
         };
 
-        let name = "name";
         let mut variable_configs = VariableConfigMap::new();
+        variable_configs.insert("env".to_string(), ShorthandLiteral("prod".to_string()));
         variable_configs.insert(
-            name.to_string(),
+            "confirmation".to_string(),
             Prompt(PromptVariableConfig {
                 argument: None,
                 environment_variable_name: None,
                 prompt: PromptConfig {
-                    message: "Select your name".to_string(),
+                    message: "Deploy to $env?".to_string(),
                     options: PromptOptionsVariant::Select(SelectPromptOptions {
                         options: SelectOptionsConfig::Literal(vec![
-                            "Alice".to_string(),
-                            "Bob".to_string(),
-                            "Charlie".to_string(),
-                            "Dingus".to_string(),
+                            "Deploy $env".to_string(),
+                            "Cancel".to_string(),
                         ]),
                     }),
+                    help: None,
                 },
+                transform: vec![],
+                export: true,
+                sensitive: false,
             }),
         );
 
@@ -460,11 +2191,58 @@ mod tests {
         let resolved_variables = variable_resolver.resolve_variables(&variable_configs);
 
         // Assert
-        assert!(!resolved_variables.is_err());
+        assert!(resolved_variables.is_ok());
+        assert_eq!(
+            resolved_variables.unwrap().get("confirmation"),
+            Some(&"Deploy prod".to_string())
+        );
+    }
 
-        let binding = resolved_variables.unwrap().clone();
-        let resolved_value = binding.get(name).unwrap().as_str();
-        assert_eq!(resolved_value, value);
+    #[test]
+    fn variable_resolver_passes_substituted_help_to_prompts() {
+        // Arrange
+        let command_executor = MockCommandExecutor::new();
+
+        let mut argument_resolver = MockArgumentResolver::new();
+        argument_resolver.expect_get().returning(|_| None);
+
+        let mut prompt_executor = MockPromptExecutor::new();
+        prompt_executor
+            .expect_execute()
+            .once()
+            .with(eq(PromptConfig {
+                message: "Which version?".to_string(),
+                help: Some("The latest version of Dingus is 1.2.3".to_string()),
+                options: PromptOptionsVariant::default(),
+            }))
+            .returning(|_| Ok("1.2.3".to_string()));
+
+        let variable_resolver = RealVariableResolver {
+            command_executor: Box::new(command_executor),
+            prompt_executor: Box::new(prompt_executor),
+            argument_resolver: Box::new(argument_resolver),
+            dingus_options: Default::default(),
+            interactive: false,
+            placeholder_prompts: false,
+        };
+
+        let variable_configs: VariableConfigMap = serde_yaml::from_str(
+            "name: Dingus
+latest: 1.2.3
+version:
+  prompt:
+    message: Which version?
+    help: The latest version of $name is $latest",
+        )
+        .unwrap();
+
+        // Act
+        let resolved_variables = variable_resolver
+            .resolve_variables(&variable_configs)
+            .unwrap();
+
+        // Assert
+        assert_eq!(resolved_variables.get("version").unwrap(), "1.2.3");
     }
 
     #[test]
@@ -483,6 +2261,8 @@ mod tests {
             prompt_executor: Box::new(prompt_executor),
             argument_resolver: Box::new(argument_resolver),
             dingus_options: Default::default(),
+            interactive: false,
+            placeholder_prompts: false,
         };
 
         let name = "name";
@@ -495,6 +2275,10 @@ mod tests {
                 value: value.to_string(),
                 argument: None,
                 environment_variable_name: Some(env_var_name.to_string()),
+                prompt_if_missing: None,
+                transform: vec![],
+                export: true,
+                sensitive: false,
             }),
         );
 
@@ -509,6 +2293,338 @@ mod tests {
         assert_eq!(resolved_value, value);
     }
 
+    fn lazy_variable_configs() -> VariableConfigMap {
+        serde_yaml::from_str(
+            "name: Dingus
+version:
+  execute: git describe --tags
+  export: false
+  lazy: true
+tag:
+  execute: echo v$version
+  export: false
+  lazy: true
+commit:
+  execute: git rev-parse HEAD
+  export: false
+  lazy: true",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn remove_unreferenced_lazy_variables_keeps_referenced_variables() {
+        // Arrange
+        let mut variable_configs = lazy_variable_configs();
+
+        // Act
+        remove_unreferenced_lazy_variables(&mut variable_configs, "action: echo ${tag}");
+
+        // Assert
+        let mut keys: Vec<&String> = variable_configs.keys().collect();
+        keys.sort();
+        assert_eq!(keys, vec!["name", "tag", "version"]);
+    }
+
+    #[test]
+    fn remove_unreferenced_lazy_variables_keeps_exported_variables() {
+        // Arrange
+        let mut variable_configs: VariableConfigMap = serde_yaml::from_str(
+            "token:
+  execute: ./fetch-token.sh
+  lazy: true
+branch:
+  execute: git branch --show-current
+  lazy: true
+  export: false",
+        )
+        .unwrap();
+
+        // Act
+        remove_unreferenced_lazy_variables(&mut variable_configs, "action: ./deploy.sh");
+
+        // Assert
+        let keys: Vec<&String> = variable_configs.keys().collect();
+        assert_eq!(keys, vec!["token"]);
+    }
+
+    #[test]
+    fn remove_unreferenced_lazy_variables_keeps_variables_referenced_by_tera_templates() {
+        // Arrange
+        let mut variable_configs = lazy_variable_configs();
+
+        // Act
+        remove_unreferenced_lazy_variables(
+            &mut variable_configs,
+            "write_file: VERSION
+content: \"{% if version %}{{ version | upper }}{% endif %}\"
+template: tera",
+        );
+
+        // Assert
+        let mut keys: Vec<&String> = variable_configs.keys().collect();
+        keys.sort();
+        assert_eq!(keys, vec!["name", "version"]);
+    }
+
+    #[test]
+    fn variable_resolver_does_not_run_unreferenced_lazy_execution_variable() {
+        // Arrange
+        let mut command_executor = MockCommandExecutor::new();
+        command_executor
+            .expect_get_output()
+            .with(
+                eq(ExecutionConfigVariant::RawCommand(
+                    RawCommandConfigVariant::Shorthand("git describe --tags".to_string()),
+                )),
+                eq(VariableMap::from([(
+                    "name".to_string(),
+                    "Dingus".to_string(),
+                )])),
+            )
+            .times(1)
+            .returning(|_, _| {
+                Ok(Output {
+                    status: ExitStatus::Success,
+                    stdout: "1.2.3\n".as_bytes().to_vec(),
+                    stderr: vec![],
+                })
+            });
+
+        let mut argument_resolver = MockArgumentResolver::new();
+        argument_resolver.expect_get().returning(|_| None);
+
+        let variable_resolver = RealVariableResolver {
+            command_executor: Box::new(command_executor),
+            prompt_executor: Box::new(MockPromptExecutor::new()),
+            argument_resolver: Box::new(argument_resolver),
+            dingus_options: Default::default(),
+            interactive: false,
+            placeholder_prompts: false,
+        };
+
+        let mut variable_configs = lazy_variable_configs();
+        remove_unreferenced_lazy_variables(&mut variable_configs, "action: echo $version");
+
+        // Act
+        let resolved_variables = variable_resolver
+            .resolve_variables(&variable_configs)
+            .unwrap();
+
+        // Assert
+        assert_eq!(resolved_variables.get("version").unwrap(), "1.2.3");
+        assert_eq!(resolved_variables.get("commit"), None);
+    }
+
+    #[test]
+    fn variable_resolver_check_variables_reports_every_failure() {
+        // Arrange
+        let mut command_executor = MockCommandExecutor::new();
+        command_executor.expect_get_output().returning(|_, _| {
+            Ok(Output {
+                status: ExitStatus::Fail(1),
+                stdout: vec![],
+                stderr: vec![],
+            })
+        });
+
+        let mut prompt_executor = MockPromptExecutor::new();
+        prompt_executor.expect_execute().returning(|prompt_config| {
+            Err(PromptError::NoDefault {
+                message: prompt_config.message.clone(),
+            })
+        });
+
+        let mut argument_resolver = MockArgumentResolver::new();
+        argument_resolver.expect_get().returning(|_| None);
+
+        let variable_resolver = RealVariableResolver {
+            command_executor: Box::new(command_executor),
+            prompt_executor: Box::new(prompt_executor),
+            argument_resolver: Box::new(argument_resolver),
+            dingus_options: Default::default(),
+            interactive: false,
+            placeholder_prompts: false,
+        };
+
+        let variable_configs: VariableConfigMap = serde_yaml::from_str(
+            "version:
+  execute: git describe --tags
+name: Dingus
+environment:
+  prompt:
+    message: Which environment?
+    options:
+      - Development
+      - Production",
+        )
+        .unwrap();
+
+        // Act
+        let result = variable_resolver.check_variables(&variable_configs);
+
+        // Assert
+        let err = result.unwrap_err();
+        assert_eq!(err.errors.len(), 2);
+        assert!(matches!(
+            &err.errors[0],
+            VariableResolutionError::ExitStatus { key, .. } if key == "version"
+        ));
+        assert!(matches!(
+            &err.errors[1],
+            VariableResolutionError::Prompt { key, .. } if key == "environment"
+        ));
+        assert_eq!(
+            err.to_string(),
+            "2 variable(s) failed to resolve
+  - failed to resolve variable \"version\": process exited with code 1
+  - failed to resolve variable \"environment\": \"Which environment?\" has no default value to use without prompting"
+        );
+    }
+
+    #[test]
+    fn apply_overrides_replaces_values_and_keeps_arguments() {
+        // Arrange
+        let argument = ArgumentConfigVariant::Shorthand("name".to_string());
+
+        let mut variable_configs = VariableConfigMap::new();
+        variable_configs.insert(
+            "greeting".to_string(),
+            VariableConfig::ShorthandLiteral("Hello".to_string()),
+        );
+        variable_configs.insert(
+            "name".to_string(),
+            VariableConfig::Prompt(PromptVariableConfig {
+                argument: Some(argument.clone()),
+                environment_variable_name: Some("NAME".to_string()),
+                prompt: PromptConfig {
+                    message: "What's your name?".to_string(),
+                    options: Default::default(),
+                    help: None,
+                },
+                transform: vec![],
+                export: true,
+                sensitive: false,
+            }),
+        );
+
+        let mut overrides = VariableOverrideMap::new();
+        overrides.insert("greeting".to_string(), "G'day".to_string());
+        overrides.insert("name".to_string(), "Dingus".to_string());
+        overrides.insert("unknown".to_string(), "ignored".to_string());
+
+        // Act
+        apply_overrides(&mut variable_configs, &overrides);
+
+        // Assert
+        assert_eq!(variable_configs.len(), 2);
+        assert_eq!(
+            variable_configs.get("greeting"),
+            Some(&VariableConfig::ShorthandLiteral("G'day".to_string()))
+        );
+        assert_eq!(
+            variable_configs.get("name"),
+            Some(&VariableConfig::Literal(LiteralVariableConfig {
+                argument: Some(argument),
+                environment_variable_name: Some("NAME".to_string()),
+                value: "Dingus".to_string(),
+                prompt_if_missing: None,
+                transform: vec![],
+                export: true,
+                sensitive: false,
+            }))
+        );
+    }
+
+    #[test]
+    fn apply_overrides_keeps_variables_unexported() {
+        // Arrange
+        let mut variable_configs = VariableConfigMap::new();
+        variable_configs.insert(
+            "secret".to_string(),
+            VariableConfig::Literal(LiteralVariableConfig {
+                argument: None,
+                environment_variable_name: None,
+                value: "base".to_string(),
+                prompt_if_missing: None,
+                transform: vec![],
+                export: false,
+                sensitive: false,
+            }),
+        );
+
+        let mut overrides = VariableOverrideMap::new();
+        overrides.insert("secret".to_string(), "overridden".to_string());
+
+        // Act
+        apply_overrides(&mut variable_configs, &overrides);
+
+        // Assert
+        assert_eq!(
+            unexported_variable_names(&variable_configs),
+            vec!["secret".to_string()]
+        );
+    }
+
+    #[test]
+    fn apply_overrides_keeps_sensitive_prompts_sensitive() {
+        // Arrange
+        let mut variable_configs = VariableConfigMap::new();
+        variable_configs.insert(
+            "password".to_string(),
+            VariableConfig::Prompt(PromptVariableConfig {
+                argument: None,
+                environment_variable_name: None,
+                prompt: PromptConfig {
+                    message: "Password?".to_string(),
+                    options: PromptOptionsVariant::Text(TextPromptOptions {
+                        multi_line: false,
+                        sensitive: true,
+                        min_length: None,
+                        max_length: None,
+                        default: None,
+                    }),
+                    help: None,
+                },
+                transform: vec![],
+                export: true,
+                sensitive: false,
+            }),
+        );
+
+        let mut overrides = VariableOverrideMap::new();
+        overrides.insert("password".to_string(), "hunter2".to_string());
+
+        // Act
+        apply_overrides(&mut variable_configs, &overrides);
+
+        // Assert
+        assert_eq!(
+            sensitive_variable_names(&variable_configs),
+            vec!["password".to_string()]
+        );
+    }
+
+    #[test]
+    fn format_exports_quotes_values() {
+        // Arrange
+        let mut variables = VariableMap::new();
+        variables.insert("name".to_string(), "Dingus".to_string());
+        variables.insert("greeting".to_string(), "it's $HOME".to_string());
+
+        // Act
+        let exports = format_exports(&variables);
+
+        // Assert
+        assert_eq!(
+            exports,
+            vec![
+                "export greeting='it'\\''s $HOME'".to_string(),
+                "export name='Dingus'".to_string(),
+            ]
+        );
+    }
+
     #[test]
     fn substitute_variables_substitutes_variables() {
         // Arrange
@@ -554,6 +2670,48 @@ mod tests {
         assert_eq!(result, "Hello, Dingus Bingus!")
     }
 
+    #[test]
+    fn render_template_simple_substitutes_variables() {
+        // Arrange
+        let mut variables = VariableMap::new();
+        variables.insert("name".to_string(), "Dingus".to_string());
+
+        // Act
+        let result = render_template("Hello, $name!", &TemplateEngine::Simple, &variables);
+
+        // Assert
+        assert_eq!(result.unwrap(), "Hello, Dingus!");
+    }
+
+    #[test]
+    #[cfg(feature = "tera")]
+    fn render_template_tera_supports_loops() {
+        // Arrange
+        let mut variables = VariableMap::new();
+        variables.insert("services".to_string(), "api web".to_string());
+
+        // Act
+        let template =
+            "{% for service in services | split(pat=\" \") %}deploy {{ service }};{% endfor %}";
+        let result = render_template(template, &TemplateEngine::Tera, &variables);
+
+        // Assert
+        assert_eq!(result.unwrap(), "deploy api;deploy web;");
+    }
+
+    #[test]
+    #[cfg(feature = "tera")]
+    fn render_template_tera_fails_for_invalid_template() {
+        // Arrange
+        let variables = VariableMap::new();
+
+        // Act
+        let result = render_template("{% if %}", &TemplateEngine::Tera, &variables);
+
+        // Assert
+        assert!(matches!(result, Err(TemplateError::Tera(_))));
+    }
+
     #[test]
     fn substitute_variables_allows_adjacent() {
         // Arrange
@@ -0,0 +1,176 @@
+use crate::config::WatchOptions;
+use colored::Colorize;
+use notify::{Event, RecursiveMode, Watcher};
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::time::Duration;
+use thiserror::Error;
+
+enum WatchEvent {
+    Changed,
+    Interrupted,
+}
+
+/// Runs `run`, then runs it again whenever a file under `directory` changes until Ctrl-C is
+/// pressed.
+/// Changes are debounced so that a burst of changes only results in a single re-run, and any
+/// errors returned by `run` are printed rather than ending the watch.
+/// Changes made while `run` is running are ignored, so that a command writing files under
+/// `directory` doesn't re-run itself forever.
+pub fn watch<F>(directory: &Path, options: &WatchOptions, mut run: F) -> Result<(), WatchError>
+where
+    F: FnMut() -> anyhow::Result<()>,
+{
+    let (sender, receiver) = channel();
+
+    let interrupt_sender = sender.clone();
+    ctrlc::set_handler(move || {
+        let _ = interrupt_sender.send(WatchEvent::Interrupted);
+    })?;
+
+    let watched_directory = directory.to_path_buf();
+    let ignore = options.ignore.clone();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+        let Ok(event) = result else {
+            return;
+        };
+
+        // Reading files shouldn't trigger a re-run, otherwise the command would trigger itself
+        if event.kind.is_access() {
+            return;
+        }
+
+        if event
+            .paths
+            .iter()
+            .any(|path| !is_ignored(path, &watched_directory, &ignore))
+        {
+            let _ = sender.send(WatchEvent::Changed);
+        }
+    })?;
+    watcher.watch(directory, RecursiveMode::Recursive)?;
+
+    let debounce = Duration::from_millis(options.debounce);
+    loop {
+        if let Err(err) = run() {
+            eprintln!("{}", format!("error: {err:#}").red());
+        }
+
+        // Events can arrive shortly after the change that caused them, so wait for them to settle
+        if let WatchEvent::Interrupted = drain_events(&receiver, debounce) {
+            return Ok(());
+        }
+
+        let message = format!("Watching {} for changes...", directory.display());
+        println!("{}", message.yellow());
+
+        match receiver.recv() {
+            Ok(WatchEvent::Changed) => {}
+            Ok(WatchEvent::Interrupted) | Err(_) => return Ok(()),
+        }
+
+        // Wait for the changes to settle before running again
+        if let WatchEvent::Interrupted = drain_events(&receiver, debounce) {
+            return Ok(());
+        }
+    }
+}
+
+/// Discards [`WatchEvent::Changed`] events until none have been received for `debounce`.
+/// Returns [`WatchEvent::Interrupted`] if watching should stop instead.
+fn drain_events(receiver: &Receiver<WatchEvent>, debounce: Duration) -> WatchEvent {
+    loop {
+        match receiver.recv_timeout(debounce) {
+            Ok(WatchEvent::Changed) => continue,
+            Err(RecvTimeoutError::Timeout) => return WatchEvent::Changed,
+            Ok(WatchEvent::Interrupted) | Err(RecvTimeoutError::Disconnected) => {
+                return WatchEvent::Interrupted
+            }
+        }
+    }
+}
+
+/// Returns `true` if any component of `path`, relative to `directory`, is in the `ignore` list.
+fn is_ignored(path: &Path, directory: &Path, ignore: &[String]) -> bool {
+    let relative_path = path.strip_prefix(directory).unwrap_or(path);
+    relative_path.components().any(|component| {
+        ignore
+            .iter()
+            .any(|ignored| component.as_os_str() == ignored.as_str())
+    })
+}
+
+#[derive(Error, Debug)]
+pub enum WatchError {
+    #[error("failed to watch for changes")]
+    Notify(#[from] notify::Error),
+
+    #[error("failed to set the Ctrl-C handler")]
+    Interrupt(#[from] ctrlc::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drain_events_discards_pending_changes() {
+        // Arrange
+        let (sender, receiver) = channel();
+        sender.send(WatchEvent::Changed).unwrap();
+        sender.send(WatchEvent::Changed).unwrap();
+
+        // Act
+        let event = drain_events(&receiver, Duration::from_millis(10));
+
+        // Assert
+        assert!(matches!(event, WatchEvent::Changed));
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn drain_events_stops_when_interrupted() {
+        // Arrange
+        let (sender, receiver) = channel();
+        sender.send(WatchEvent::Changed).unwrap();
+        sender.send(WatchEvent::Interrupted).unwrap();
+
+        // Act
+        let event = drain_events(&receiver, Duration::from_millis(10));
+
+        // Assert
+        assert!(matches!(event, WatchEvent::Interrupted));
+    }
+
+    #[test]
+    fn is_ignored_matches_any_component() {
+        // Arrange
+        let directory = Path::new("/project");
+        let ignore = vec!["target".to_string(), ".git".to_string()];
+
+        // Act
+        let ignored = is_ignored(
+            Path::new("/project/target/debug/dingus"),
+            directory,
+            &ignore,
+        );
+        let not_ignored = is_ignored(Path::new("/project/src/main.rs"), directory, &ignore);
+
+        // Assert
+        assert!(ignored);
+        assert!(!not_ignored);
+    }
+
+    #[test]
+    fn is_ignored_only_checks_inside_directory() {
+        // Arrange
+        let directory = Path::new("/target/project");
+        let ignore = vec!["target".to_string()];
+
+        // Act
+        let ignored = is_ignored(Path::new("/target/project/src/main.rs"), directory, &ignore);
+
+        // Assert
+        assert!(!ignored);
+    }
+}
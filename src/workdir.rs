@@ -0,0 +1,186 @@
+use crate::config::{DingusOptions, Source, WorkdirAnchor};
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+use std::{env, io};
+
+/// Changes the current working directory to the directory containing the config file, unless
+/// [`DingusOptions::change_directory`] is disabled.
+pub fn enter_config_directory(source: &Source, options: &DingusOptions) -> io::Result<()> {
+    if !options.change_directory {
+        return Ok(());
+    }
+
+    if let Source::File(config_file_path) = source {
+        if let Some(parent_directory) = config_file_path.parent() {
+            env::set_current_dir(parent_directory)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Changes the current working directory according to the provided [`WorkdirAnchor`].
+/// This assumes the current working directory is already the directory containing the config file,
+/// or the directory dingus was started in if [`DingusOptions::change_directory`] is disabled.
+pub fn apply_workdir_anchor(workdir_anchor: &WorkdirAnchor) -> io::Result<()> {
+    match workdir_anchor {
+        WorkdirAnchor::ConfigDir => Ok(()),
+
+        WorkdirAnchor::GitRoot => {
+            let current_dir = env::current_dir()?;
+            match find_git_root(&current_dir) {
+                Some(git_root) => env::set_current_dir(git_root),
+                None => {
+                    let message = format!(
+                        "warning: could not find a git repository, falling back to {}",
+                        current_dir.display()
+                    );
+                    eprintln!("{}", message.yellow());
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+/// Replaces a leading `~` in the provided path with the user's home directory.
+/// Paths that don't start with `~`, or `~user` paths, are returned as-is.
+pub fn expand_tilde(path: &str) -> String {
+    let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"));
+    expand_tilde_with_home(path, home.as_deref().map(Path::new))
+}
+
+fn expand_tilde_with_home(path: &str, home: Option<&Path>) -> String {
+    let Some(home) = home else {
+        return path.to_string();
+    };
+
+    if path == "~" {
+        return home.display().to_string();
+    }
+
+    match path.strip_prefix("~/").or_else(|| path.strip_prefix("~\\")) {
+        Some(relative_path) => home.join(relative_path).display().to_string(),
+        None => path.to_string(),
+    }
+}
+
+/// Finds the nearest directory, starting from `directory`, that contains a `.git` entry.
+fn find_git_root(directory: &Path) -> Option<PathBuf> {
+    directory
+        .ancestors()
+        .find(|ancestor| ancestor.join(".git").exists())
+        .map(|ancestor| ancestor.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{
+        BashCommandConfig, ExecutionConfigVariant, ShellCommandConfigVariant, StdinConfig,
+    };
+    use crate::exec::create_command_executor;
+    use crate::variables::VariableMap;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    #[cfg(not(windows))]
+    fn enter_config_directory_keeps_current_directory_when_disabled() {
+        // Arrange
+        let original_dir = env::current_dir().unwrap();
+        let temp_dir = TempDir::new().unwrap();
+        let source = Source::File(temp_dir.path().join("dingus.yaml"));
+        let options = DingusOptions {
+            change_directory: false,
+            ..Default::default()
+        };
+
+        let pwd = ExecutionConfigVariant::ShellCommand(ShellCommandConfigVariant::Bash(
+            BashCommandConfig {
+                working_directory: None,
+                command: "pwd".to_string(),
+                run_as: None,
+                stdout_file: None,
+                stderr_file: None,
+                append: false,
+                allow_extra_args: false,
+                banner: None,
+                stdin: StdinConfig::Inherit,
+                label: None,
+                max_output_bytes: None,
+                extra_args: vec![],
+            },
+        ));
+
+        // Act
+        enter_config_directory(&source, &options).unwrap();
+        let output = create_command_executor(&options, &None, &[])
+            .get_output(&pwd, &VariableMap::new())
+            .unwrap();
+
+        // Assert
+        let working_dir = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(working_dir.trim_end(), original_dir.to_str().unwrap());
+    }
+
+    #[test]
+    fn expand_tilde_expands_home_directory() {
+        // Arrange
+        let home = Path::new("/home/dingus");
+
+        // Act
+        let expanded = expand_tilde_with_home("~/foo", Some(home));
+        let expanded_home = expand_tilde_with_home("~", Some(home));
+        let other_user = expand_tilde_with_home("~godzilla/foo", Some(home));
+        let relative = expand_tilde_with_home("./~/foo", Some(home));
+
+        // Assert
+        assert_eq!(expanded, "/home/dingus/foo");
+        assert_eq!(expanded_home, "/home/dingus");
+        assert_eq!(other_user, "~godzilla/foo");
+        assert_eq!(relative, "./~/foo");
+    }
+
+    #[test]
+    fn expand_tilde_keeps_path_without_home() {
+        // Act
+        let expanded = expand_tilde_with_home("~/foo", None);
+
+        // Assert
+        assert_eq!(expanded, "~/foo");
+    }
+
+    #[test]
+    fn find_git_root_finds_nearest_ancestor() {
+        // Arrange
+        let temp_dir = TempDir::new().unwrap();
+        let nested_dir = temp_dir.path().join("services").join("api");
+        fs::create_dir_all(&nested_dir).unwrap();
+        fs::create_dir(temp_dir.path().join(".git")).unwrap();
+
+        // Act
+        let git_root = find_git_root(&nested_dir);
+
+        // Assert
+        assert_eq!(git_root, Some(temp_dir.path().to_path_buf()));
+    }
+
+    #[test]
+    fn find_git_root_accepts_git_file() {
+        // Arrange
+        // Worktrees and submodules use a `.git` file instead of a directory.
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join(".git"),
+            "gitdir: ../.git/worktrees/dingus",
+        )
+        .unwrap();
+
+        // Act
+        let git_root = find_git_root(temp_dir.path());
+
+        // Assert
+        assert_eq!(git_root, Some(temp_dir.path().to_path_buf()));
+    }
+}